@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, make_array};
+use arrow::datatypes::{Field, Schema as ArrowSchema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema, from_ffi};
+use arrow::record_batch::RecordBatch;
+use polars::prelude::{CompatLevel, Schema};
+use polars_arrow::record_batch::RecordBatch as PolarsRecordBatch;
+
+use crate::RpslError;
+use crate::schema::SchemaPolarsBuilder;
+
+/// The arrow-rs schema of the batches read with `schema`
+pub(crate) fn arrow_schema(schema: &Schema) -> Result<SchemaRef, RpslError> {
+    let empty = SchemaPolarsBuilder::new(schema)?
+        .build()
+        .rechunk_to_record_batch(CompatLevel::newest());
+    Ok(to_arrow_rs(empty)?.schema())
+}
+
+/// Convert a Polars Arrow batch into an arrow-rs batch through the Arrow C data
+/// interface, without copying the buffers
+pub(crate) fn to_arrow_rs(batch: PolarsRecordBatch) -> Result<RecordBatch, ArrowError> {
+    let fields: Vec<_> = batch.schema().iter_values().cloned().collect();
+    let mut arrow_fields = Vec::with_capacity(fields.len());
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(fields.len());
+
+    for (field, array) in fields.iter().zip(batch.into_arrays()) {
+        let schema = polars_arrow::ffi::export_field_to_c(field);
+        let array = polars_arrow::ffi::export_array_to_c(array);
+
+        // SAFETY: both sides are `#[repr(C)]` definitions of the same C data
+        // interface structs, and ownership of the exported data moves with them
+        let schema: FFI_ArrowSchema = unsafe { std::mem::transmute(schema) };
+        let array: FFI_ArrowArray = unsafe { std::mem::transmute(array) };
+
+        arrow_fields.push(Field::try_from(&schema)?);
+        columns.push(make_array(unsafe { from_ffi(array, &schema)? }));
+    }
+
+    RecordBatch::try_new(Arc::new(ArrowSchema::new(arrow_fields)), columns)
+}
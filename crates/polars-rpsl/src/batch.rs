@@ -0,0 +1,116 @@
+use std::io::BufRead;
+
+use polars::prelude::{CompatLevel, Schema};
+use polars_arrow::record_batch::RecordBatch;
+use rpsl_parser::{FirstError, RpslParser};
+
+use crate::RpslError;
+use crate::schema::SchemaPolarsBuilder;
+
+/// Pull-based reader yielding Arrow record batches of (up to) a fixed number of objects
+///
+/// Objects are parsed incrementally as batches are requested, so memory use is
+/// bounded by the batch size rather than the size of the input.
+pub struct RpslBatchReader<R> {
+    reader: R,
+    parser: RpslParser<FirstError<SchemaPolarsBuilder>>,
+    batch_size: usize,
+    done: bool,
+}
+
+impl<R: BufRead> RpslBatchReader<R> {
+    pub fn new(reader: R, schema: &Schema, batch_size: usize) -> Result<Self, RpslError> {
+        let builder = SchemaPolarsBuilder::new(schema)?;
+        Ok(Self {
+            reader,
+            parser: RpslParser::new(FirstError::new(builder)),
+            batch_size: batch_size.max(1),
+            done: false,
+        })
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>, RpslError> {
+        let mut rows = 0;
+        while rows < self.batch_size {
+            if !self.parser.parse_object(&mut self.reader)? {
+                self.done = true;
+                break;
+            }
+            rows += 1;
+        }
+
+        let callbacks = self.parser.callbacks_mut();
+        if let Some(err) = callbacks.take_error() {
+            return Err(err);
+        }
+
+        if rows == 0 {
+            return Ok(None);
+        }
+
+        let df = callbacks.inner_mut().drain();
+        Ok(Some(df.rechunk_to_record_batch(CompatLevel::newest())))
+    }
+}
+
+impl<R: BufRead> Iterator for RpslBatchReader<R> {
+    type Item = Result<RecordBatch, RpslError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_batch() {
+            Ok(batch) => batch.map(Ok),
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::*;
+
+    use super::*;
+    use crate::read_rpsl_with_schema_from_reader;
+
+    const INPUT: &[u8] = b"route: 192.0.2.0/24\norigin: AS1\nmnt-by: A\nmnt-by: B\n\n\
+        route: 198.51.100.0/24\ndescr: first\n  second\norigin: AS2\n\n\
+        aut-num: AS1\n\n\
+        route: 203.0.113.0/24\r\norigin: AS3\r\n\r\n\
+        route: 192.0.2.128/25\nmnt-by: C\n";
+
+    #[test]
+    fn test_batches_match_single_read() {
+        let schema = Schema::from_iter([
+            Field::new("route".into(), DataType::String),
+            Field::new("descr".into(), DataType::String),
+            Field::new("origin".into(), DataType::String),
+            Field::new("mnt-by".into(), DataType::List(Box::new(DataType::String))),
+        ]);
+        let df = read_rpsl_with_schema_from_reader(INPUT, &schema).unwrap();
+        assert_eq!(df.height(), 5);
+
+        for batch_size in 1..=6 {
+            let batches = RpslBatchReader::new(INPUT, &schema, batch_size)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(batches.len(), df.height().div_ceil(batch_size));
+
+            let mut offset = 0;
+            for batch in batches {
+                let expected = df
+                    .slice(offset as i64, batch.height())
+                    .rechunk_to_record_batch(CompatLevel::newest());
+                assert_eq!(batch, expected, "batch size {batch_size}, offset {offset}");
+                offset += batch.height();
+            }
+            assert_eq!(offset, df.height());
+        }
+    }
+}
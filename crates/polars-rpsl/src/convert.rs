@@ -0,0 +1,308 @@
+use std::io::{BufRead, Write};
+
+use rpsl_parser::{FirstError, RpslParser, RpslWriter, TryCallbacks};
+
+use crate::RpslError;
+
+/// An output format that objects can be converted to
+///
+/// Streaming formats write each object as it ends, while others buffer objects and
+/// write everything in [`FormatWriter::finish`], which is called once after the last
+/// object.
+pub trait FormatWriter {
+    /// Called when a new object starts
+    fn start_object(&mut self) -> Result<(), RpslError>;
+
+    /// Called for each attribute with its name and value
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> Result<(), RpslError>;
+
+    /// Called when an object ends
+    fn end_object(&mut self) -> Result<(), RpslError>;
+
+    /// Called after the last object to write any buffered output and flush
+    fn finish(&mut self) -> Result<(), RpslError>;
+}
+
+/// Writes RPSL text, see [`RpslWriter`]
+impl<W: Write> FormatWriter for RpslWriter<W> {
+    fn start_object(&mut self) -> Result<(), RpslError> {
+        Ok(TryCallbacks::start_object(self)?)
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> Result<(), RpslError> {
+        Ok(TryCallbacks::attribute(self, name, value)?)
+    }
+
+    fn end_object(&mut self) -> Result<(), RpslError> {
+        Ok(TryCallbacks::end_object(self)?)
+    }
+
+    fn finish(&mut self) -> Result<(), RpslError> {
+        Ok(self.flush()?)
+    }
+}
+
+/// Writes one JSON object per line, in the layout of the schema-less `attributes`
+/// column: `{"attributes":[{"name":"route","value":"192.0.2.0/24"},...]}`
+pub struct NdjsonWriter<W> {
+    writer: W,
+    first_attribute: bool,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            first_attribute: true,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> FormatWriter for NdjsonWriter<W> {
+    fn start_object(&mut self) -> Result<(), RpslError> {
+        self.first_attribute = true;
+        self.writer.write_all(b"{\"attributes\":[")?;
+        Ok(())
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> Result<(), RpslError> {
+        if !self.first_attribute {
+            self.writer.write_all(b",")?;
+        }
+        self.first_attribute = false;
+
+        self.writer.write_all(b"{\"name\":")?;
+        write_json_string(&mut self.writer, name)?;
+        self.writer.write_all(b",\"value\":")?;
+        write_json_string(&mut self.writer, value)?;
+        self.writer.write_all(b"}")?;
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), RpslError> {
+        self.writer.write_all(b"]}\n")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), RpslError> {
+        Ok(self.writer.flush()?)
+    }
+}
+
+/// Write bytes as a JSON string, replacing invalid UTF-8
+fn write_json_string<W: Write>(writer: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+    writer.write_all(b"\"")?;
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '"' => writer.write_all(b"\\\"")?,
+            '\\' => writer.write_all(b"\\\\")?,
+            '\n' => writer.write_all(b"\\n")?,
+            '\t' => writer.write_all(b"\\t")?,
+            c if c < ' ' => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{c}")?,
+        }
+    }
+    writer.write_all(b"\"")
+}
+
+/// Callbacks that fan each object out to several [`FormatWriter`]s, in order
+pub struct MultiWriter<'a> {
+    writers: Vec<Box<dyn FormatWriter + 'a>>,
+}
+
+impl<'a> MultiWriter<'a> {
+    pub fn new(writers: Vec<Box<dyn FormatWriter + 'a>>) -> Self {
+        Self { writers }
+    }
+
+    /// Finish every writer, even if an earlier one fails, returning the first error
+    pub fn finish(&mut self) -> Result<(), RpslError> {
+        let mut result = Ok(());
+        for writer in &mut self.writers {
+            let finished = writer.finish();
+            if result.is_ok() {
+                result = finished;
+            }
+        }
+        result
+    }
+}
+
+impl TryCallbacks for MultiWriter<'_> {
+    type Error = RpslError;
+
+    fn start_object(&mut self) -> Result<(), RpslError> {
+        self.writers.iter_mut().try_for_each(|w| w.start_object())
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> Result<(), RpslError> {
+        self.writers
+            .iter_mut()
+            .try_for_each(|w| w.attribute(name, value))
+    }
+
+    fn end_object(&mut self) -> Result<(), RpslError> {
+        self.writers.iter_mut().try_for_each(|w| w.end_object())
+    }
+}
+
+/// Convert RPSL data to several output formats in one pass
+///
+/// Every writer is finished at the end, including when parsing or another writer
+/// fails, so that streaming outputs are flushed up to the point of failure.
+pub fn convert_rpsl_multi<'a, R: BufRead>(
+    reader: R,
+    writers: Vec<Box<dyn FormatWriter + 'a>>,
+) -> Result<(), RpslError> {
+    let mut parser = RpslParser::new(FirstError::new(MultiWriter::new(writers)));
+    let parsed = parser.parse(reader);
+
+    let callbacks = parser.callbacks_mut();
+    let written = match callbacks.take_error() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    };
+    let finished = callbacks.inner_mut().finish();
+
+    parsed?;
+    written?;
+    finished
+}
+
+#[cfg(feature = "parquet")]
+mod parquet {
+    use std::io::Write;
+
+    use polars::prelude::{ParquetWriter, Schema};
+    use rpsl_parser::{Callbacks, TryCallbacks};
+
+    use super::FormatWriter;
+    use crate::RpslError;
+    use crate::schema::SchemaPolarsBuilder;
+    use crate::schemaless::PolarsBuilder;
+
+    enum Builder {
+        Schemaless(PolarsBuilder),
+        Schema(SchemaPolarsBuilder),
+    }
+
+    /// Buffers all objects and writes them as a Parquet file when finished
+    pub struct ParquetFormatWriter<W> {
+        writer: Option<W>,
+        builder: Builder,
+    }
+
+    impl<W: Write> ParquetFormatWriter<W> {
+        /// Write the schema-less `attributes` column
+        pub fn new(writer: W) -> Self {
+            Self {
+                writer: Some(writer),
+                builder: Builder::Schemaless(PolarsBuilder::new()),
+            }
+        }
+
+        /// Write one column per schema field
+        pub fn with_schema(writer: W, schema: &Schema) -> Result<Self, RpslError> {
+            Ok(Self {
+                writer: Some(writer),
+                builder: Builder::Schema(SchemaPolarsBuilder::new(schema)?),
+            })
+        }
+    }
+
+    impl<W: Write> FormatWriter for ParquetFormatWriter<W> {
+        fn start_object(&mut self) -> Result<(), RpslError> {
+            match &mut self.builder {
+                Builder::Schemaless(builder) => Callbacks::start_object(builder),
+                Builder::Schema(builder) => TryCallbacks::start_object(builder)?,
+            }
+            Ok(())
+        }
+
+        fn attribute(&mut self, name: &[u8], value: &[u8]) -> Result<(), RpslError> {
+            match &mut self.builder {
+                Builder::Schemaless(builder) => Callbacks::attribute(builder, name, value),
+                Builder::Schema(builder) => TryCallbacks::attribute(builder, name, value)?,
+            }
+            Ok(())
+        }
+
+        fn end_object(&mut self) -> Result<(), RpslError> {
+            match &mut self.builder {
+                Builder::Schemaless(builder) => Callbacks::end_object(builder),
+                Builder::Schema(builder) => TryCallbacks::end_object(builder)?,
+            }
+            Ok(())
+        }
+
+        fn finish(&mut self) -> Result<(), RpslError> {
+            let Some(writer) = self.writer.take() else {
+                return Ok(());
+            };
+
+            let mut df = match &mut self.builder {
+                Builder::Schemaless(builder) => builder.drain(),
+                Builder::Schema(builder) => builder.drain(),
+            };
+            ParquetWriter::new(writer).finish(&mut df)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "parquet")]
+pub use parquet::ParquetFormatWriter;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_rpsl_multi() {
+        let input = b"route: 192.0.2.0/24\ndescr: \"quoted\" \\ back\n\naut-num: AS1\n";
+
+        let mut rpsl = Vec::new();
+        let mut ndjson = Vec::new();
+        convert_rpsl_multi(
+            &input[..],
+            vec![
+                Box::new(RpslWriter::new(&mut rpsl)),
+                Box::new(NdjsonWriter::new(&mut ndjson)),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(rpsl).unwrap(),
+            "route: 192.0.2.0/24\ndescr: \"quoted\" \\ back\n\naut-num: AS1\n"
+        );
+        assert_eq!(
+            String::from_utf8(ndjson).unwrap(),
+            concat!(
+                r#"{"attributes":[{"name":"route","value":"192.0.2.0/24"},"#,
+                r#"{"name":"descr","value":"\"quoted\" \\ back"}]}"#,
+                "\n",
+                r#"{"attributes":[{"name":"aut-num","value":"AS1"}]}"#,
+                "\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_convert_rpsl_multi_error() {
+        let input = b"route: 192.0.2.0/24\n\nthis line would fail to parse\n";
+
+        let mut ndjson = Vec::new();
+        let result = convert_rpsl_multi(&input[..], vec![Box::new(NdjsonWriter::new(&mut ndjson))]);
+
+        assert!(result.is_err());
+        assert_eq!(
+            String::from_utf8(ndjson).unwrap(),
+            "{\"attributes\":[{\"name\":\"route\",\"value\":\"192.0.2.0/24\"}]}\n"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::BufRead;
+use std::path::Path;
+
+use indexmap::IndexMap;
+use rpsl_parser::Callbacks;
+
+use crate::object_columns::ObjectHasher;
+use crate::{ReadOptions, RpslError, input, parse_with_options};
+
+/// Identity of an object across snapshots: its class (lowercased) and primary key,
+/// i.e. the name and value of its first attribute
+pub type ObjectKey = (String, String);
+
+/// Differences between two snapshots of RPSL data, see [`diff_rpsl_from_paths`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RpslDiff {
+    /// Objects only in the new snapshot, in its order
+    pub added: Vec<ObjectKey>,
+
+    /// Objects only in the old snapshot, in its order
+    pub removed: Vec<ObjectKey>,
+
+    /// Objects in both snapshots whose content hash differs, in the new
+    /// snapshot's order
+    pub modified: Vec<ObjectKey>,
+}
+
+impl RpslDiff {
+    /// Whether the snapshots hold the same objects
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Callbacks mapping the key of each object to its content hash
+struct KeyHashes {
+    hasher: ObjectHasher,
+    key: Option<ObjectKey>,
+    hashes: IndexMap<ObjectKey, u64>,
+}
+
+impl Callbacks for KeyHashes {
+    fn start_object(&mut self) {
+        self.hasher.start_object();
+        self.key = None;
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        if self.key.is_none() {
+            self.key = Some((
+                String::from_utf8_lossy(name).to_ascii_lowercase(),
+                String::from_utf8_lossy(value).trim().to_string(),
+            ));
+        }
+        self.hasher.attribute(name, value);
+    }
+
+    fn end_object(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.hashes.insert(key, self.hasher.finish());
+        }
+    }
+}
+
+fn key_hashes<R: BufRead>(
+    reader: R,
+    options: &ReadOptions,
+) -> Result<IndexMap<ObjectKey, u64>, RpslError> {
+    let callbacks = KeyHashes {
+        hasher: ObjectHasher::new(options.hash_order),
+        key: None,
+        hashes: IndexMap::new(),
+    };
+    Ok(parse_with_options(reader, callbacks, options)?.hashes)
+}
+
+/// Compare two snapshots of RPSL data by object key and content hash
+///
+/// Objects are matched by class and primary key, and count as modified when the
+/// hash of their attributes (as for [`ReadOptions::hash_column`], with
+/// [`ReadOptions::hash_order`]) differs. When a snapshot has several objects with
+/// the same key, the last one is compared. Only keys and hashes are kept, so
+/// memory use grows with the number of objects rather than their size. Both
+/// snapshots are read with `options`, e.g. to only compare some `classes`.
+pub fn diff_rpsl_from_readers<R1: BufRead, R2: BufRead>(
+    old: R1,
+    new: R2,
+    options: &ReadOptions,
+) -> Result<RpslDiff, RpslError> {
+    let old = key_hashes(old, options)?;
+    let new = key_hashes(new, options)?;
+
+    let mut diff = RpslDiff::default();
+    for (key, hash) in &new {
+        match old.get(key) {
+            None => diff.added.push(key.clone()),
+            Some(old_hash) if old_hash != hash => diff.modified.push(key.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.removed = old
+        .into_keys()
+        .filter(|key| !new.contains_key(key))
+        .collect();
+    Ok(diff)
+}
+
+/// Compare two RPSL files, see [`diff_rpsl_from_readers`]. Gzip-compressed files
+/// (`.gz`) are decompressed transparently.
+pub fn diff_rpsl_from_paths<P1: AsRef<Path>, P2: AsRef<Path>>(
+    old: P1,
+    new: P2,
+    options: &ReadOptions,
+) -> Result<RpslDiff, RpslError> {
+    let old = input::open_path(old.as_ref(), options)?;
+    let new = input::open_path(new.as_ref(), options)?;
+    diff_rpsl_from_readers(old, new, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(class: &str, value: &str) -> ObjectKey {
+        (class.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_diff_rpsl() {
+        let old = b"route: 192.0.2.0/24\norigin: AS1\n\n\
+            route: 198.51.100.0/24\norigin: AS1\n\n\
+            aut-num: AS1\n";
+        let new = b"Route: 198.51.100.0/24\norigin: AS2\n\n\
+            route:  192.0.2.0/24\norigin: AS1\n\n\
+            route: 203.0.113.0/24\norigin: AS1\n";
+        let options = ReadOptions::default();
+
+        assert_eq!(
+            diff_rpsl_from_readers(&old[..], &new[..], &options).unwrap(),
+            RpslDiff {
+                added: vec![key("route", "203.0.113.0/24")],
+                removed: vec![key("aut-num", "AS1")],
+                modified: vec![key("route", "198.51.100.0/24")],
+            }
+        );
+        assert!(
+            diff_rpsl_from_readers(&old[..], &old[..], &options)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use polars::prelude::{DataType, Schema};
+use rpsl_parser::Callbacks;
+
+use crate::{ReadOptions, RpslError, parse_with_options};
+
+/// Approximate memory footprint of reading RPSL data with a schema, see
+/// [`estimate_rpsl_memory_from_reader`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// Number of objects, i.e. rows
+    pub objects: u64,
+
+    /// Estimated bytes of each schema column, in schema order
+    pub columns: Vec<(String, u64)>,
+}
+
+impl MemoryEstimate {
+    /// Estimated bytes of all columns together
+    pub fn total_bytes(&self) -> u64 {
+        self.columns.iter().map(|(_, bytes)| bytes).sum()
+    }
+}
+
+/// Value bytes and count of one schema column
+#[derive(Default, Clone, Copy)]
+struct ColumnSize {
+    bytes: u64,
+    values: u64,
+}
+
+/// Callbacks summing value sizes per schema column, without copying values
+struct SizeScanner {
+    indices: HashMap<Vec<u8>, usize>,
+    sizes: Vec<ColumnSize>,
+    objects: u64,
+}
+
+impl Callbacks for SizeScanner {
+    fn start_object(&mut self) {}
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        if let Some(&index) = self.indices.get(name) {
+            let size = &mut self.sizes[index];
+            size.bytes += value.len() as u64;
+            size.values += 1;
+        }
+    }
+
+    fn end_object(&mut self) {
+        self.objects += 1;
+    }
+}
+
+/// Estimate the memory a schema-based read of RPSL data would take, by scanning
+/// attribute names and value sizes without building any columns
+///
+/// The estimate counts value bytes, 64-bit offsets and validity bits of the
+/// Arrow arrays, and ignores allocator overhead and spare capacity, so the actual
+/// footprint is usually somewhat higher. Peak memory while building is higher
+/// still, as each object's values are briefly held as owned strings.
+pub fn estimate_rpsl_memory_from_reader<R: BufRead>(
+    reader: R,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<MemoryEstimate, RpslError> {
+    let mut indices = HashMap::new();
+    let mut lists = Vec::new();
+    for (index, (name, dtype)) in schema.iter().enumerate() {
+        let list = match dtype {
+            DataType::String => false,
+            DataType::List(inner) if matches!(inner.as_ref(), DataType::String) => true,
+            _ => {
+                return Err(RpslError::UnsupportedType {
+                    column: name.to_string(),
+                    dtype: format!("{:?}", dtype),
+                });
+            }
+        };
+        indices.insert(name.as_bytes().to_vec(), index);
+        lists.push(list);
+    }
+
+    let scanner = SizeScanner {
+        sizes: vec![ColumnSize::default(); indices.len()],
+        indices,
+        objects: 0,
+    };
+    let scanner = parse_with_options(reader, scanner, options)?;
+
+    let rows = scanner.objects;
+    let columns = schema
+        .iter_names()
+        .zip(scanner.sizes)
+        .zip(lists)
+        .map(|((name, size), list)| {
+            // Offsets per row and a validity bit per row, plus the list's inner
+            // offsets per value
+            let mut bytes = size.bytes + (rows + 1) * 8 + rows.div_ceil(8);
+            if list {
+                bytes += (size.values + 1) * 8;
+            }
+            (name.to_string(), bytes)
+        })
+        .collect();
+
+    Ok(MemoryEstimate {
+        objects: rows,
+        columns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::Field;
+
+    use super::*;
+
+    #[test]
+    fn test_estimate_rpsl_memory() {
+        let input = b"route: 192.0.2.0/24\nmnt-by: A\nmnt-by: B\n\n\
+            aut-num: AS1\n\n\
+            route: 198.51.100.0/24\nmnt-by: C\n";
+        let schema = Schema::from_iter([
+            Field::new("route".into(), DataType::String),
+            Field::new("mnt-by".into(), DataType::List(Box::new(DataType::String))),
+        ]);
+        let estimate =
+            estimate_rpsl_memory_from_reader(&input[..], &schema, &ReadOptions::default()).unwrap();
+
+        // Value bytes, then 4 row offsets and a byte of validity bits for 3 rows,
+        // then 4 inner offsets for the 3 list values
+        assert_eq!(estimate.objects, 3);
+        assert_eq!(
+            estimate.columns,
+            [
+                ("route".to_string(), 27 + 4 * 8 + 1),
+                ("mnt-by".to_string(), 3 + 4 * 8 + 1 + 4 * 8)
+            ]
+        );
+        assert_eq!(estimate.total_bytes(), 60 + 68);
+
+        let schema = Schema::from_iter([Field::new("route".into(), DataType::Int64)]);
+        assert!(matches!(
+            estimate_rpsl_memory_from_reader(&input[..], &schema, &ReadOptions::default()),
+            Err(RpslError::UnsupportedType { .. })
+        ));
+    }
+}
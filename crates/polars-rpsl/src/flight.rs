@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+
+use arrow_flight::encode::{FlightDataEncoder, FlightDataEncoderBuilder};
+use arrow_flight::error::FlightError;
+use futures::stream;
+use polars::prelude::Schema;
+use tokio::sync::mpsc;
+
+use crate::arrow_rs::{arrow_schema, to_arrow_rs};
+use crate::{ReadOptions, RpslBatchReader, RpslError, input};
+
+/// Stream an RPSL file as Arrow Flight data, e.g. as the response of a Flight
+/// service's `do_get`
+///
+/// The file is read with [`RpslBatchReader`] as the stream is consumed, producing
+/// batches of up to `batch_size` objects with one column per schema field. The
+/// schema is sent first, so an empty file still gives a valid stream.
+/// Gzip-compressed files (`.gz`) are decompressed transparently. Parsing runs on a
+/// blocking task, so this must be called from within a Tokio runtime.
+pub fn flight_stream_from_path(
+    path: impl Into<PathBuf>,
+    schema: &Schema,
+    batch_size: usize,
+) -> Result<FlightDataEncoder, RpslError> {
+    let arrow_schema = arrow_schema(schema)?;
+    let (path, schema) = (path.into(), schema.clone());
+    let (tx, rx) = mpsc::channel(2);
+
+    // Parsing is blocking I/O and CPU work, so it runs off the async runtime
+    tokio::task::spawn_blocking(move || {
+        let batches = input::open_path(&path, &ReadOptions::default())
+            .map_err(RpslError::from)
+            .and_then(|reader| RpslBatchReader::new(reader, &schema, batch_size));
+        let batches = match batches {
+            Ok(batches) => batches,
+            Err(err) => {
+                let _ = tx.blocking_send(Err(external(err)));
+                return;
+            }
+        };
+
+        for batch in batches {
+            let batch = batch
+                .map_err(external)
+                .and_then(|batch| Ok(to_arrow_rs(batch)?));
+            let failed = batch.is_err();
+            // Stop when the consumer is gone, e.g. because the client disconnected
+            if tx.blocking_send(batch).is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let batches = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|batch| (batch, rx))
+    });
+    Ok(FlightDataEncoderBuilder::new()
+        .with_schema(arrow_schema)
+        .build(batches))
+}
+
+fn external(err: RpslError) -> FlightError {
+    FlightError::ExternalError(Box::new(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::AsArray;
+    use arrow::compute::cast;
+    use arrow::datatypes::DataType as ArrowDataType;
+    use arrow_flight::decode::FlightRecordBatchStream;
+    use futures::TryStreamExt;
+    use polars::prelude::{DataType, Field};
+
+    use super::*;
+
+    #[test]
+    fn test_flight_stream() {
+        let path = std::env::temp_dir().join(format!("rpsl-flight-{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "route: 192.0.2.0/24\n\n\
+            route: 198.51.100.0/24\n\n\
+            route: 203.0.113.0/24\n",
+        )
+        .unwrap();
+        let schema = Schema::from_iter([Field::new("route".into(), DataType::String)]);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = runtime
+            .block_on(async {
+                let stream = flight_stream_from_path(&path, &schema, 2)?;
+                Ok::<_, Box<dyn std::error::Error>>(
+                    FlightRecordBatchStream::new_from_flight_data(stream)
+                        .try_collect()
+                        .await?,
+                )
+            })
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let routes: Vec<Vec<_>> = batches
+            .iter()
+            .map(|batch| {
+                let routes = cast(batch.column(0), &ArrowDataType::Utf8).unwrap();
+                let routes = routes.as_string::<i32>();
+                routes
+                    .iter()
+                    .map(|route| route.map(str::to_string))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            routes,
+            [
+                vec![
+                    Some("192.0.2.0/24".to_string()),
+                    Some("198.51.100.0/24".to_string())
+                ],
+                vec![Some("203.0.113.0/24".to_string())]
+            ]
+        );
+    }
+}
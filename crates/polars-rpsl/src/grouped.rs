@@ -0,0 +1,164 @@
+use indexmap::IndexMap;
+use polars::{
+    frame::DataFrame,
+    prelude::{ArrowField, LargeListArray, Series},
+};
+use polars_arrow::{
+    array::{Array, MutableUtf8Array, StructArray, Utf8Array},
+    datatypes::ArrowDataType,
+    offset::OffsetsBuffer,
+};
+use rpsl_parser::Callbacks;
+
+/// The values of one attribute name, as a list per object
+struct GroupedField {
+    values: MutableUtf8Array<i64>,
+    offsets: Vec<i64>,
+}
+
+impl GroupedField {
+    /// Create a field first seen in object `row`, with empty lists for the
+    /// objects before it
+    fn new(row: usize) -> Self {
+        Self {
+            values: MutableUtf8Array::new(),
+            offsets: vec![0; row + 1],
+        }
+    }
+
+    fn into_array(self) -> Box<dyn Array> {
+        let values: Utf8Array<i64> = self.values.into();
+        let offsets = unsafe { OffsetsBuffer::new_unchecked(self.offsets.into()) };
+        Box::new(LargeListArray::new(
+            list_dtype(),
+            offsets,
+            Box::new(values),
+            None,
+        ))
+    }
+}
+
+fn list_dtype() -> ArrowDataType {
+    ArrowDataType::LargeList(Box::new(ArrowField::new(
+        "item".into(),
+        ArrowDataType::LargeUtf8,
+        true,
+    )))
+}
+
+/// Callbacks that build a DataFrame with a single `attributes` Struct column,
+/// with one List(String) field per attribute name holding its values in each
+/// object
+///
+/// Fields are added as attribute names are first seen, in that order, and are
+/// empty lists for objects without the attribute. Names are matched exactly.
+pub struct GroupedPolarsBuilder {
+    fields: IndexMap<String, GroupedField>,
+    rows: usize,
+}
+
+impl Default for GroupedPolarsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GroupedPolarsBuilder {
+    pub fn new() -> Self {
+        Self {
+            fields: IndexMap::new(),
+            rows: 0,
+        }
+    }
+
+    pub fn build(self) -> DataFrame {
+        let (names, fields): (Vec<_>, Vec<_>) = self.fields.into_iter().unzip();
+        let struct_fields = names
+            .into_iter()
+            .map(|name| ArrowField::new(name.into(), list_dtype(), true))
+            .collect();
+        let values = fields.into_iter().map(GroupedField::into_array).collect();
+
+        let struct_array = StructArray::new(
+            ArrowDataType::Struct(struct_fields),
+            self.rows,
+            values,
+            None,
+        );
+        let series = Series::from_arrow("attributes".into(), Box::new(struct_array))
+            .expect("Failed to create struct series");
+
+        DataFrame::new(vec![series.into()]).expect("Failed to create DataFrame")
+    }
+}
+
+impl Callbacks for GroupedPolarsBuilder {
+    fn start_object(&mut self) {}
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        let name = String::from_utf8_lossy(name);
+        if !self.fields.contains_key(name.as_ref()) {
+            self.fields
+                .insert(name.to_string(), GroupedField::new(self.rows));
+        }
+        let field = self.fields.get_mut(name.as_ref()).unwrap();
+        field
+            .values
+            .push(Some(String::from_utf8_lossy(value).as_ref()));
+    }
+
+    fn end_object(&mut self) {
+        for field in self.fields.values_mut() {
+            field.offsets.push(field.values.len() as i64);
+        }
+        self.rows += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rpsl_parser::RpslParser;
+
+    use super::*;
+
+    #[test]
+    fn test_grouped() {
+        let input = b"route: 192.0.2.0/24\nmnt-by: A\nmnt-by: B\n\n\
+            aut-num: AS1\n\n\
+            route: 198.51.100.0/24\nmnt-by: C\n";
+        let mut parser = RpslParser::new(GroupedPolarsBuilder::new());
+        parser.parse(&input[..]).unwrap();
+        let df = parser.into_callbacks().build();
+
+        assert_eq!(df.height(), 3);
+        let attributes = df.column("attributes").unwrap().struct_().unwrap();
+        let field = |name: &str| -> Vec<Vec<String>> {
+            let field = attributes.field_by_name(name).unwrap();
+            field
+                .list()
+                .unwrap()
+                .into_iter()
+                .map(|values| {
+                    let values = values.unwrap();
+                    let values = values.str().unwrap();
+                    values.into_iter().map(|v| v.unwrap().to_string()).collect()
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            attributes
+                .fields_as_series()
+                .iter()
+                .map(|field| field.name().to_string())
+                .collect::<Vec<_>>(),
+            ["route", "mnt-by", "aut-num"]
+        );
+        assert_eq!(
+            field("route"),
+            [vec!["192.0.2.0/24"], vec![], vec!["198.51.100.0/24"]]
+        );
+        assert_eq!(field("mnt-by"), [vec!["A", "B"], vec![], vec!["C"]]);
+        assert_eq!(field("aut-num"), [vec![], vec!["AS1"], vec![]]);
+    }
+}
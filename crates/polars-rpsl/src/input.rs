@@ -0,0 +1,113 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use encoding_rs::{CoderResult, Decoder, Encoding};
+use flate2::read::MultiGzDecoder;
+use rpsl_parser::IncludeReader;
+
+use crate::ReadOptions;
+
+/// Open a file for reading, transparently decompressing `.gz` files, decoding
+/// `options.encoding` and expanding includes with `options.follow_includes`
+pub(crate) fn open_path(path: &Path, options: &ReadOptions) -> io::Result<Box<dyn BufRead>> {
+    let tolerate_truncated_gzip = options.tolerate_truncated_gzip;
+    let encoding = options
+        .encoding
+        .as_deref()
+        .map(encoding_for_label)
+        .transpose()?;
+    if options.follow_includes {
+        let reader = IncludeReader::new(path, move |path: &Path| {
+            open_file(path, tolerate_truncated_gzip, encoding)
+        })?;
+        Ok(Box::new(reader))
+    } else {
+        open_file(path, tolerate_truncated_gzip, encoding)
+    }
+}
+
+fn encoding_for_label(label: &str) -> io::Result<&'static Encoding> {
+    Encoding::for_label(label.as_bytes()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown encoding '{label}'"),
+        )
+    })
+}
+
+fn open_file(
+    path: &Path,
+    tolerate_truncated_gzip: bool,
+    encoding: Option<&'static Encoding>,
+) -> io::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+
+    let reader: Box<dyn BufRead> = if path.extension().and_then(|s| s.to_str()) == Some("gz") {
+        // Dumps produced by appending hold several gzip members, all of which are read
+        let decoder = MultiGzDecoder::new(file);
+        if tolerate_truncated_gzip {
+            Box::new(BufReader::new(TolerateTruncation(decoder)))
+        } else {
+            Box::new(BufReader::new(decoder))
+        }
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    match encoding {
+        Some(encoding) => Ok(Box::new(BufReader::new(Decode {
+            inner: reader,
+            decoder: encoding.new_decoder(),
+            done: false,
+        }))),
+        None => Ok(reader),
+    }
+}
+
+/// Reader adapter that decodes the inner reader to UTF-8, replacing malformed
+/// sequences with U+FFFD
+struct Decode<R> {
+    inner: R,
+    decoder: Decoder,
+    done: bool,
+}
+
+impl<R: BufRead> Read for Decode<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while !self.done {
+            let input = self.inner.fill_buf()?;
+            let last = input.is_empty();
+            let (result, read, written, _) = self.decoder.decode_to_utf8(input, buf, last);
+            self.inner.consume(read);
+            self.done = last && result == CoderResult::InputEmpty;
+
+            if written > 0 {
+                return Ok(written);
+            }
+            if result == CoderResult::OutputFull {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Buffer too small to decode into",
+                ));
+            }
+            // Otherwise the input ended within a multi-byte sequence: read on
+        }
+        Ok(0)
+    }
+}
+
+/// Reader adapter that turns an unexpected EOF from the inner reader into a clean end of input
+struct TolerateTruncation<R>(R);
+
+impl<R: Read> Read for TolerateTruncation<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.read(buf) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(0),
+            other => other,
+        }
+    }
+}
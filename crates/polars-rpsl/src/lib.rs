@@ -1,19 +1,63 @@
-use std::fs::File;
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader, Cursor};
 use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
 
-use flate2::read::GzDecoder;
-use polars::{frame::DataFrame, prelude::Schema};
+use polars::{
+    error::PolarsError,
+    frame::DataFrame,
+    prelude::{DataType, Schema},
+};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use pyo3_polars::PyDataFrame;
-use rpsl_parser::{ParseError, RpslParser};
+use rpsl_parser::{
+    AttributeSpan, AttributeStats, AttributeStatsCollector, Callbacks, ClassFilter, DumpMetadata,
+    ErrorAction, FirstError, ModifiedSince, ObjectFilter, ParseError, RouteOriginReport,
+    RpslParser, Tee,
+};
 use thiserror::Error;
 
+#[cfg(any(feature = "datafusion", feature = "flight"))]
+mod arrow_rs;
+mod batch;
+mod convert;
+mod diff;
+mod estimate;
+#[cfg(feature = "flight")]
+mod flight;
+mod grouped;
+mod input;
+mod map;
+mod object_columns;
+mod partition;
 mod schema;
 mod schemaless;
+#[cfg(feature = "datafusion")]
+mod table_provider;
+mod typed;
+mod write;
+
+pub use batch::RpslBatchReader;
+#[cfg(feature = "parquet")]
+pub use convert::ParquetFormatWriter;
+pub use convert::{FormatWriter, MultiWriter, NdjsonWriter, convert_rpsl_multi};
+pub use diff::{ObjectKey, RpslDiff, diff_rpsl_from_paths, diff_rpsl_from_readers};
+pub use estimate::{MemoryEstimate, estimate_rpsl_memory_from_reader};
+#[cfg(feature = "flight")]
+pub use flight::flight_stream_from_path;
+pub use grouped::GroupedPolarsBuilder;
+pub use object_columns::HashOrder;
+pub use partition::{read_rpsl_partitioned_from_path, read_rpsl_partitioned_from_reader};
+pub use schema::{InvalidIpv6, schema_from_columns};
+pub use schemaless::{PolarsBuilder, RepeatedClass};
+#[cfg(feature = "datafusion")]
+pub use table_provider::RpslTableProvider;
+pub use write::{write_rpsl_to_path, write_rpsl_to_writer};
 
 use schema::SchemaPolarsBuilder;
-use schemaless::PolarsBuilder;
 
 #[derive(Error, Debug)]
 pub enum RpslError {
@@ -26,8 +70,284 @@ pub enum RpslError {
     #[error("Unsupported schema type for column '{column}': {dtype}. Only String and List(String) are supported.")]
     UnsupportedType { column: String, dtype: String },
 
+    #[error("Attribute '{attr}' is not in the schema, in object at row {row}")]
+    UnknownAttribute { attr: String, row: usize },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("Polars error: {0}")]
+    Polars(#[from] PolarsError),
+
+    #[cfg(any(feature = "datafusion", feature = "flight"))]
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] arrow::error::ArrowError),
+
+    #[cfg(feature = "datafusion")]
+    #[error("DataFusion error: {0}")]
+    DataFusion(#[from] datafusion::error::DataFusionError),
+}
+
+/// Options controlling how RPSL data is read
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Treat an unexpected end of a gzip stream (e.g. a truncated download) as the
+    /// end of input instead of an error, keeping everything parsed up to that point.
+    /// The last object may be incomplete.
+    pub tolerate_truncated_gzip: bool,
+
+    /// Expand `include: path` lines in files with the contents of the named file,
+    /// see [`IncludeReader`](rpsl_parser::IncludeReader) for how paths are resolved
+    /// and the limits on nesting. Only applies to reads from a path.
+    pub follow_includes: bool,
+
+    /// Label of the encoding of files, e.g. `latin1` or `windows-1252`, to decode
+    /// them to UTF-8 before parsing. Labels are those of the WHATWG Encoding
+    /// Standard, which reads `latin1` as its superset windows-1252. By default input
+    /// is read as UTF-8, with invalid sequences replaced by U+FFFD. Only applies to
+    /// reads from a path.
+    pub encoding: Option<String>,
+
+    /// In schema-based reads, treat attributes with an empty (or whitespace-only)
+    /// value as missing instead of as present with value `""`
+    pub empty_as_null: bool,
+
+    /// In schema-less reads, add a `line` field with the line number each attribute
+    /// starts on to the attribute structs
+    pub line_numbers: bool,
+
+    /// In schema-less reads, what to do with attributes repeating the class of
+    /// their object, see [`PolarsBuilder::with_repeated_class`]
+    pub repeated_class: RepeatedClass,
+
+    /// In schema-less reads, add a `comments` column with the comments of each
+    /// object, see [`PolarsBuilder::with_comments_column`]
+    pub comments_column: bool,
+
+    /// In schema-based reads, fail on attributes that are not in the schema instead
+    /// of ignoring them
+    pub reject_unknown_attributes: bool,
+
+    /// In schema-based reads, values for columns whose attribute an object doesn't
+    /// have, instead of null or an empty list
+    pub defaults: HashMap<String, String>,
+
+    /// In schema-based reads, columns whose values are IPv6 addresses or prefixes to
+    /// write in canonical RFC 5952 form, e.g. `route6` or `inet6num`
+    pub ipv6_columns: Vec<String>,
+
+    /// With `ipv6_columns`, what to do with values that don't parse as IPv6
+    pub invalid_ipv6: InvalidIpv6,
+
+    /// In schema-based reads, attributes to read as List(String) columns even if
+    /// they are String in the schema (or missing from it), so that a single
+    /// occurrence becomes a one-element list
+    pub force_list: Vec<String>,
+
+    /// Add a `UInt32` column with this name holding, for each object, the most
+    /// continuation lines folded into any one of its attributes
+    pub max_continuation_column: Option<String>,
+
+    /// Add a `UInt64` column with this name holding a hash of each object's
+    /// attributes, for change detection between snapshots
+    pub hash_column: Option<String>,
+
+    /// Whether attribute order is part of the hashed content
+    pub hash_order: HashOrder,
+
+    /// Add a `UInt64` column with this name numbering the objects from 0, as a join
+    /// key between frames read from the same input
+    pub object_id_column: Option<String>,
+
+    /// Add a `UInt64` column with this name holding the number of input bytes each
+    /// object's attribute lines take up, see [`PolarsBuilder::with_byte_len_column`]
+    pub byte_len_column: Option<String>,
+
+    /// Only read objects whose `last-modified` (or latest `changed`) time is at or
+    /// after this, see [`ModifiedSince`]
+    pub modified_since: Option<SystemTime>,
+
+    /// With `modified_since`, also drop objects without a parseable modification
+    /// time instead of keeping them
+    pub drop_undated: bool,
+
+    /// Only read objects of these classes (matched case-insensitively), see
+    /// [`ClassFilter`]. Other objects are dropped before they reach the builder, so
+    /// their attributes don't add columns to an inferred schema.
+    pub classes: Option<Vec<String>>,
+
+    /// Only read objects accepted by this predicate, see
+    /// [`ReadOptions::with_object_filter`]
+    pub object_filter: Option<ObjectPredicate>,
+
+    /// Stop after this many objects, without reading the rest of the input. Only
+    /// objects kept by `modified_since`, `object_filter` and `classes` count towards
+    /// the limit.
+    pub limit: Option<usize>,
+}
+
+impl ReadOptions {
+    /// Only read objects for which `predicate` returns `true`, given the object's
+    /// `(name, value)` pairs in input order
+    ///
+    /// Each object is buffered and copied before the predicate runs, see
+    /// [`ObjectFilter`], so prefer `classes` or `modified_since` where they
+    /// suffice. The slices are only valid during the call.
+    pub fn with_object_filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&[(&[u8], &[u8])]) -> bool + Send + Sync + 'static,
+    {
+        self.object_filter = Some(ObjectPredicate(Arc::new(predicate)));
+        self
+    }
+}
+
+/// Function over an object's `(name, value)` pairs, as taken by [`ObjectFilter`]
+type PredicateFn = dyn Fn(&[(&[u8], &[u8])]) -> bool + Send + Sync;
+
+/// Callbacks decorator counting the objects that end in `inner`, for
+/// [`ReadOptions::limit`]
+struct ObjectCounter<'a, C> {
+    inner: C,
+    count: &'a Cell<usize>,
+}
+
+impl<C: Callbacks> Callbacks for ObjectCounter<'_, C> {
+    fn start_object(&mut self) {
+        self.inner.start_object();
+    }
+
+    fn start_object_class(&mut self, class: &[u8]) {
+        self.inner.start_object_class(class);
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.inner.attribute(name, value);
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        self.inner.attribute_at(span, name, value);
+    }
+
+    fn end_object(&mut self) {
+        self.inner.end_object();
+        self.count.set(self.count.get() + 1);
+    }
+
+    fn discard_object(&mut self) {
+        self.inner.discard_object();
+    }
+
+    fn side_line(&mut self, line: &[u8]) {
+        self.inner.side_line(line);
+    }
+
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        self.inner.comment_at(line_number, name, comment);
+    }
+
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) {
+        self.inner.raw_line(name, line);
+    }
+
+    fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+        self.inner.sub_attribute(parent, key, value);
+    }
+
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        self.inner.on_error(err)
+    }
+}
+
+/// Predicate over the attributes of an object, see [`ReadOptions::with_object_filter`]
+#[derive(Clone)]
+pub struct ObjectPredicate(Arc<PredicateFn>);
+
+impl std::fmt::Debug for ObjectPredicate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ObjectPredicate")
+    }
+}
+
+/// Parse into the given callbacks, applying the object filters of `options`
+fn parse_with_options<R: BufRead, C: Callbacks>(
+    reader: R,
+    callbacks: C,
+    options: &ReadOptions,
+) -> Result<C, ParseError> {
+    let forwarded = Cell::new(0);
+    let callbacks = ObjectCounter {
+        inner: callbacks,
+        count: &forwarded,
+    };
+    Ok(parse_modified_since(reader, callbacks, options, &forwarded)?.inner)
+}
+
+/// Parse into the given callbacks, keeping only objects modified since
+/// `options.modified_since`
+fn parse_modified_since<R: BufRead, C: Callbacks>(
+    reader: R,
+    callbacks: C,
+    options: &ReadOptions,
+    forwarded: &Cell<usize>,
+) -> Result<C, ParseError> {
+    match options.modified_since {
+        Some(cutoff) => {
+            let filter =
+                ModifiedSince::new(callbacks, cutoff).with_keep_undated(!options.drop_undated);
+            Ok(parse_object_filter(reader, filter, options, forwarded)?.into_inner())
+        }
+        None => parse_object_filter(reader, callbacks, options, forwarded),
+    }
+}
+
+/// Parse into the given callbacks, keeping only objects accepted by
+/// `options.object_filter`
+fn parse_object_filter<R: BufRead, C: Callbacks>(
+    reader: R,
+    callbacks: C,
+    options: &ReadOptions,
+    forwarded: &Cell<usize>,
+) -> Result<C, ParseError> {
+    match &options.object_filter {
+        Some(ObjectPredicate(predicate)) => {
+            let filter = ObjectFilter::new(callbacks, &**predicate);
+            Ok(parse_classes(reader, filter, options, forwarded)?.into_inner())
+        }
+        None => parse_classes(reader, callbacks, options, forwarded),
+    }
+}
+
+/// Parse into the given callbacks, keeping only objects of `options.classes`
+fn parse_classes<R: BufRead, C: Callbacks>(
+    reader: R,
+    callbacks: C,
+    options: &ReadOptions,
+    forwarded: &Cell<usize>,
+) -> Result<C, ParseError> {
+    match &options.classes {
+        Some(classes) => {
+            let filter = ClassFilter::new(callbacks, classes);
+            Ok(parse_objects(reader, filter, options.limit, forwarded)?.into_inner())
+        }
+        None => parse_objects(reader, callbacks, options.limit, forwarded),
+    }
+}
+
+/// Parse until `limit` objects have been `forwarded` past the filters, or all of
+/// them if `None`
+fn parse_objects<R: BufRead, C: Callbacks>(
+    mut reader: R,
+    callbacks: C,
+    limit: Option<usize>,
+    forwarded: &Cell<usize>,
+) -> Result<C, ParseError> {
+    let mut parser = RpslParser::new(callbacks);
+    match limit {
+        Some(limit) => while forwarded.get() < limit && parser.parse_object(&mut reader)? {},
+        None => parser.parse(reader)?,
+    }
+    Ok(parser.into_callbacks())
 }
 
 // =============================================================================
@@ -36,9 +356,15 @@ pub enum RpslError {
 
 /// Read RPSL data from a buffered reader into a Polars DataFrame (schema-less)
 pub fn read_rpsl_from_reader<R: BufRead>(reader: R) -> Result<DataFrame, ParseError> {
-    let mut parser = RpslParser::new(PolarsBuilder::new());
-    parser.parse(reader)?;
-    let polars_builder = parser.into_callbacks();
+    read_rpsl_from_reader_with_options(reader, &ReadOptions::default())
+}
+
+/// Read RPSL data from a buffered reader into a Polars DataFrame (schema-less) using the given options
+pub fn read_rpsl_from_reader_with_options<R: BufRead>(
+    reader: R,
+    options: &ReadOptions,
+) -> Result<DataFrame, ParseError> {
+    let polars_builder = parse_with_options(reader, PolarsBuilder::from_options(options), options)?;
     Ok(polars_builder.build())
 }
 
@@ -46,18 +372,29 @@ pub fn read_rpsl_from_reader<R: BufRead>(reader: R) -> Result<DataFrame, ParseEr
 pub fn read_rpsl_from_path<P: AsRef<Path>>(
     path: P,
 ) -> Result<DataFrame, Box<dyn std::error::Error>> {
-    let path = path.as_ref();
-    let file = File::open(path)?;
-
-    let df = if path.extension().and_then(|s| s.to_str()) == Some("gz") {
-        let reader = BufReader::new(GzDecoder::new(file));
-        read_rpsl_from_reader(reader)?
-    } else {
-        let reader = BufReader::new(file);
-        read_rpsl_from_reader(reader)?
-    };
+    read_rpsl_from_path_with_options(path, &ReadOptions::default())
+}
+
+/// Read RPSL data from a file path into a Polars DataFrame (schema-less) using the given options
+pub fn read_rpsl_from_path_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &ReadOptions,
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    Ok(read_rpsl_from_reader_with_options(reader, options)?)
+}
 
-    Ok(df)
+/// Read RPSL data in the given encoding (e.g. `latin1`) from a file path into a
+/// Polars DataFrame (schema-less), see [`ReadOptions::encoding`]
+pub fn read_rpsl_from_path_with_encoding<P: AsRef<Path>>(
+    path: P,
+    encoding: &str,
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    let options = ReadOptions {
+        encoding: Some(encoding.to_string()),
+        ..ReadOptions::default()
+    };
+    read_rpsl_from_path_with_options(path, &options)
 }
 
 // =============================================================================
@@ -69,11 +406,18 @@ pub fn read_rpsl_with_schema_from_reader<R: BufRead>(
     reader: R,
     schema: &Schema,
 ) -> Result<DataFrame, RpslError> {
-    let builder = SchemaPolarsBuilder::new(schema)?;
-    let mut parser = RpslParser::new(builder);
-    parser.parse(reader)?;
-    let builder = parser.into_callbacks();
-    builder.build()
+    read_rpsl_with_schema_from_reader_with_options(reader, schema, &ReadOptions::default())
+}
+
+/// Read RPSL data with a specific schema using the given options
+pub fn read_rpsl_with_schema_from_reader_with_options<R: BufRead>(
+    reader: R,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<DataFrame, RpslError> {
+    let builder = SchemaPolarsBuilder::from_options(schema, options)?;
+    let builder = parse_with_options(reader, FirstError::new(builder), options)?.into_result()?;
+    Ok(builder.build())
 }
 
 /// Read RPSL data from a file path with a specific schema
@@ -81,64 +425,562 @@ pub fn read_rpsl_with_schema_from_path<P: AsRef<Path>>(
     path: P,
     schema: &Schema,
 ) -> Result<DataFrame, RpslError> {
-    let path = path.as_ref();
-    let file = File::open(path)?;
-
-    let df = if path.extension().and_then(|s| s.to_str()) == Some("gz") {
-        let reader = BufReader::new(GzDecoder::new(file));
-        read_rpsl_with_schema_from_reader(reader, schema)?
-    } else {
-        let reader = BufReader::new(file);
-        read_rpsl_with_schema_from_reader(reader, schema)?
-    };
+    read_rpsl_with_schema_from_path_with_options(path, schema, &ReadOptions::default())
+}
+
+/// Read RPSL data from a file path with a specific schema using the given options
+pub fn read_rpsl_with_schema_from_path_with_options<P: AsRef<Path>>(
+    path: P,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<DataFrame, RpslError> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    read_rpsl_with_schema_from_reader_with_options(reader, schema, options)
+}
+
+// =============================================================================
+// Combined reading
+// =============================================================================
+
+/// Read RPSL data with a specific schema, plus the schema-less `attributes` column, in one pass
+///
+/// Per-object columns such as `object_id_column` are added once, with the schema
+/// columns. The schema-less options `line_numbers`, `repeated_class` and
+/// `comments_column` apply to the `attributes` (and `comments`) column.
+pub fn read_rpsl_full_from_reader<R: BufRead>(
+    reader: R,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<DataFrame, RpslError> {
+    let builder = SchemaPolarsBuilder::from_options(schema, options)?;
+    let polars_builder = PolarsBuilder::from_attribute_options(options);
+    let callbacks = Tee::new(FirstError::new(builder), polars_builder);
+    let (schema_builder, polars_builder) =
+        parse_with_options(reader, callbacks, options)?.into_inner();
+    let df = schema_builder.into_result()?.build();
+    Ok(df.hstack(polars_builder.build().get_columns())?)
+}
+
+/// Read RPSL data from a file path with a specific schema, plus the schema-less `attributes` column
+pub fn read_rpsl_full_from_path<P: AsRef<Path>>(
+    path: P,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<DataFrame, RpslError> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    read_rpsl_full_from_reader(reader, schema, options)
+}
+
+// =============================================================================
+// Grouped reading
+// =============================================================================
 
-    Ok(df)
+/// Read RPSL data into a DataFrame with a single `attributes` Struct column, with
+/// one List(String) field per attribute name, see [`GroupedPolarsBuilder`]
+pub fn read_rpsl_grouped_from_reader<R: BufRead>(
+    reader: R,
+    options: &ReadOptions,
+) -> Result<DataFrame, ParseError> {
+    let builder = parse_with_options(reader, GroupedPolarsBuilder::new(), options)?;
+    Ok(builder.build())
+}
+
+/// Read RPSL data from a file path into a DataFrame with a single `attributes`
+/// Struct column, see [`read_rpsl_grouped_from_reader`]
+pub fn read_rpsl_grouped_from_path<P: AsRef<Path>>(
+    path: P,
+    options: &ReadOptions,
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    Ok(read_rpsl_grouped_from_reader(reader, options)?)
+}
+
+// =============================================================================
+// Typed schema-less reading
+// =============================================================================
+
+/// Read RPSL data into a wide DataFrame with one column per attribute, typing only
+/// the attributes given in `dtypes`
+///
+/// Other attributes are inferred from the data: List(String) if any object has
+/// the attribute more than once, String otherwise, in order of first appearance.
+/// Given attributes are read as String (or List(String) for list dtypes) and then
+/// cast, so values that don't parse as the dtype become null. Given attributes
+/// that never occur are added as all-null columns. All objects are held in memory
+/// until the columns are known.
+pub fn read_rpsl_typed_schemaless_from_reader<R: BufRead>(
+    reader: R,
+    dtypes: &HashMap<String, DataType>,
+    options: &ReadOptions,
+) -> Result<DataFrame, RpslError> {
+    let collector = parse_with_options(reader, typed::ObjectCollector::default(), options)?;
+    typed::build_typed(collector, dtypes, options)
+}
+
+/// Read RPSL data from a file path into a wide DataFrame, typing only the
+/// attributes given in `dtypes`, see [`read_rpsl_typed_schemaless_from_reader`]
+pub fn read_rpsl_typed_schemaless_from_path<P: AsRef<Path>>(
+    path: P,
+    dtypes: &HashMap<String, DataType>,
+    options: &ReadOptions,
+) -> Result<DataFrame, RpslError> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    read_rpsl_typed_schemaless_from_reader(reader, dtypes, options)
+}
+
+// =============================================================================
+// Analysis
+// =============================================================================
+
+/// Audit the `route`/`route6` objects in a file, tallying the origins of each
+/// prefix and recording malformed objects
+pub fn route_origin_report_from_path<P: AsRef<Path>>(
+    path: P,
+    options: &ReadOptions,
+) -> Result<RouteOriginReport, RpslError> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    Ok(rpsl_parser::route_origin_report(reader)?)
+}
+
+/// Count how often each attribute occurs in a file, estimate its number of
+/// distinct values and find its `top_k` most common values, see
+/// [`AttributeStatsCollector`]. Objects are filtered as set in `options`.
+pub fn attribute_value_stats_from_path<P: AsRef<Path>>(
+    path: P,
+    top_k: usize,
+    options: &ReadOptions,
+) -> Result<Vec<AttributeStats>, RpslError> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    let collector = parse_with_options(reader, AttributeStatsCollector::new(top_k), options)?;
+    Ok(collector.into_stats())
+}
+
+/// Estimate the memory a schema-based read of a file would take, see
+/// [`estimate_rpsl_memory_from_reader`]
+pub fn estimate_rpsl_memory_from_path<P: AsRef<Path>>(
+    path: P,
+    schema: &Schema,
+    options: &ReadOptions,
+) -> Result<MemoryEstimate, RpslError> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    estimate_rpsl_memory_from_reader(reader, schema, options)
+}
+
+/// Read the serial, generation time and source of a dump from the comments
+/// before its first object, without reading the rest of the file
+pub fn read_rpsl_metadata_from_path<P: AsRef<Path>>(
+    path: P,
+    options: &ReadOptions,
+) -> Result<DumpMetadata, RpslError> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    Ok(rpsl_parser::read_dump_metadata(reader)?)
 }
 
 // =============================================================================
 // Python bindings
 // =============================================================================
 
+fn runtime_error<E: ToString>(e: E) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string())
+}
+
+/// A schema passed from Python, either as an (empty) DataFrame or as an ordered
+/// list of `(name, dtype)` tuples using the dtype strings accepted by
+/// [`schema_from_columns`]
+#[derive(FromPyObject)]
+enum SchemaArg {
+    Frame(PyDataFrame),
+    Columns(Vec<(String, String)>),
+}
+
+impl SchemaArg {
+    fn into_schema(self) -> PyResult<Schema> {
+        match self {
+            SchemaArg::Frame(df) => Ok(Schema::clone(df.0.schema())),
+            SchemaArg::Columns(columns) => schema_from_columns(columns).map_err(runtime_error),
+        }
+    }
+}
+
 #[pyfunction]
-#[pyo3(name = "read_rpsl", signature = (path, schema=None))]
-fn py_read_rpsl(path: &str, schema: Option<PyDataFrame>) -> PyResult<PyDataFrame> {
+#[pyo3(
+    name = "read_rpsl",
+    signature = (
+        path,
+        schema=None,
+        tolerate_truncated_gzip=false,
+        empty_as_null=false,
+        line_numbers=false,
+        force_list=None,
+        limit=None,
+        comments=false,
+        classes=None,
+        encoding=None
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+fn py_read_rpsl(
+    path: &str,
+    schema: Option<SchemaArg>,
+    tolerate_truncated_gzip: bool,
+    empty_as_null: bool,
+    line_numbers: bool,
+    force_list: Option<Vec<String>>,
+    limit: Option<usize>,
+    comments: bool,
+    classes: Option<Vec<String>>,
+    encoding: Option<String>,
+) -> PyResult<PyDataFrame> {
+    let options = ReadOptions {
+        tolerate_truncated_gzip,
+        empty_as_null,
+        line_numbers,
+        force_list: force_list.unwrap_or_default(),
+        limit,
+        comments_column: comments,
+        classes,
+        encoding,
+        ..ReadOptions::default()
+    };
+    let schema = schema.map(SchemaArg::into_schema).transpose()?;
+    Ok(PyDataFrame(read_path(path, schema.as_ref(), &options)?))
+}
+
+/// Read a file schema-less, or with the schema if given
+fn read_path(path: &str, schema: Option<&Schema>, options: &ReadOptions) -> PyResult<DataFrame> {
     match schema {
-        None => {
-            let df = read_rpsl_from_path(path)
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
-            Ok(PyDataFrame(df))
-        }
-        Some(schema_df) => {
-            let polars_schema = schema_df.0.schema();
-            let df = read_rpsl_with_schema_from_path(path, &polars_schema)
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
-            Ok(PyDataFrame(df))
-        }
+        None => read_rpsl_from_path_with_options(path, options).map_err(runtime_error),
+        Some(schema) => read_rpsl_with_schema_from_path_with_options(path, schema, options)
+            .map_err(runtime_error),
     }
 }
 
+/// Like `read_rpsl`, but returns an awaitable. The file is read on a worker
+/// thread without holding the GIL, so the event loop stays responsive.
 #[pyfunction]
-#[pyo3(name = "read_rpsl_bytes", signature = (data, schema=None))]
-fn py_read_rpsl_bytes(data: &[u8], schema: Option<PyDataFrame>) -> PyResult<PyDataFrame> {
+#[pyo3(
+    name = "read_rpsl_async",
+    signature = (
+        path,
+        schema=None,
+        tolerate_truncated_gzip=false,
+        empty_as_null=false,
+        line_numbers=false,
+        force_list=None,
+        limit=None,
+        comments=false,
+        classes=None,
+        encoding=None
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+fn py_read_rpsl_async(
+    py: Python<'_>,
+    path: String,
+    schema: Option<SchemaArg>,
+    tolerate_truncated_gzip: bool,
+    empty_as_null: bool,
+    line_numbers: bool,
+    force_list: Option<Vec<String>>,
+    limit: Option<usize>,
+    comments: bool,
+    classes: Option<Vec<String>>,
+    encoding: Option<String>,
+) -> PyResult<Bound<'_, PyAny>> {
+    let options = ReadOptions {
+        tolerate_truncated_gzip,
+        empty_as_null,
+        line_numbers,
+        force_list: force_list.unwrap_or_default(),
+        limit,
+        comments_column: comments,
+        classes,
+        encoding,
+        ..ReadOptions::default()
+    };
+    // Converted here, as a schema given as a DataFrame can only be read with the GIL
+    let schema = schema.map(SchemaArg::into_schema).transpose()?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let df = tokio::task::spawn_blocking(move || read_path(&path, schema.as_ref(), &options))
+            .await
+            .map_err(runtime_error)??;
+        Ok(PyDataFrame(df))
+    })
+}
+
+#[pyfunction]
+#[pyo3(
+    name = "read_rpsl_bytes",
+    signature = (
+        data,
+        schema=None,
+        empty_as_null=false,
+        line_numbers=false,
+        force_list=None,
+        limit=None,
+        comments=false,
+        classes=None
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+fn py_read_rpsl_bytes(
+    data: &[u8],
+    schema: Option<SchemaArg>,
+    empty_as_null: bool,
+    line_numbers: bool,
+    force_list: Option<Vec<String>>,
+    limit: Option<usize>,
+    comments: bool,
+    classes: Option<Vec<String>>,
+) -> PyResult<PyDataFrame> {
     let reader = BufReader::new(Cursor::new(data));
+    let options = ReadOptions {
+        empty_as_null,
+        line_numbers,
+        force_list: force_list.unwrap_or_default(),
+        limit,
+        comments_column: comments,
+        classes,
+        ..ReadOptions::default()
+    };
     match schema {
         None => {
-            let df = read_rpsl_from_reader(reader)
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+            let df = read_rpsl_from_reader_with_options(reader, &options).map_err(runtime_error)?;
             Ok(PyDataFrame(df))
         }
-        Some(schema_df) => {
-            let polars_schema = schema_df.0.schema();
-            let df = read_rpsl_with_schema_from_reader(reader, &polars_schema)
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
+        Some(schema) => {
+            let polars_schema = schema.into_schema()?;
+            let df =
+                read_rpsl_with_schema_from_reader_with_options(reader, &polars_schema, &options)
+                    .map_err(runtime_error)?;
             Ok(PyDataFrame(df))
         }
     }
 }
 
+#[pyfunction]
+#[pyo3(
+    name = "read_rpsl_full",
+    signature = (
+        path,
+        schema,
+        tolerate_truncated_gzip=false,
+        empty_as_null=false,
+        line_numbers=false,
+        force_list=None
+    )
+)]
+fn py_read_rpsl_full(
+    path: &str,
+    schema: SchemaArg,
+    tolerate_truncated_gzip: bool,
+    empty_as_null: bool,
+    line_numbers: bool,
+    force_list: Option<Vec<String>>,
+) -> PyResult<PyDataFrame> {
+    let options = ReadOptions {
+        tolerate_truncated_gzip,
+        empty_as_null,
+        line_numbers,
+        force_list: force_list.unwrap_or_default(),
+        ..ReadOptions::default()
+    };
+    let df =
+        read_rpsl_full_from_path(path, &schema.into_schema()?, &options).map_err(runtime_error)?;
+    Ok(PyDataFrame(df))
+}
+
+#[pyfunction]
+#[pyo3(
+    name = "read_rpsl_full_bytes",
+    signature = (data, schema, empty_as_null=false, line_numbers=false, force_list=None)
+)]
+fn py_read_rpsl_full_bytes(
+    data: &[u8],
+    schema: SchemaArg,
+    empty_as_null: bool,
+    line_numbers: bool,
+    force_list: Option<Vec<String>>,
+) -> PyResult<PyDataFrame> {
+    let reader = BufReader::new(Cursor::new(data));
+    let options = ReadOptions {
+        empty_as_null,
+        line_numbers,
+        force_list: force_list.unwrap_or_default(),
+        ..ReadOptions::default()
+    };
+    let df = read_rpsl_full_from_reader(reader, &schema.into_schema()?, &options)
+        .map_err(runtime_error)?;
+    Ok(PyDataFrame(df))
+}
+
+/// Call `func` on every object of a file as a dict of attribute value lists,
+/// parsing on `workers` threads, and return the results in input order
+#[pyfunction]
+#[pyo3(name = "map_rpsl", signature = (path, func, workers=None))]
+fn py_map_rpsl(
+    py: Python<'_>,
+    path: &str,
+    func: Py<PyAny>,
+    workers: Option<usize>,
+) -> PyResult<Vec<Py<PyAny>>> {
+    map::map_path(py, Path::new(path), &func, workers)
+}
+
+/// Read a file into a dict of DataFrames keyed on the value of `attribute`, in
+/// the order the values are first seen
+#[pyfunction]
+#[pyo3(name = "read_rpsl_partitioned", signature = (path, attribute, schema=None))]
+fn py_read_rpsl_partitioned<'py>(
+    py: Python<'py>,
+    path: &str,
+    attribute: &str,
+    schema: Option<SchemaArg>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let schema = schema.map(SchemaArg::into_schema).transpose()?;
+    let frames =
+        read_rpsl_partitioned_from_path(path, attribute, schema.as_ref(), &ReadOptions::default())
+            .map_err(runtime_error)?;
+
+    let dict = PyDict::new(py);
+    for (key, df) in frames {
+        dict.set_item(key, PyDataFrame(df))?;
+    }
+    Ok(dict)
+}
+
+/// Statistics of one attribute as returned to Python: `(name, count, distinct,
+/// top_values)` with `top_values` a list of `(value, count)` tuples
+type PyAttributeStats = (String, u64, u64, Vec<(String, u64)>);
+
+/// Per-attribute value statistics of a file, see [`PyAttributeStats`]
+#[pyfunction]
+#[pyo3(name = "attribute_value_stats", signature = (path, top_k=10))]
+fn py_attribute_value_stats(path: &str, top_k: usize) -> PyResult<Vec<PyAttributeStats>> {
+    let stats = attribute_value_stats_from_path(path, top_k, &ReadOptions::default())
+        .map_err(runtime_error)?;
+    Ok(stats
+        .into_iter()
+        .map(|s| (s.name, s.count, s.distinct, s.top_values))
+        .collect())
+}
+
+#[pyfunction]
+#[pyo3(name = "write_rpsl")]
+fn py_write_rpsl(df: PyDataFrame, path: &str) -> PyResult<()> {
+    write_rpsl_to_path(&df.0, path).map_err(runtime_error)
+}
+
 #[pymodule]
 fn _rpsl_reader(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(py_read_rpsl, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_rpsl_async, m)?)?;
     m.add_function(wrap_pyfunction!(py_read_rpsl_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_rpsl_full, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_rpsl_full_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(py_map_rpsl, m)?)?;
+    m.add_function(wrap_pyfunction!(py_read_rpsl_partitioned, m)?)?;
+    m.add_function(wrap_pyfunction!(py_attribute_value_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(py_write_rpsl, m)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_object_id_column() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n\n\
+            aut-num: AS1\n\n\
+            route: 198.51.100.0/24\norigin: AS2\n";
+        let options = ReadOptions {
+            object_id_column: Some("id".to_string()),
+            ..Default::default()
+        };
+        let ids = |df: &DataFrame| df.column("id").unwrap().u64().unwrap().to_vec();
+
+        let schemaless = read_rpsl_from_reader_with_options(&input[..], &options).unwrap();
+        assert_eq!(ids(&schemaless), [Some(0), Some(1), Some(2)]);
+
+        let schema = Schema::from_iter([Field::new("route".into(), DataType::String)]);
+        let with_schema =
+            read_rpsl_with_schema_from_reader_with_options(&input[..], &schema, &options).unwrap();
+        assert_eq!(with_schema.get_column_names(), ["route", "id"]);
+        assert_eq!(ids(&with_schema), ids(&schemaless));
+
+        // Only kept objects are numbered
+        let options = ReadOptions {
+            classes: Some(vec!["route".to_string()]),
+            ..options
+        };
+        let df =
+            read_rpsl_with_schema_from_reader_with_options(&input[..], &schema, &options).unwrap();
+        assert_eq!(ids(&df), [Some(0), Some(1)]);
+        assert_eq!(
+            df.column("route").unwrap().str().unwrap().get(1),
+            Some("198.51.100.0/24")
+        );
+    }
+
+    #[test]
+    fn test_read_full() {
+        let input = b"% about A\nroute: 192.0.2.0/24\norigin: AS1\nroute: 198.51.100.0/24\n\n\
+            route: 203.0.113.0/24\n";
+        let options = ReadOptions {
+            object_id_column: Some("id".to_string()),
+            comments_column: true,
+            repeated_class: RepeatedClass::Drop,
+            ..Default::default()
+        };
+        let schema = Schema::from_iter([Field::new("origin".into(), DataType::String)]);
+        let df = read_rpsl_full_from_reader(&input[..], &schema, &options).unwrap();
+
+        assert_eq!(
+            df.get_column_names(),
+            ["origin", "id", "attributes", "comments"]
+        );
+        let attributes = df.column("attributes").unwrap().list().unwrap();
+        assert_eq!(
+            attributes.lst_lengths().into_iter().collect::<Vec<_>>(),
+            [Some(2), Some(1)]
+        );
+        let comments = df.column("comments").unwrap().explode(false).unwrap();
+        assert_eq!(
+            comments.str().unwrap().into_iter().collect::<Vec<_>>(),
+            [Some("% about A"), None]
+        );
+    }
+
+    #[test]
+    fn test_read_multi_member_gzip() {
+        use std::io::Write;
+
+        use flate2::{Compression, write::GzEncoder};
+
+        let gzip = |input: &[u8]| {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+            encoder.write_all(input).unwrap();
+            encoder.finish().unwrap()
+        };
+        // As produced by appending to a dump
+        let mut input = gzip(b"route: 192.0.2.0/24\norigin: AS1\n\n");
+        input.extend(gzip(b"route: 198.51.100.0/24\norigin: AS2\n"));
+        let path = std::env::temp_dir().join(format!("rpsl-multi-{}.gz", std::process::id()));
+        std::fs::write(&path, input).unwrap();
+
+        let schema = Schema::from_iter([Field::new("route".into(), DataType::String)]);
+        let df = read_rpsl_with_schema_from_path(&path, &schema);
+        std::fs::remove_file(&path).unwrap();
+
+        let df = df.unwrap();
+        let routes = df.column("route").unwrap().str().unwrap();
+        assert_eq!(
+            routes.into_iter().collect::<Vec<_>>(),
+            [Some("192.0.2.0/24"), Some("198.51.100.0/24")]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::num::NonZero;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use rpsl_parser::{RpslObject, rpsl_objects, split_rpsl_offsets};
+
+use crate::{ReadOptions, input, runtime_error};
+
+/// An object as a dict mapping each attribute name to the list of its values
+fn object_dict<'py>(py: Python<'py>, object: &RpslObject) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (name, value) in &object.attributes {
+        match dict.get_item(name)? {
+            Some(values) => values.downcast::<PyList>()?.append(value)?,
+            None => dict.set_item(name, PyList::new(py, [value])?)?,
+        }
+    }
+    Ok(dict)
+}
+
+/// Call `func` on each object of `reader`, stopping early when another worker
+/// has failed
+fn map_objects<R: BufRead>(
+    reader: R,
+    func: &Py<PyAny>,
+    failed: &AtomicBool,
+) -> PyResult<Vec<Py<PyAny>>> {
+    let mut results = Vec::new();
+
+    for object in rpsl_objects(reader) {
+        if failed.load(Ordering::Relaxed) {
+            break;
+        }
+        let object = match object {
+            Ok(object) => object,
+            Err(e) => {
+                failed.store(true, Ordering::Relaxed);
+                return Err(runtime_error(e));
+            }
+        };
+
+        // The GIL is only held while calling into Python, so other workers keep
+        // parsing meanwhile
+        let result = Python::attach(|py| func.call1(py, (object_dict(py, &object)?,)));
+        match result {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                failed.store(true, Ordering::Relaxed);
+                return Err(e);
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Call `func` on every object of a file, parsing with up to `workers` threads,
+/// and return the results in input order
+///
+/// Uncompressed files are split into ranges of whole objects with
+/// [`split_rpsl_offsets`], one per worker. Gzip-compressed files cannot be split
+/// and are read by a single worker.
+pub(crate) fn map_path(
+    py: Python<'_>,
+    path: &Path,
+    func: &Py<PyAny>,
+    workers: Option<usize>,
+) -> PyResult<Vec<Py<PyAny>>> {
+    let workers =
+        workers.unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZero::get));
+    let ranges = if path.extension().and_then(|s| s.to_str()) == Some("gz") {
+        Vec::new()
+    } else {
+        split_rpsl_offsets(path, workers)?
+    };
+    let failed = AtomicBool::new(false);
+
+    py.detach(|| {
+        if ranges.len() <= 1 {
+            let reader = input::open_path(path, &ReadOptions::default())?;
+            return map_objects(reader, func, &failed);
+        }
+
+        thread::scope(|scope| {
+            let failed = &failed;
+            let handles: Vec<_> = ranges
+                .iter()
+                .map(|&(start, end)| {
+                    scope.spawn(move || {
+                        let mut file = File::open(path)?;
+                        file.seek(SeekFrom::Start(start))?;
+                        map_objects(BufReader::new(file.take(end - start)), func, failed)
+                    })
+                })
+                .collect();
+
+            let mut results = Vec::new();
+            for handle in handles {
+                let worker_results = handle
+                    .join()
+                    .map_err(|_| runtime_error("map_rpsl worker thread panicked"))??;
+                results.extend(worker_results);
+            }
+            Ok(results)
+        })
+    })
+}
@@ -0,0 +1,358 @@
+use polars::prelude::{ArrowField, Column, LargeListArray, NamedFrom, Series};
+use polars_arrow::{
+    array::{MutableUtf8Array, Utf8Array},
+    datatypes::ArrowDataType,
+    offset::OffsetsBuffer,
+};
+use rpsl_parser::AttributeSpan;
+
+/// Per-object `UInt32` column with the most continuation lines folded into any
+/// attribute of the object
+pub(crate) struct MaxContinuationColumn {
+    name: String,
+    values: Vec<u32>,
+    current: u32,
+}
+
+impl MaxContinuationColumn {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            values: Vec::new(),
+            current: 0,
+        }
+    }
+
+    #[inline]
+    pub fn start_object(&mut self) {
+        self.current = 0;
+    }
+
+    #[inline]
+    pub fn attribute(&mut self, span: AttributeSpan) {
+        self.current = self.current.max(span.continuation_lines);
+    }
+
+    #[inline]
+    pub fn end_object(&mut self) {
+        self.values.push(self.current);
+    }
+
+    /// Take the values seen so far as a column
+    pub fn drain(&mut self) -> Column {
+        Series::new(self.name.as_str().into(), std::mem::take(&mut self.values)).into()
+    }
+}
+
+/// Per-object `UInt64` column with the number of input bytes taken up by the
+/// attributes of the object, see [`AttributeSpan::byte_len`]
+pub(crate) struct ByteLenColumn {
+    name: String,
+    values: Vec<u64>,
+    current: u64,
+}
+
+impl ByteLenColumn {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            values: Vec::new(),
+            current: 0,
+        }
+    }
+
+    #[inline]
+    pub fn start_object(&mut self) {
+        self.current = 0;
+    }
+
+    #[inline]
+    pub fn attribute(&mut self, span: AttributeSpan) {
+        self.current += span.byte_len;
+    }
+
+    #[inline]
+    pub fn end_object(&mut self) {
+        self.values.push(self.current);
+    }
+
+    /// Take the values seen so far as a column
+    pub fn drain(&mut self) -> Column {
+        Series::new(self.name.as_str().into(), std::mem::take(&mut self.values)).into()
+    }
+}
+
+/// Per-object `UInt64` column numbering objects from 0, in the order they are
+/// built. Numbering carries on across drains.
+pub(crate) struct ObjectIdColumn {
+    name: String,
+    values: Vec<u64>,
+    next: u64,
+}
+
+impl ObjectIdColumn {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            values: Vec::new(),
+            next: 0,
+        }
+    }
+
+    #[inline]
+    pub fn end_object(&mut self) {
+        self.values.push(self.next);
+        self.next += 1;
+    }
+
+    /// Take the values seen so far as a column
+    pub fn drain(&mut self) -> Column {
+        Series::new(self.name.as_str().into(), std::mem::take(&mut self.values)).into()
+    }
+}
+
+/// How the attributes of an object are combined into its content hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HashOrder {
+    /// Attribute order is part of the content, so reordering changes the hash
+    #[default]
+    Preserve,
+
+    /// Attributes are hashed as a sorted multiset, so reordering doesn't change
+    /// the hash
+    Sorted,
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+#[inline]
+fn fnv1a(hash: u64, bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes
+        .into_iter()
+        .fold(hash, |hash, b| (hash ^ b as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Hash of an object's attributes
+///
+/// Uses 64-bit FNV-1a, which is fast and stable across platforms and versions.
+/// Attribute names are lowercased, values are hashed as-is.
+pub(crate) struct ObjectHasher {
+    order: HashOrder,
+    attributes: Vec<u64>,
+}
+
+impl ObjectHasher {
+    pub fn new(order: HashOrder) -> Self {
+        Self {
+            order,
+            attributes: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn start_object(&mut self) {
+        self.attributes.clear();
+    }
+
+    #[inline]
+    pub fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        let hash = fnv1a(FNV_OFFSET, name.iter().map(u8::to_ascii_lowercase));
+        // Separate name and value, so that e.g. `ab: c` and `a: bc` differ
+        let hash = fnv1a(hash, [0]);
+        self.attributes.push(fnv1a(hash, value.iter().copied()));
+    }
+
+    /// The hash of the attributes since the object started
+    #[inline]
+    pub fn finish(&mut self) -> u64 {
+        if self.order == HashOrder::Sorted {
+            self.attributes.sort_unstable();
+        }
+        self.attributes.iter().fold(FNV_OFFSET, |hash, attribute| {
+            fnv1a(hash, attribute.to_le_bytes())
+        })
+    }
+}
+
+/// Per-object `UInt64` column with a hash of the object's attributes, see
+/// [`ObjectHasher`]
+pub(crate) struct HashColumn {
+    name: String,
+    hasher: ObjectHasher,
+    values: Vec<u64>,
+}
+
+impl HashColumn {
+    pub fn new(name: &str, order: HashOrder) -> Self {
+        Self {
+            name: name.to_string(),
+            hasher: ObjectHasher::new(order),
+            values: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn start_object(&mut self) {
+        self.hasher.start_object();
+    }
+
+    #[inline]
+    pub fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.hasher.attribute(name, value);
+    }
+
+    #[inline]
+    pub fn end_object(&mut self) {
+        self.values.push(self.hasher.finish());
+    }
+
+    /// Take the values seen so far as a column
+    pub fn drain(&mut self) -> Column {
+        Series::new(self.name.as_str().into(), std::mem::take(&mut self.values)).into()
+    }
+}
+
+/// Per-object `List(String)` column with the comments seen since the previous
+/// object ended, i.e. those within the object and on the lines before it
+pub(crate) struct CommentsColumn {
+    values: MutableUtf8Array<i64>,
+    offsets: Vec<i64>,
+
+    /// Comments not yet assigned to an object
+    pending: Vec<String>,
+}
+
+impl CommentsColumn {
+    pub fn new() -> Self {
+        Self {
+            values: MutableUtf8Array::new(),
+            offsets: vec![0],
+            pending: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn comment(&mut self, comment: &[u8]) {
+        self.pending
+            .push(String::from_utf8_lossy(comment).into_owned());
+    }
+
+    #[inline]
+    pub fn end_object(&mut self) {
+        for comment in self.pending.drain(..) {
+            self.values.push(Some(comment));
+        }
+        self.offsets.push(self.values.len() as i64);
+    }
+
+    /// Take the values seen so far as a column
+    pub fn drain(&mut self) -> Column {
+        let values: Utf8Array<i64> = std::mem::take(&mut self.values).into();
+        let offsets = std::mem::replace(&mut self.offsets, vec![0]);
+
+        let offsets = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
+        let list_array = LargeListArray::new(
+            ArrowDataType::LargeList(Box::new(ArrowField::new(
+                "item".into(),
+                ArrowDataType::LargeUtf8,
+                true,
+            ))),
+            offsets,
+            Box::new(values),
+            None,
+        );
+
+        Series::from_arrow("comments".into(), Box::new(list_array))
+            .expect("Failed to create comments series")
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rpsl_parser::RpslParser;
+
+    use super::*;
+    use crate::PolarsBuilder;
+
+    fn hash(order: HashOrder, attributes: &[(&str, &str)]) -> u64 {
+        let mut hasher = ObjectHasher::new(order);
+        hasher.start_object();
+        for (name, value) in attributes {
+            hasher.attribute(name.as_bytes(), value.as_bytes());
+        }
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_object_hasher() {
+        let object = [("route", "192.0.2.0/24"), ("origin", "AS1")];
+        let reordered = [("origin", "AS1"), ("route", "192.0.2.0/24")];
+
+        for order in [HashOrder::Preserve, HashOrder::Sorted] {
+            assert_eq!(hash(order, &object), hash(order, &object));
+            assert_eq!(
+                hash(order, &object),
+                hash(order, &[("Route", "192.0.2.0/24"), ("ORIGIN", "AS1")])
+            );
+            assert_ne!(hash(order, &[("ab", "c")]), hash(order, &[("a", "bc")]));
+            assert_ne!(
+                hash(order, &object),
+                hash(order, &[("route", "192.0.2.0/24"), ("origin", "AS2")])
+            );
+        }
+
+        assert_ne!(
+            hash(HashOrder::Preserve, &object),
+            hash(HashOrder::Preserve, &reordered)
+        );
+        assert_eq!(
+            hash(HashOrder::Sorted, &object),
+            hash(HashOrder::Sorted, &reordered)
+        );
+    }
+
+    #[test]
+    fn test_hash_column_stable_across_reads() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n\naut-num: AS1\nas-name: X\n";
+        let read = || {
+            let builder = PolarsBuilder::new().with_hash_column("hash", HashOrder::Preserve);
+            let mut parser = RpslParser::new(builder);
+            parser.parse(&input[..]).unwrap();
+            let df = parser.into_callbacks().build();
+            df.column("hash").unwrap().u64().unwrap().to_vec()
+        };
+
+        let hashes = read();
+        assert_eq!(hashes.len(), 2);
+        assert_ne!(hashes[0], hashes[1]);
+        assert_eq!(hashes, read());
+        assert_eq!(
+            hashes[0],
+            Some(hash(
+                HashOrder::Preserve,
+                &[("route", "192.0.2.0/24"), ("origin", "AS1")]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_byte_len_column() {
+        let first = "route: 192.0.2.0/24\r\ndescr: first\r\n  second # comment\r\n+third\r\n";
+        let second = "aut-num: AS1\nremarks: a\n\tb\nas-name: X";
+        let input = format!("% header\r\n{first}\r\n\r\n% between\n{second}");
+
+        let builder = PolarsBuilder::new()
+            .with_attribute_whitelist(&["route"])
+            .with_byte_len_column("bytes");
+        let mut parser = RpslParser::new(builder);
+        parser.parse(input.as_bytes()).unwrap();
+        let df = parser.into_callbacks().build();
+
+        assert_eq!(
+            df.column("bytes").unwrap().u64().unwrap().to_vec(),
+            [Some(first.len() as u64), Some(second.len() as u64)]
+        );
+    }
+}
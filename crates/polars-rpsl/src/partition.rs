@@ -0,0 +1,144 @@
+use std::io::BufRead;
+use std::path::Path;
+
+use indexmap::IndexMap;
+use polars::prelude::{DataFrame, Schema};
+use rpsl_parser::{AttributeSpan, Callbacks, FirstError};
+
+use crate::schema::SchemaPolarsBuilder;
+use crate::{PolarsBuilder, ReadOptions, RpslError, input, parse_with_options};
+
+/// Callbacks decorator routing each object to one of several callbacks (buckets),
+/// keyed on the value of an attribute
+///
+/// Each object is buffered until it ends, then replayed into the bucket for its
+/// key, created with `new_bucket` when the key is first seen. The key is the
+/// trimmed value of the first occurrence of the attribute (matched
+/// case-insensitively); later occurrences don't affect routing. Objects without
+/// the attribute go to the `None` bucket. Comments go to the bucket of the object
+/// they precede or belong to.
+struct AttributePartitioner<C, F> {
+    attribute: Vec<u8>,
+    new_bucket: F,
+    buckets: IndexMap<Option<String>, C>,
+    key: Option<String>,
+    attributes: Vec<(AttributeSpan, Vec<u8>, Vec<u8>)>,
+    comments: Vec<Vec<u8>>,
+}
+
+impl<C: Callbacks, F: FnMut() -> C> AttributePartitioner<C, F> {
+    fn new(attribute: &str, new_bucket: F) -> Self {
+        Self {
+            attribute: attribute.as_bytes().to_vec(),
+            new_bucket,
+            buckets: IndexMap::new(),
+            key: None,
+            attributes: Vec::new(),
+            comments: Vec::new(),
+        }
+    }
+
+    /// The buckets, in the order their keys were first seen
+    fn into_buckets(self) -> IndexMap<Option<String>, C> {
+        self.buckets
+    }
+}
+
+impl<C: Callbacks, F: FnMut() -> C> Callbacks for AttributePartitioner<C, F> {
+    fn start_object(&mut self) {
+        self.key = None;
+        self.attributes.clear();
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.attribute_at(AttributeSpan::default(), name, value);
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        if self.key.is_none() && name.eq_ignore_ascii_case(&self.attribute) {
+            self.key = Some(String::from_utf8_lossy(value).trim().to_string());
+        }
+        self.attributes.push((span, name.to_vec(), value.to_vec()));
+    }
+
+    fn end_object(&mut self) {
+        let bucket = self
+            .buckets
+            .entry(self.key.take())
+            .or_insert_with(&mut self.new_bucket);
+
+        for comment in self.comments.drain(..) {
+            bucket.comment(&comment);
+        }
+        let class = self
+            .attributes
+            .first()
+            .map_or(&b""[..], |(_, name, _)| name);
+        bucket.start_object_class(class);
+        for (span, name, value) in &self.attributes {
+            bucket.attribute_at(*span, name, value);
+        }
+        bucket.end_object();
+    }
+
+    fn comment(&mut self, comment: &[u8]) {
+        self.comments.push(comment.to_vec());
+    }
+}
+
+/// Read RPSL data into one DataFrame per value of `attribute`, e.g. per `source`
+/// when a file mixes objects of several registries
+///
+/// Frames are keyed on the trimmed value of the first occurrence of the attribute
+/// in each object (matched case-insensitively), so a multi-valued attribute routes
+/// by its first value; every value is still read into the frame. Objects without
+/// the attribute are in the frame keyed `None`. Frames are in the order their
+/// keys are first seen, and hold the columns of a read with `schema`, or of a
+/// schema-less read if `None`, with `options`.
+///
+/// Each object is buffered before it is routed, and every frame is built in
+/// memory at once.
+pub fn read_rpsl_partitioned_from_reader<R: BufRead>(
+    reader: R,
+    attribute: &str,
+    schema: Option<&Schema>,
+    options: &ReadOptions,
+) -> Result<IndexMap<Option<String>, DataFrame>, RpslError> {
+    match schema {
+        None => {
+            let partitioner =
+                AttributePartitioner::new(attribute, || PolarsBuilder::from_options(options));
+            let buckets = parse_with_options(reader, partitioner, options)?.into_buckets();
+            Ok(buckets
+                .into_iter()
+                .map(|(key, builder)| (key, builder.build()))
+                .collect())
+        }
+        Some(schema) => {
+            // Checks the schema up front, so that creating buckets can't fail
+            SchemaPolarsBuilder::from_options(schema, options)?;
+            let partitioner = AttributePartitioner::new(attribute, || {
+                FirstError::new(
+                    SchemaPolarsBuilder::from_options(schema, options).expect("schema was checked"),
+                )
+            });
+            let buckets = parse_with_options(reader, partitioner, options)?.into_buckets();
+            buckets
+                .into_iter()
+                .map(|(key, builder)| Ok((key, builder.into_result()?.build())))
+                .collect()
+        }
+    }
+}
+
+/// Read an RPSL file into one DataFrame per value of `attribute`, see
+/// [`read_rpsl_partitioned_from_reader`]
+pub fn read_rpsl_partitioned_from_path<P: AsRef<Path>>(
+    path: P,
+    attribute: &str,
+    schema: Option<&Schema>,
+    options: &ReadOptions,
+) -> Result<IndexMap<Option<String>, DataFrame>, RpslError> {
+    let reader = input::open_path(path.as_ref(), options)?;
+    read_rpsl_partitioned_from_reader(reader, attribute, schema, options)
+}
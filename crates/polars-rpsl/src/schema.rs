@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::net::Ipv6Addr;
 
 use indexmap::IndexMap;
 use polars::{
@@ -10,9 +11,12 @@ use polars_arrow::{
     datatypes::ArrowDataType,
     offset::OffsetsBuffer,
 };
-use rpsl_parser::Callbacks;
+use rpsl_parser::{AttributeSpan, TryCallbacks};
 
-use crate::RpslError;
+use crate::object_columns::{
+    ByteLenColumn, HashColumn, HashOrder, MaxContinuationColumn, ObjectIdColumn,
+};
+use crate::{ReadOptions, RpslError};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum ColumnType {
@@ -20,6 +24,63 @@ enum ColumnType {
     ListString,
 }
 
+/// What to do with values of IPv6 columns that are not an IPv6 address or prefix,
+/// see [`ReadOptions::ipv6_columns`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidIpv6 {
+    /// Keep them as they are
+    #[default]
+    Keep,
+
+    /// Treat them as missing, so they produce null / no list element
+    Null,
+}
+
+/// Canonical (RFC 5952) spelling of an IPv6 address or `address/length` prefix,
+/// e.g. `2001:db8::/32` for `2001:0DB8:0000::/32`. Host bits are left as they are.
+fn normalize_ipv6(value: &str) -> Option<String> {
+    let value = value.trim();
+    match value.split_once('/') {
+        Some((addr, len)) => {
+            let addr: Ipv6Addr = addr.trim().parse().ok()?;
+            let len: u8 = len.trim().parse().ok().filter(|len| *len <= 128)?;
+            Some(format!("{addr}/{len}"))
+        }
+        None => Some(value.parse::<Ipv6Addr>().ok()?.to_string()),
+    }
+}
+
+/// Build a schema from `(name, dtype)` pairs, where dtype is one of the accepted
+/// dtype strings: `"str"`/`"string"`/`"utf8"` for String columns and
+/// `"list[str]"`/`"list[string]"`/`"list[utf8]"` for List(String) columns
+/// (case-insensitive)
+pub fn schema_from_columns<N, D>(
+    columns: impl IntoIterator<Item = (N, D)>,
+) -> Result<Schema, RpslError>
+where
+    N: AsRef<str>,
+    D: AsRef<str>,
+{
+    let mut schema = Schema::default();
+    for (name, dtype) in columns {
+        let (name, dtype) = (name.as_ref(), dtype.as_ref());
+        let parsed = match dtype.trim().to_ascii_lowercase().as_str() {
+            "str" | "string" | "utf8" => DataType::String,
+            "list[str]" | "list[string]" | "list[utf8]" => {
+                DataType::List(Box::new(DataType::String))
+            }
+            _ => {
+                return Err(RpslError::UnsupportedType {
+                    column: name.to_string(),
+                    dtype: dtype.to_string(),
+                });
+            }
+        };
+        schema.with_column(name.into(), parsed);
+    }
+    Ok(schema)
+}
+
 pub(crate) struct SchemaPolarsBuilder {
     /// Column names in schema order
     columns: IndexMap<String, ColumnType>,
@@ -36,8 +97,27 @@ pub(crate) struct SchemaPolarsBuilder {
     /// Current row number (for error reporting)
     row_count: usize,
 
-    /// Error that occurred during parsing (if any)
-    error: Option<RpslError>,
+    /// Treat empty attribute values as missing
+    empty_as_null: bool,
+
+    /// Fail on attributes that are not in the schema
+    reject_unknown_attributes: bool,
+
+    /// Values for columns whose attribute an object doesn't have
+    defaults: HashMap<String, String>,
+
+    /// Columns whose values are IPv6 addresses or prefixes to normalize
+    ipv6_columns: HashSet<String>,
+
+    invalid_ipv6: InvalidIpv6,
+
+    max_continuation: Option<MaxContinuationColumn>,
+
+    hash: Option<HashColumn>,
+
+    object_id: Option<ObjectIdColumn>,
+
+    byte_len: Option<ByteLenColumn>,
 }
 
 impl SchemaPolarsBuilder {
@@ -81,27 +161,152 @@ impl SchemaPolarsBuilder {
             list_builders,
             current_object: HashMap::new(),
             row_count: 0,
-            error: None,
+            empty_as_null: false,
+            reject_unknown_attributes: false,
+            defaults: HashMap::new(),
+            ipv6_columns: HashSet::new(),
+            invalid_ipv6: InvalidIpv6::Keep,
+            max_continuation: None,
+            hash: None,
+            object_id: None,
+            byte_len: None,
         })
     }
 
-    pub fn build(self) -> Result<DataFrame, RpslError> {
-        if let Some(err) = self.error {
-            return Err(err);
+    /// Create a builder configured by the schema-based [`ReadOptions`]
+    pub fn from_options(schema: &Schema, options: &ReadOptions) -> Result<Self, RpslError> {
+        let mut builder = Self::new(schema)?
+            .with_empty_as_null(options.empty_as_null)
+            .with_reject_unknown_attributes(options.reject_unknown_attributes)
+            .with_defaults(options.defaults.clone())
+            .with_ipv6_columns(&options.ipv6_columns, options.invalid_ipv6)
+            .with_force_list(&options.force_list);
+        if let Some(name) = &options.max_continuation_column {
+            builder = builder.with_max_continuation_column(name);
+        }
+        if let Some(name) = &options.hash_column {
+            builder = builder.with_hash_column(name, options.hash_order);
         }
+        if let Some(name) = &options.object_id_column {
+            builder = builder.with_object_id_column(name);
+        }
+        if let Some(name) = &options.byte_len_column {
+            builder = builder.with_byte_len_column(name);
+        }
+        Ok(builder)
+    }
+
+    /// Treat attributes with an empty (or whitespace-only) value as missing, so
+    /// they produce null / no list element. By default they are kept as `""`.
+    pub fn with_empty_as_null(mut self, empty_as_null: bool) -> Self {
+        self.empty_as_null = empty_as_null;
+        self
+    }
+
+    /// Fail with [`RpslError::UnknownAttribute`] on attributes that are not in the
+    /// schema (or forced to lists), e.g. to check that a file only has expected
+    /// attributes. By default they are ignored.
+    pub fn with_reject_unknown_attributes(mut self, reject: bool) -> Self {
+        self.reject_unknown_attributes = reject;
+        self
+    }
 
+    /// Values to use for columns whose attribute an object doesn't have, instead of
+    /// null (for String columns) or an empty list (for List(String) columns, which
+    /// get a one-element list). Columns without a default are unaffected.
+    pub fn with_defaults(mut self, defaults: HashMap<String, String>) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Write the values of these columns in canonical RFC 5952 form, e.g.
+    /// `2001:db8::/32` for `2001:0DB8:0000::/32`, so that `route6` and `inet6num`
+    /// prefixes compare equal however they were spelled. Values that are not an
+    /// IPv6 address or prefix are handled according to `invalid`.
+    pub fn with_ipv6_columns<S: AsRef<str>>(mut self, names: &[S], invalid: InvalidIpv6) -> Self {
+        self.ipv6_columns = names.iter().map(|name| name.as_ref().to_string()).collect();
+        self.invalid_ipv6 = invalid;
+        self
+    }
+
+    /// Read the named attributes as List(String) columns, so that single occurrences
+    /// become one-element lists. String columns in the schema are switched to
+    /// List(String), and names not in the schema are added as List(String) columns
+    /// after the schema columns.
+    pub fn with_force_list<S: AsRef<str>>(mut self, names: &[S]) -> Self {
+        for name in names {
+            let name = name.as_ref();
+            match self.columns.get_mut(name) {
+                Some(ColumnType::ListString) => continue,
+                Some(col_type) => {
+                    *col_type = ColumnType::ListString;
+                    self.string_builders.remove(name);
+                }
+                None => {
+                    self.columns
+                        .insert(name.to_string(), ColumnType::ListString);
+                }
+            }
+            self.list_builders.insert(
+                name.to_string(),
+                (MutableUtf8Array::<i64>::new(), vec![0i64]),
+            );
+        }
+        self
+    }
+
+    /// Add a `UInt32` column with the given name after the schema columns holding,
+    /// for each object, the most continuation lines folded into any one of its
+    /// attributes (including attributes not in the schema)
+    pub fn with_max_continuation_column(mut self, name: &str) -> Self {
+        self.max_continuation = Some(MaxContinuationColumn::new(name));
+        self
+    }
+
+    /// Add a `UInt64` column with the given name after the schema columns holding
+    /// a hash of each object's attributes (including attributes not in the schema)
+    pub fn with_hash_column(mut self, name: &str, order: HashOrder) -> Self {
+        self.hash = Some(HashColumn::new(name, order));
+        self
+    }
+
+    /// Add a `UInt64` column with the given name after the schema columns numbering
+    /// the rows from 0, as a join key with other frames built from the same input
+    pub fn with_object_id_column(mut self, name: &str) -> Self {
+        self.object_id = Some(ObjectIdColumn::new(name));
+        self
+    }
+
+    /// Add a `UInt64` column with the given name after the schema columns holding
+    /// the number of input bytes each object's attribute lines take up (including
+    /// attributes not in the schema), see
+    /// [`PolarsBuilder::with_byte_len_column`](crate::PolarsBuilder::with_byte_len_column)
+    pub fn with_byte_len_column(mut self, name: &str) -> Self {
+        self.byte_len = Some(ByteLenColumn::new(name));
+        self
+    }
+
+    pub fn build(mut self) -> DataFrame {
+        self.drain()
+    }
+
+    /// Build a DataFrame from the objects seen so far and reset the column
+    /// builders to empty, so the builder can keep receiving objects
+    pub fn drain(&mut self) -> DataFrame {
         let mut series_vec = Vec::new();
 
         for (name, col_type) in &self.columns {
             let series = match col_type {
                 ColumnType::String => {
-                    let array = self.string_builders.get(name).unwrap().clone();
+                    let array = std::mem::take(self.string_builders.get_mut(name).unwrap());
                     let utf8_array: polars_arrow::array::Utf8Array<i64> = array.into();
                     Series::from_arrow(name.as_str().into(), Box::new(utf8_array))
                         .expect("Failed to create string series")
                 }
                 ColumnType::ListString => {
-                    let (values_array, offsets) = self.list_builders.get(name).unwrap().clone();
+                    let (values_builder, offsets) = self.list_builders.get_mut(name).unwrap();
+                    let values_array = std::mem::take(values_builder);
+                    let offsets = std::mem::replace(offsets, vec![0i64]);
                     let utf8_array: polars_arrow::array::Utf8Array<i64> = values_array.into();
 
                     let offsets_buffer = unsafe { OffsetsBuffer::new_unchecked(offsets.into()) };
@@ -123,79 +328,359 @@ impl SchemaPolarsBuilder {
             series_vec.push(series.into());
         }
 
-        Ok(DataFrame::new(series_vec).expect("Failed to create DataFrame"))
+        if let Some(max_continuation) = &mut self.max_continuation {
+            series_vec.push(max_continuation.drain());
+        }
+        if let Some(hash) = &mut self.hash {
+            series_vec.push(hash.drain());
+        }
+        if let Some(object_id) = &mut self.object_id {
+            series_vec.push(object_id.drain());
+        }
+        if let Some(byte_len) = &mut self.byte_len {
+            series_vec.push(byte_len.drain());
+        }
+
+        DataFrame::new(series_vec).expect("Failed to create DataFrame")
     }
 }
 
-impl Callbacks for SchemaPolarsBuilder {
-    fn start_object(&mut self) {
+impl TryCallbacks for SchemaPolarsBuilder {
+    type Error = RpslError;
+
+    fn start_object(&mut self) -> Result<(), RpslError> {
         self.current_object.clear();
+        if let Some(max_continuation) = &mut self.max_continuation {
+            max_continuation.start_object();
+        }
+        if let Some(hash) = &mut self.hash {
+            hash.start_object();
+        }
+        if let Some(byte_len) = &mut self.byte_len {
+            byte_len.start_object();
+        }
+        Ok(())
     }
 
-    fn attribute(&mut self, name: &[u8], value: &[u8]) {
-        if self.error.is_some() {
-            return;
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> Result<(), RpslError> {
+        if let Some(hash) = &mut self.hash {
+            hash.attribute(name, value);
         }
 
         let name_str = String::from_utf8_lossy(name).to_string();
-        let value_str = String::from_utf8_lossy(value).to_string();
+        if self.reject_unknown_attributes && !self.columns.contains_key(&name_str) {
+            return Err(RpslError::UnknownAttribute {
+                attr: name_str,
+                row: self.row_count,
+            });
+        }
+
+        if self.empty_as_null && value.trim_ascii().is_empty() {
+            return Ok(());
+        }
+
+        let mut value_str = String::from_utf8_lossy(value).to_string();
+        if self.ipv6_columns.contains(&name_str) {
+            match normalize_ipv6(&value_str) {
+                Some(normalized) => value_str = normalized,
+                None if self.invalid_ipv6 == InvalidIpv6::Null => return Ok(()),
+                None => {}
+            }
+        }
 
         // Only collect attributes that are in the schema
         if self.columns.contains_key(&name_str) {
             self.current_object
                 .entry(name_str)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push(value_str);
         }
+
+        Ok(())
     }
 
-    fn end_object(&mut self) {
-        if self.error.is_some() {
-            return;
+    fn attribute_at(
+        &mut self,
+        span: AttributeSpan,
+        name: &[u8],
+        value: &[u8],
+    ) -> Result<(), RpslError> {
+        if let Some(max_continuation) = &mut self.max_continuation {
+            max_continuation.attribute(span);
+        }
+        if let Some(byte_len) = &mut self.byte_len {
+            byte_len.attribute(span);
+        }
+        self.attribute(name, value)
+    }
+
+    fn end_object(&mut self) -> Result<(), RpslError> {
+        // Check before pushing anything, so that every column advances by exactly
+        // one entry per object or none does
+        for (name, col_type) in &self.columns {
+            if *col_type == ColumnType::String
+                && self
+                    .current_object
+                    .get(name)
+                    .is_some_and(|vals| vals.len() > 1)
+            {
+                return Err(RpslError::DuplicateSingleValue {
+                    attr: name.clone(),
+                    row: self.row_count,
+                });
+            }
         }
 
-        // Process each column in schema order
+        // Process each column in schema order, pushing null (or an empty list) for
+        // attributes the object doesn't have
         for (name, col_type) in &self.columns {
             let values = self.current_object.get(name);
+            let default = self.defaults.get(name);
 
             match col_type {
                 ColumnType::String => {
                     let builder = self.string_builders.get_mut(name).unwrap();
                     match values {
-                        None => builder.push::<&str>(None),
-                        Some(vals) if vals.is_empty() => builder.push::<&str>(None),
-                        Some(vals) if vals.len() == 1 => builder.push(Some(&vals[0])),
-                        Some(_) => {
-                            self.error = Some(RpslError::DuplicateSingleValue {
-                                attr: name.clone(),
-                                row: self.row_count,
-                            });
-                            return;
-                        }
+                        Some(vals) if !vals.is_empty() => builder.push(Some(&vals[0])),
+                        _ => builder.push(default),
                     }
                 }
                 ColumnType::ListString => {
                     let (values_builder, offsets) = self.list_builders.get_mut(name).unwrap();
                     match values {
-                        None => {
-                            // Empty list - just update offset
-                            offsets.push(values_builder.len() as i64);
-                        }
-                        Some(vals) if vals.is_empty() => {
-                            // Empty list - just update offset
-                            offsets.push(values_builder.len() as i64);
-                        }
-                        Some(vals) => {
+                        Some(vals) if !vals.is_empty() => {
                             for val in vals {
                                 values_builder.push(Some(val.as_str()));
                             }
                             offsets.push(values_builder.len() as i64);
                         }
+                        _ => {
+                            // Empty list (or the default) - just update offset
+                            if let Some(default) = default {
+                                values_builder.push(Some(default));
+                            }
+                            offsets.push(values_builder.len() as i64);
+                        }
                     }
                 }
             }
         }
 
+        if let Some(max_continuation) = &mut self.max_continuation {
+            max_continuation.end_object();
+        }
+        if let Some(hash) = &mut self.hash {
+            hash.end_object();
+        }
+        if let Some(object_id) = &mut self.object_id {
+            object_id.end_object();
+        }
+        if let Some(byte_len) = &mut self.byte_len {
+            byte_len.end_object();
+        }
+
         self.row_count += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::*;
+    use rpsl_parser::{FirstError, RpslParser};
+
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::from_iter([
+            Field::new("route".into(), DataType::String),
+            Field::new("origin".into(), DataType::String),
+            Field::new("mnt-by".into(), DataType::List(Box::new(DataType::String))),
+        ])
+    }
+
+    #[test]
+    fn test_drain_matches_build() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\nmnt-by: A\nmnt-by: B\n\n\
+            route: 198.51.100.0/24\norigin: AS2\n\n\
+            aut-num: AS1\n\n\
+            route: 203.0.113.0/24\nmnt-by: C\n\n\
+            route: 192.0.2.128/25\norigin: AS3\n";
+        let builder = || {
+            let builder = SchemaPolarsBuilder::new(&schema())
+                .unwrap()
+                .with_object_id_column("id");
+            RpslParser::new(FirstError::new(builder))
+        };
+
+        let mut parser = builder();
+        parser.parse(&input[..]).unwrap();
+        let expected = parser.into_callbacks().into_result().unwrap().build();
+
+        let mut parser = builder();
+        let mut reader = &input[..];
+        let mut df = DataFrame::empty();
+        let mut more = true;
+        while more {
+            for _ in 0..2 {
+                more = parser.parse_object(&mut reader).unwrap();
+            }
+            let batch = parser.callbacks_mut().inner_mut().drain();
+            assert!(batch.height() <= 2);
+            df = if df.width() == 0 {
+                batch
+            } else {
+                df.vstack(&batch).unwrap()
+            };
+        }
+
+        assert_eq!(df.height(), 5);
+        assert_eq!(
+            df.column("id").unwrap().u64().unwrap().to_vec(),
+            [Some(0), Some(1), Some(2), Some(3), Some(4)]
+        );
+        assert!(df.equals_missing(&expected), "{df}\n{expected}");
+    }
+
+    #[test]
+    fn test_normalize_ipv6() {
+        assert_eq!(
+            normalize_ipv6("2001:0DB8:0000::/32").as_deref(),
+            Some("2001:db8::/32")
+        );
+        assert_eq!(
+            normalize_ipv6(" 2001:DB8:0:0:0:0:0:1 ").as_deref(),
+            Some("2001:db8::1")
+        );
+        assert_eq!(
+            normalize_ipv6("2001:db8::/128").as_deref(),
+            Some("2001:db8::/128")
+        );
+        assert_eq!(normalize_ipv6("2001:db8::/129"), None);
+        assert_eq!(normalize_ipv6("2001:db8::/x"), None);
+        assert_eq!(normalize_ipv6("192.0.2.0/24"), None);
+        assert_eq!(normalize_ipv6("not an address"), None);
+    }
+
+    #[test]
+    fn test_ipv6_columns() {
+        let input = b"route6: 2001:0DB8:0000::/32\n\n\
+            route6: 2001:db8::/129\n\n\
+            route6: bogus\n";
+        let schema = Schema::from_iter([Field::new("route6".into(), DataType::String)]);
+        let read = |invalid| {
+            let builder = SchemaPolarsBuilder::new(&schema)
+                .unwrap()
+                .with_ipv6_columns(&["route6"], invalid);
+            let mut parser = RpslParser::new(FirstError::new(builder));
+            parser.parse(&input[..]).unwrap();
+            let df = parser.into_callbacks().into_result().unwrap().build();
+            df.column("route6")
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.map(str::to_string))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            read(InvalidIpv6::Keep),
+            [
+                Some("2001:db8::/32".to_string()),
+                Some("2001:db8::/129".to_string()),
+                Some("bogus".to_string())
+            ]
+        );
+        assert_eq!(
+            read(InvalidIpv6::Null),
+            [Some("2001:db8::/32".to_string()), None, None]
+        );
+    }
+
+    #[test]
+    fn test_reject_unknown_attributes() {
+        let read = |input: &[u8]| {
+            let builder = SchemaPolarsBuilder::new(&schema())
+                .unwrap()
+                .with_force_list(&["remarks"])
+                .with_reject_unknown_attributes(true);
+            let mut parser = RpslParser::new(FirstError::new(builder));
+            parser.parse(input).unwrap();
+            parser
+                .into_callbacks()
+                .into_result()
+                .map(|builder| builder.build())
+        };
+
+        let df = read(b"route: 192.0.2.0/24\nremarks: forced\n\nroute: 198.51.100.0/24\n").unwrap();
+        assert_eq!(df.height(), 2);
+
+        let err = read(b"route: 192.0.2.0/24\n\nroute: 198.51.100.0/24\ndescr: x\n").unwrap_err();
+        assert!(
+            matches!(&err, RpslError::UnknownAttribute { attr, row: 1 } if attr == "descr"),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_defaults() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\nmnt-by: A\n\nroute: 198.51.100.0/24\n";
+        let defaults = HashMap::from([
+            ("origin".to_string(), "AS0".to_string()),
+            ("mnt-by".to_string(), "NONE".to_string()),
+        ]);
+        let builder = SchemaPolarsBuilder::new(&schema())
+            .unwrap()
+            .with_defaults(defaults);
+        let mut parser = RpslParser::new(FirstError::new(builder));
+        parser.parse(&input[..]).unwrap();
+        let df = parser.into_callbacks().into_result().unwrap().build();
+
+        let origin = df.column("origin").unwrap().str().unwrap();
+        assert_eq!(
+            origin.into_iter().collect::<Vec<_>>(),
+            [Some("AS1"), Some("AS0")]
+        );
+        let mnt_by = df.column("mnt-by").unwrap().explode(false).unwrap();
+        assert_eq!(
+            mnt_by.str().unwrap().into_iter().collect::<Vec<_>>(),
+            [Some("A"), Some("NONE")]
+        );
+    }
+
+    #[test]
+    fn test_empty_as_null() {
+        let read = |empty_as_null| {
+            let mut builder = SchemaPolarsBuilder::new(&schema())
+                .unwrap()
+                .with_empty_as_null(empty_as_null);
+            builder.start_object().unwrap();
+            builder.attribute(b"route", b"192.0.2.0/24").unwrap();
+            builder.attribute(b"origin", b" \t").unwrap();
+            builder.attribute(b"mnt-by", b"").unwrap();
+            builder.attribute(b"mnt-by", b"A").unwrap();
+            builder.end_object().unwrap();
+            builder.build()
+        };
+
+        let df = read(true);
+        assert_eq!(df.column("origin").unwrap().str().unwrap().get(0), None);
+        let mnt_by = df.column("mnt-by").unwrap().explode(false).unwrap();
+        assert_eq!(
+            mnt_by.str().unwrap().into_iter().collect::<Vec<_>>(),
+            [Some("A")]
+        );
+
+        let df = read(false);
+        assert_eq!(
+            df.column("origin").unwrap().str().unwrap().get(0),
+            Some(" \t")
+        );
+        let mnt_by = df.column("mnt-by").unwrap().explode(false).unwrap();
+        assert_eq!(
+            mnt_by.str().unwrap().into_iter().collect::<Vec<_>>(),
+            [Some(""), Some("A")]
+        );
     }
 }
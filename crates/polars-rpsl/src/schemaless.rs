@@ -1,18 +1,74 @@
+use std::collections::HashSet;
+
 use polars::{
     frame::DataFrame,
     prelude::{ArrowField, LargeListArray, Series},
 };
 use polars_arrow::{
-    array::{Array, MutableUtf8Array, StructArray},
+    array::{Array, MutablePrimitiveArray, MutableUtf8Array, PrimitiveArray, StructArray},
     datatypes::ArrowDataType,
     offset::OffsetsBuffer,
 };
-use rpsl_parser::Callbacks;
+use rpsl_parser::{AttributeSpan, Callbacks};
+
+use crate::ReadOptions;
+use crate::object_columns::{
+    ByteLenColumn, CommentsColumn, HashColumn, HashOrder, MaxContinuationColumn, ObjectIdColumn,
+};
+
+/// What to do with attributes repeating the class (first attribute name) of their
+/// object, e.g. a second `route:` line in a corrupted object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RepeatedClass {
+    /// Keep them like any other attribute
+    #[default]
+    Keep,
 
-pub(crate) struct PolarsBuilder {
+    /// Keep only the first, so that it is the canonical class and key
+    Drop,
+
+    /// Keep them, but count them in [`PolarsBuilder::repeated_classes`]
+    Warn,
+}
+
+/// Callbacks that build a schema-less DataFrame with a single `attributes` column
+pub struct PolarsBuilder {
     names: MutableUtf8Array<i32>,
     values: MutableUtf8Array<i64>,
     object_starts: Vec<i64>,
+
+    /// Line number of each attribute, if enabled
+    lines: Option<MutablePrimitiveArray<u32>>,
+
+    /// If set, only attributes with these names are kept
+    whitelist: Option<HashSet<Vec<u8>>>,
+
+    /// Skip objects left without attributes, e.g. after whitelist filtering
+    drop_empty_objects: bool,
+
+    max_continuation: Option<MaxContinuationColumn>,
+
+    hash: Option<HashColumn>,
+
+    comments: Option<CommentsColumn>,
+
+    object_id: Option<ObjectIdColumn>,
+
+    byte_len: Option<ByteLenColumn>,
+
+    repeated_class: RepeatedClass,
+
+    /// Class of the current object, when checking for repeats
+    class: Option<Vec<u8>>,
+
+    /// Repeated class attributes seen (and dropped, with [`RepeatedClass::Drop`])
+    repeated_classes: u64,
+}
+
+impl Default for PolarsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PolarsBuilder {
@@ -21,25 +77,204 @@ impl PolarsBuilder {
             names: MutableUtf8Array::<i32>::new(),
             values: MutableUtf8Array::<i64>::new(),
             object_starts: vec![0],
+            lines: None,
+            whitelist: None,
+            drop_empty_objects: false,
+            max_continuation: None,
+            hash: None,
+            comments: None,
+            object_id: None,
+            byte_len: None,
+            repeated_class: RepeatedClass::Keep,
+            class: None,
+            repeated_classes: 0,
+        }
+    }
+
+    /// Create a builder configured by the schema-less [`ReadOptions`]
+    pub(crate) fn from_options(options: &ReadOptions) -> Self {
+        let mut builder = Self::from_attribute_options(options);
+        if let Some(name) = &options.max_continuation_column {
+            builder = builder.with_max_continuation_column(name);
         }
+        if let Some(name) = &options.hash_column {
+            builder = builder.with_hash_column(name, options.hash_order);
+        }
+        if let Some(name) = &options.object_id_column {
+            builder = builder.with_object_id_column(name);
+        }
+        if let Some(name) = &options.byte_len_column {
+            builder = builder.with_byte_len_column(name);
+        }
+        builder
+    }
+
+    /// Create a builder configured by the [`ReadOptions`] that only schema-less
+    /// reads have, leaving out the per-object columns shared with schema-based reads
+    pub(crate) fn from_attribute_options(options: &ReadOptions) -> Self {
+        Self::new()
+            .with_line_numbers(options.line_numbers)
+            .with_comments_column(options.comments_column)
+            .with_repeated_class(options.repeated_class)
+    }
+
+    /// Add a `line` field to the attribute structs holding the line number each
+    /// attribute starts on. It is null for attributes not reported by the parser.
+    pub fn with_line_numbers(mut self, line_numbers: bool) -> Self {
+        self.lines = line_numbers.then(MutablePrimitiveArray::new);
+        self
     }
 
-    pub fn build(self) -> DataFrame {
-        let names_array: polars_arrow::array::Utf8Array<i32> = self.names.into();
-        let values_array: polars_arrow::array::Utf8Array<i64> = self.values.into();
+    /// Only keep attributes with the given names (matched exactly). Objects whose
+    /// attributes are all filtered out still produce an empty row, unless
+    /// [`with_drop_empty_objects`](Self::with_drop_empty_objects) is set.
+    pub fn with_attribute_whitelist(mut self, names: &[&str]) -> Self {
+        self.whitelist = Some(names.iter().map(|name| name.as_bytes().to_vec()).collect());
+        self
+    }
 
-        let struct_fields = vec![
+    /// Don't produce rows for objects without any (kept) attributes
+    pub fn with_drop_empty_objects(mut self, drop_empty_objects: bool) -> Self {
+        self.drop_empty_objects = drop_empty_objects;
+        self
+    }
+
+    /// Add a `UInt32` column with the given name holding, for each object, the
+    /// most continuation lines folded into any one of its attributes
+    pub fn with_max_continuation_column(mut self, name: &str) -> Self {
+        self.max_continuation = Some(MaxContinuationColumn::new(name));
+        self
+    }
+
+    /// Add a `UInt64` column with the given name holding a hash of each object's
+    /// attributes (before whitelist filtering), e.g. to find changed objects
+    /// between two dumps
+    pub fn with_hash_column(mut self, name: &str, order: HashOrder) -> Self {
+        self.hash = Some(HashColumn::new(name, order));
+        self
+    }
+
+    /// Add a `comments` column of List(String) holding, for each object, the
+    /// comments seen since the previous object: comment-only lines within and
+    /// before the object, and comments trailing its lines
+    pub fn with_comments_column(mut self, comments: bool) -> Self {
+        self.comments = comments.then(CommentsColumn::new);
+        self
+    }
+
+    /// Add a `UInt64` column with the given name numbering the rows from 0, as a
+    /// join key with other frames built from the same input
+    pub fn with_object_id_column(mut self, name: &str) -> Self {
+        self.object_id = Some(ObjectIdColumn::new(name));
+        self
+    }
+
+    /// Add a `UInt64` column with the given name holding, for each object, the
+    /// number of input bytes its attribute lines take up: every attribute and
+    /// continuation line (before whitelist filtering), including line endings and
+    /// trailing comments, but not comment-only lines or the blank line after the
+    /// object
+    pub fn with_byte_len_column(mut self, name: &str) -> Self {
+        self.byte_len = Some(ByteLenColumn::new(name));
+        self
+    }
+
+    /// What to do with attributes repeating the class of their object (matched
+    /// case-insensitively). Defaults to [`RepeatedClass::Keep`].
+    pub fn with_repeated_class(mut self, repeated_class: RepeatedClass) -> Self {
+        self.repeated_class = repeated_class;
+        self
+    }
+
+    /// Number of attributes seen repeating the class of their object, with
+    /// [`RepeatedClass::Drop`] or [`RepeatedClass::Warn`]
+    pub fn repeated_classes(&self) -> u64 {
+        self.repeated_classes
+    }
+
+    pub fn build(mut self) -> DataFrame {
+        self.drain()
+    }
+
+    /// Build a DataFrame from the objects seen so far and reset the builder to
+    /// empty, so it can keep receiving objects. Must be called between objects.
+    pub fn drain(&mut self) -> DataFrame {
+        let attribute_capacity = self.names.len();
+        let object_capacity = self.object_starts.len();
+
+        let names = std::mem::replace(
+            &mut self.names,
+            MutableUtf8Array::<i32>::with_capacity(attribute_capacity),
+        );
+        let values = std::mem::replace(
+            &mut self.values,
+            MutableUtf8Array::<i64>::with_capacity(attribute_capacity),
+        );
+        let lines = self.lines.as_mut().map(|lines| {
+            std::mem::replace(
+                lines,
+                MutablePrimitiveArray::with_capacity(attribute_capacity),
+            )
+        });
+        let mut object_starts = Vec::with_capacity(object_capacity);
+        object_starts.push(0);
+        let object_starts = std::mem::replace(&mut self.object_starts, object_starts);
+
+        let mut df = Self::to_frame(names, values, lines, object_starts);
+        if let Some(max_continuation) = &mut self.max_continuation {
+            df.with_column(max_continuation.drain())
+                .expect("Failed to add max continuation column");
+        }
+        if let Some(hash) = &mut self.hash {
+            df.with_column(hash.drain())
+                .expect("Failed to add hash column");
+        }
+        if let Some(comments) = &mut self.comments {
+            df.with_column(comments.drain())
+                .expect("Failed to add comments column");
+        }
+        if let Some(object_id) = &mut self.object_id {
+            df.with_column(object_id.drain())
+                .expect("Failed to add object id column");
+        }
+        if let Some(byte_len) = &mut self.byte_len {
+            df.with_column(byte_len.drain())
+                .expect("Failed to add byte length column");
+        }
+        df
+    }
+
+    fn to_frame(
+        names: MutableUtf8Array<i32>,
+        values: MutableUtf8Array<i64>,
+        lines: Option<MutablePrimitiveArray<u32>>,
+        object_starts: Vec<i64>,
+    ) -> DataFrame {
+        let names_array: polars_arrow::array::Utf8Array<i32> = names.into();
+        let values_array: polars_arrow::array::Utf8Array<i64> = values.into();
+        let len = names_array.len();
+
+        let mut struct_fields = vec![
             ArrowField::new("name".into(), ArrowDataType::Utf8, false),
             ArrowField::new("value".into(), ArrowDataType::LargeUtf8, false),
         ];
+        let mut struct_values: Vec<Box<dyn Array>> =
+            vec![Box::new(names_array), Box::new(values_array)];
+
+        if let Some(lines) = lines {
+            let lines_array: PrimitiveArray<u32> = lines.into();
+            struct_fields.push(ArrowField::new("line".into(), ArrowDataType::UInt32, true));
+            struct_values.push(Box::new(lines_array));
+        }
+
         let struct_array = StructArray::new(
             ArrowDataType::Struct(struct_fields),
-            names_array.len(),
-            vec![Box::new(names_array), Box::new(values_array)],
+            len,
+            struct_values,
             None,
         );
 
-        let offsets = unsafe { OffsetsBuffer::new_unchecked(self.object_starts.into()) };
+        let offsets = unsafe { OffsetsBuffer::new_unchecked(object_starts.into()) };
         let list_array = LargeListArray::new(
             ArrowDataType::LargeList(Box::new(ArrowField::new(
                 "item".into(),
@@ -56,19 +291,238 @@ impl PolarsBuilder {
 
         DataFrame::new(vec![series.into()]).expect("Failed to create DataFrame")
     }
-}
 
-impl Callbacks for PolarsBuilder {
-    fn start_object(&mut self) {}
+    #[inline]
+    fn push(&mut self, name: &[u8], value: &[u8], line_number: Option<u32>) {
+        if let Some(hash) = &mut self.hash {
+            hash.attribute(name, value);
+        }
+
+        if self.repeated_class != RepeatedClass::Keep {
+            match &self.class {
+                None => self.class = Some(name.to_vec()),
+                Some(class) if class.eq_ignore_ascii_case(name) => {
+                    self.repeated_classes += 1;
+                    if self.repeated_class == RepeatedClass::Drop {
+                        return;
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(whitelist) = &self.whitelist
+            && !whitelist.contains(name)
+        {
+            return;
+        }
 
-    fn attribute(&mut self, name: &[u8], value: &[u8]) {
         self.names
             .push(Some(String::from_utf8_lossy(name).as_ref()));
         self.values
             .push(Some(String::from_utf8_lossy(value).as_ref()));
+        if let Some(lines) = &mut self.lines {
+            lines.push(line_number);
+        }
+    }
+}
+
+impl Callbacks for PolarsBuilder {
+    fn start_object(&mut self) {
+        if let Some(max_continuation) = &mut self.max_continuation {
+            max_continuation.start_object();
+        }
+        if let Some(hash) = &mut self.hash {
+            hash.start_object();
+        }
+        if let Some(byte_len) = &mut self.byte_len {
+            byte_len.start_object();
+        }
+        self.class = None;
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.push(name, value, None);
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        if let Some(max_continuation) = &mut self.max_continuation {
+            max_continuation.attribute(span);
+        }
+        if let Some(byte_len) = &mut self.byte_len {
+            byte_len.attribute(span);
+        }
+        self.push(name, value, Some(span.line_number));
     }
 
     fn end_object(&mut self) {
-        self.object_starts.push(self.names.len() as i64);
+        let end = self.names.len() as i64;
+        if self.drop_empty_objects && self.object_starts.last() == Some(&end) {
+            return;
+        }
+        self.object_starts.push(end);
+        if let Some(max_continuation) = &mut self.max_continuation {
+            max_continuation.end_object();
+        }
+        if let Some(hash) = &mut self.hash {
+            hash.end_object();
+        }
+        if let Some(comments) = &mut self.comments {
+            comments.end_object();
+        }
+        if let Some(object_id) = &mut self.object_id {
+            object_id.end_object();
+        }
+        if let Some(byte_len) = &mut self.byte_len {
+            byte_len.end_object();
+        }
+    }
+
+    fn comment(&mut self, comment: &[u8]) {
+        if let Some(comments) = &mut self.comments {
+            comments.comment(comment);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::*;
+    use rpsl_parser::RpslParser;
+
+    use super::*;
+
+    /// Number of attributes in each row
+    fn list_lengths(df: &DataFrame) -> Vec<Option<u32>> {
+        let lengths = df
+            .column("attributes")
+            .unwrap()
+            .list()
+            .unwrap()
+            .lst_lengths();
+        lengths.into_iter().collect()
+    }
+
+    /// The `field` of each attribute, across rows
+    fn attribute_field(df: &DataFrame, field: &str) -> Series {
+        let attributes = df.column("attributes").unwrap().explode(true).unwrap();
+        attributes.struct_().unwrap().field_by_name(field).unwrap()
+    }
+
+    #[test]
+    fn test_drain() {
+        let builder = PolarsBuilder::new()
+            .with_line_numbers(true)
+            .with_object_id_column("id");
+        let mut parser = RpslParser::new(builder);
+
+        let mut reader = &b"route: 192.0.2.0/24\norigin: AS1\n\naut-num: AS1\n\n\
+            route: 198.51.100.0/24\norigin: AS2\nmnt-by: X\n"[..];
+        parser.parse_object(&mut reader).unwrap();
+        parser.parse_object(&mut reader).unwrap();
+        let first = parser.callbacks_mut().drain();
+        while parser.parse_object(&mut reader).unwrap() {}
+        let second = parser.into_callbacks().build();
+
+        assert_eq!(list_lengths(&first), [Some(2), Some(1)]);
+        assert_eq!(list_lengths(&second), [Some(3)]);
+
+        let ids = |df: &DataFrame| -> Vec<Option<u64>> {
+            df.column("id")
+                .unwrap()
+                .u64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        assert_eq!(ids(&first), [Some(0), Some(1)]);
+        assert_eq!(ids(&second), [Some(2)]);
+
+        assert_eq!(
+            attribute_field(&second, "name")
+                .str()
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [Some("route"), Some("origin"), Some("mnt-by")]
+        );
+        assert_eq!(
+            attribute_field(&second, "line").u32().unwrap().to_vec(),
+            [Some(6), Some(7), Some(8)]
+        );
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let input = b"% header\nroute: 192.0.2.0/24\ndescr: first\n second\n+\n\tthird\n\
+            origin: AS1\n\naut-num: AS1\nremarks: a\n b\n";
+        let builder = PolarsBuilder::new()
+            .with_line_numbers(true)
+            .with_max_continuation_column("continuations");
+        let mut parser = RpslParser::new(builder);
+        parser.parse(&input[..]).unwrap();
+        let df = parser.into_callbacks().build();
+
+        // Continued attributes report the line they start on
+        assert_eq!(
+            attribute_field(&df, "line").u32().unwrap().to_vec(),
+            [Some(2), Some(3), Some(7), Some(9), Some(10)]
+        );
+        assert_eq!(
+            df.column("continuations").unwrap().u32().unwrap().to_vec(),
+            [Some(3), Some(1)]
+        );
+    }
+
+    #[test]
+    fn test_attribute_whitelist() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\ndescr: x\n\n\
+            aut-num: AS1\nas-name: X\n\n\
+            route: 198.51.100.0/24\n";
+        let read = |drop_empty_objects| {
+            let builder = PolarsBuilder::new()
+                .with_attribute_whitelist(&["route", "origin"])
+                .with_drop_empty_objects(drop_empty_objects)
+                .with_object_id_column("id");
+            let mut parser = RpslParser::new(builder);
+            parser.parse(&input[..]).unwrap();
+            parser.into_callbacks().build()
+        };
+
+        let df = read(false);
+        assert_eq!(list_lengths(&df), [Some(2), Some(0), Some(1)]);
+        assert_eq!(
+            attribute_field(&df, "name")
+                .str()
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [Some("route"), Some("origin"), Some("route")]
+        );
+
+        let df = read(true);
+        assert_eq!(list_lengths(&df), [Some(2), Some(1)]);
+        assert_eq!(
+            df.column("id").unwrap().u64().unwrap().to_vec(),
+            [Some(0), Some(1)]
+        );
+    }
+
+    #[test]
+    fn test_repeated_class() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\nRoute: 198.51.100.0/24\n\n\
+            aut-num: AS1\nroute: 203.0.113.0/24\n";
+        let read = |repeated_class| {
+            let builder = PolarsBuilder::new().with_repeated_class(repeated_class);
+            let mut parser = RpslParser::new(builder);
+            parser.parse(&input[..]).unwrap();
+            let builder = parser.into_callbacks();
+            let repeated = builder.repeated_classes();
+            (list_lengths(&builder.build()), repeated)
+        };
+
+        assert_eq!(read(RepeatedClass::Keep), (vec![Some(3), Some(2)], 0));
+        assert_eq!(read(RepeatedClass::Warn), (vec![Some(3), Some(2)], 1));
+        assert_eq!(read(RepeatedClass::Drop), (vec![Some(2), Some(2)], 1));
     }
 }
@@ -0,0 +1,177 @@
+use std::any::Any;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+use datafusion::catalog::streaming::StreamingTable;
+use datafusion::catalog::{Session, TableProvider};
+use datafusion::error::{DataFusionError, Result};
+use datafusion::execution::{SendableRecordBatchStream, TaskContext};
+use datafusion::logical_expr::{Expr, TableType};
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::physical_plan::stream::RecordBatchReceiverStream;
+use datafusion::physical_plan::streaming::PartitionStream;
+use polars::prelude::Schema;
+
+use crate::arrow_rs::{arrow_schema, to_arrow_rs};
+use crate::{ReadOptions, RpslBatchReader, RpslError, input};
+
+/// DataFusion table scanning an RPSL file with a given schema
+///
+/// The file is read with [`RpslBatchReader`] each time the table is scanned, so
+/// nothing is held in memory between queries. Gzip-compressed files (`.gz`) are
+/// decompressed transparently. Filters and projections are applied by DataFusion
+/// after parsing; every object is parsed on each scan.
+#[derive(Debug)]
+pub struct RpslTableProvider {
+    table: StreamingTable,
+}
+
+impl RpslTableProvider {
+    /// Create a table over the file at `path`, producing batches of up to
+    /// `batch_size` objects with one column per schema field
+    pub fn try_new(
+        path: impl Into<PathBuf>,
+        schema: &Schema,
+        batch_size: usize,
+    ) -> Result<Self, RpslError> {
+        let arrow_schema = arrow_schema(schema)?;
+
+        let partition = RpslPartition {
+            path: path.into(),
+            schema: schema.clone(),
+            arrow_schema: arrow_schema.clone(),
+            batch_size,
+        };
+        let table = StreamingTable::try_new(arrow_schema, vec![Arc::new(partition)])?;
+        Ok(Self { table })
+    }
+}
+
+#[async_trait]
+impl TableProvider for RpslTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table.schema()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        self.table.scan(state, projection, filters, limit).await
+    }
+}
+
+/// The single partition of a table, reading the whole file
+#[derive(Debug)]
+struct RpslPartition {
+    path: PathBuf,
+    schema: Schema,
+    arrow_schema: SchemaRef,
+    batch_size: usize,
+}
+
+impl PartitionStream for RpslPartition {
+    fn schema(&self) -> &SchemaRef {
+        &self.arrow_schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let mut builder = RecordBatchReceiverStream::builder(self.arrow_schema.clone(), 2);
+        let tx = builder.tx();
+        let (path, schema, batch_size) = (self.path.clone(), self.schema.clone(), self.batch_size);
+
+        // Parsing is blocking I/O and CPU work, so it runs off the async runtime
+        builder.spawn_blocking(move || {
+            let reader = input::open_path(&path, &ReadOptions::default())?;
+            let batches = RpslBatchReader::new(reader, &schema, batch_size).map_err(external)?;
+            for batch in batches {
+                let batch = to_arrow_rs(batch.map_err(external)?)?;
+                if tx.blocking_send(Ok(batch)).is_err() {
+                    // The consumer is gone, e.g. because a limit was reached
+                    break;
+                }
+            }
+            Ok(())
+        });
+        builder.build()
+    }
+}
+
+fn external(err: RpslError) -> DataFusionError {
+    DataFusionError::External(Box::new(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::AsArray;
+    use arrow::compute::cast;
+    use arrow::datatypes::DataType as ArrowDataType;
+    use datafusion::prelude::SessionContext;
+    use polars::prelude::{DataType, Field};
+
+    use super::*;
+
+    #[test]
+    fn test_query_table() {
+        let path = std::env::temp_dir().join(format!("rpsl-table-{}.txt", std::process::id()));
+        std::fs::write(
+            &path,
+            "route: 192.0.2.0/24\norigin: AS1\n\n\
+            route: 198.51.100.0/24\norigin: AS2\n\n\
+            route: 203.0.113.0/24\norigin: AS2\n",
+        )
+        .unwrap();
+        let schema = Schema::from_iter([
+            Field::new("route".into(), DataType::String),
+            Field::new("origin".into(), DataType::String),
+        ]);
+        let table = RpslTableProvider::try_new(&path, &schema, 2).unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let batches = runtime
+            .block_on(async {
+                let ctx = SessionContext::new();
+                ctx.register_table("rpsl", Arc::new(table))?;
+                ctx.sql("SELECT route FROM rpsl WHERE origin = 'AS2' ORDER BY route")
+                    .await?
+                    .collect()
+                    .await
+            })
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let routes: Vec<_> = batches
+            .iter()
+            .flat_map(|batch| {
+                let routes = cast(batch.column(0), &ArrowDataType::Utf8).unwrap();
+                let routes = routes.as_string::<i32>();
+                routes
+                    .iter()
+                    .map(|route| route.map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(
+            routes,
+            [
+                Some("198.51.100.0/24".to_string()),
+                Some("203.0.113.0/24".to_string())
+            ]
+        );
+    }
+}
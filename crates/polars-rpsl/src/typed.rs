@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+use polars::{
+    frame::DataFrame,
+    prelude::{DataType, Schema},
+};
+use rpsl_parser::{AttributeSpan, Callbacks, TryCallbacks};
+
+use crate::schema::SchemaPolarsBuilder;
+use crate::{ReadOptions, RpslError};
+
+/// Callbacks that keep every object, noting for each attribute name (in order of
+/// first appearance) whether any object has it more than once
+#[derive(Default)]
+pub(crate) struct ObjectCollector {
+    objects: Vec<Vec<(AttributeSpan, String, String)>>,
+    current: Vec<(AttributeSpan, String, String)>,
+    repeated: IndexMap<String, bool>,
+}
+
+impl ObjectCollector {
+    /// The inferred schema: List(String) for attributes repeated within an object
+    /// and String otherwise, unless given in `dtypes`. Attributes in `dtypes` that
+    /// never occur are added after the others, in name order.
+    fn schema(&self, dtypes: &HashMap<String, DataType>) -> Schema {
+        let mut schema = Schema::default();
+        for (name, repeated) in &self.repeated {
+            let dtype = match dtypes.get(name) {
+                Some(dtype) => builder_dtype(dtype),
+                None if *repeated => DataType::List(Box::new(DataType::String)),
+                None => DataType::String,
+            };
+            schema.with_column(name.as_str().into(), dtype);
+        }
+
+        let mut missing: Vec<_> = dtypes
+            .iter()
+            .filter(|(name, _)| !self.repeated.contains_key(*name))
+            .collect();
+        missing.sort_by_key(|(name, _)| *name);
+        for (name, dtype) in missing {
+            schema.with_column(name.as_str().into(), builder_dtype(dtype));
+        }
+        schema
+    }
+
+    /// Replay the collected objects into a schema builder
+    fn replay(self, builder: &mut SchemaPolarsBuilder) -> Result<(), RpslError> {
+        for object in self.objects {
+            builder.start_object()?;
+            for (span, name, value) in &object {
+                builder.attribute_at(*span, name.as_bytes(), value.as_bytes())?;
+            }
+            builder.end_object()?;
+        }
+        Ok(())
+    }
+}
+
+impl Callbacks for ObjectCollector {
+    fn start_object(&mut self) {
+        self.current.clear();
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.attribute_at(AttributeSpan::default(), name, value);
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        self.current.push((
+            span,
+            String::from_utf8_lossy(name).into_owned(),
+            String::from_utf8_lossy(value).into_owned(),
+        ));
+    }
+
+    fn end_object(&mut self) {
+        let object = std::mem::take(&mut self.current);
+        for (i, (_, name, _)) in object.iter().enumerate() {
+            let repeated = object[..i].iter().any(|(_, other, _)| other == name);
+            let entry = self.repeated.entry(name.clone()).or_insert(false);
+            *entry |= repeated;
+        }
+        self.objects.push(object);
+    }
+}
+
+/// The dtype a column is built with before casting: List(String) for list types
+/// and String for anything else
+fn builder_dtype(dtype: &DataType) -> DataType {
+    match dtype {
+        DataType::List(_) => DataType::List(Box::new(DataType::String)),
+        _ => DataType::String,
+    }
+}
+
+/// Build a wide DataFrame from collected objects, casting the columns in `dtypes`
+pub(crate) fn build_typed(
+    collector: ObjectCollector,
+    dtypes: &HashMap<String, DataType>,
+    options: &ReadOptions,
+) -> Result<DataFrame, RpslError> {
+    let schema = collector.schema(dtypes);
+    let mut builder = SchemaPolarsBuilder::from_options(&schema, options)?;
+    collector.replay(&mut builder)?;
+
+    let mut df = builder.build();
+    for (name, dtype) in dtypes {
+        let column = df.column(name)?;
+        if column.dtype() != dtype {
+            let column = column.cast(dtype)?;
+            df.with_column(column)?;
+        }
+    }
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use polars::prelude::*;
+
+    use crate::{ReadOptions, read_rpsl_typed_schemaless_from_reader};
+
+    #[test]
+    fn test_read_typed_schemaless() {
+        let input = b"aut-num: AS1\nsize: 10\nmnt-by: A\nmnt-by: B\n\n\
+            aut-num: AS2\nsize: unknown\nremarks: x\n";
+        let dtypes = HashMap::from([
+            ("size".to_string(), DataType::UInt32),
+            ("weight".to_string(), DataType::Float64),
+        ]);
+        let df =
+            read_rpsl_typed_schemaless_from_reader(&input[..], &dtypes, &ReadOptions::default())
+                .unwrap();
+
+        assert_eq!(
+            df.get_column_names(),
+            ["aut-num", "size", "mnt-by", "remarks", "weight"]
+        );
+        assert_eq!(
+            df.dtypes(),
+            [
+                DataType::String,
+                DataType::UInt32,
+                DataType::List(Box::new(DataType::String)),
+                DataType::String,
+                DataType::Float64
+            ]
+        );
+        assert_eq!(
+            df.column("size").unwrap().u32().unwrap().to_vec(),
+            [Some(10), None]
+        );
+        assert_eq!(df.column("weight").unwrap().null_count(), 2);
+        assert_eq!(df.column("remarks").unwrap().null_count(), 1);
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use flate2::{Compression, write::GzEncoder};
+use polars::{
+    frame::DataFrame,
+    prelude::{DataType, ListChunked, Series, StringChunked},
+};
+use rpsl_parser::RpslWriter;
+
+use crate::RpslError;
+
+/// Where the attributes of each row come from
+enum Layout<'a> {
+    /// The schema-less `attributes` column of `{name, value}` structs
+    Attributes(&'a ListChunked),
+
+    /// One attribute per String or List(String) column, in column order
+    Columns(Vec<(&'a str, ColumnValues<'a>)>),
+}
+
+enum ColumnValues<'a> {
+    String(&'a StringChunked),
+    List(&'a ListChunked),
+}
+
+impl<'a> Layout<'a> {
+    fn new(df: &'a DataFrame) -> Result<Self, RpslError> {
+        if let Ok(column) = df.column("attributes")
+            && let DataType::List(inner) = column.dtype()
+            && matches!(**inner, DataType::Struct(_))
+        {
+            return Ok(Layout::Attributes(column.as_materialized_series().list()?));
+        }
+
+        let mut columns = Vec::new();
+        for column in df.get_columns() {
+            let values = match column.dtype() {
+                DataType::String => ColumnValues::String(column.as_materialized_series().str()?),
+                DataType::List(inner) if **inner == DataType::String => {
+                    ColumnValues::List(column.as_materialized_series().list()?)
+                }
+                dtype => {
+                    return Err(RpslError::UnsupportedType {
+                        column: column.name().to_string(),
+                        dtype: dtype.to_string(),
+                    });
+                }
+            };
+            columns.push((column.name().as_str(), values));
+        }
+        Ok(Layout::Columns(columns))
+    }
+
+    /// Collect the attributes of a row, skipping nulls
+    fn row(&self, row: usize, attributes: &mut Vec<(String, String)>) -> Result<(), RpslError> {
+        attributes.clear();
+        match self {
+            Layout::Attributes(list) => {
+                if let Some(structs) = list.get_as_series(row) {
+                    let structs = structs.struct_()?;
+                    let names = structs.field_by_name("name")?;
+                    let values = structs.field_by_name("value")?;
+                    for (name, value) in names.str()?.into_iter().zip(values.str()?) {
+                        if let (Some(name), Some(value)) = (name, value) {
+                            attributes.push((name.to_string(), value.to_string()));
+                        }
+                    }
+                }
+            }
+            Layout::Columns(columns) => {
+                for (name, values) in columns {
+                    match values {
+                        ColumnValues::String(values) => {
+                            if let Some(value) = values.get(row) {
+                                attributes.push((name.to_string(), value.to_string()));
+                            }
+                        }
+                        ColumnValues::List(values) => {
+                            if let Some(values) = values.get_as_series(row) {
+                                push_all(attributes, name, &values)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn push_all(
+    attributes: &mut Vec<(String, String)>,
+    name: &str,
+    values: &Series,
+) -> Result<(), RpslError> {
+    for value in values.str()?.into_iter().flatten() {
+        attributes.push((name.to_string(), value.to_string()));
+    }
+    Ok(())
+}
+
+/// Write a DataFrame as RPSL text, one object per row
+///
+/// Frames with an `attributes` column of `{name, value}` structs, as read without a
+/// schema, are written from that column alone, with each struct becoming one
+/// attribute line and other fields such as `line` ignored. Otherwise every column
+/// must be String or List(String): each column becomes an attribute named after
+/// it, in column order, with one line per list element. Null values and list
+/// elements are omitted, and rows left without attributes are skipped.
+pub fn write_rpsl_to_writer<W: Write>(df: &DataFrame, writer: W) -> Result<(), RpslError> {
+    let layout = Layout::new(df)?;
+    let mut writer = RpslWriter::new(writer);
+
+    let mut attributes = Vec::new();
+    for row in 0..df.height() {
+        layout.row(row, &mut attributes)?;
+        if attributes.is_empty() {
+            continue;
+        }
+
+        let borrowed: Vec<(&[u8], &[u8])> = attributes
+            .iter()
+            .map(|(name, value)| (name.as_bytes(), value.as_bytes()))
+            .collect();
+        writer.write_object(&borrowed)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write a DataFrame as RPSL text to a file, gzip-compressed if the path ends in
+/// `.gz`. See [`write_rpsl_to_writer`].
+pub fn write_rpsl_to_path<P: AsRef<Path>>(df: &DataFrame, path: P) -> Result<(), RpslError> {
+    let path = path.as_ref();
+    let file = BufWriter::new(File::create(path)?);
+
+    if path.extension().and_then(|s| s.to_str()) == Some("gz") {
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        write_rpsl_to_writer(df, &mut encoder)?;
+        encoder.finish()?.flush()?;
+    } else {
+        write_rpsl_to_writer(df, file)?;
+    }
+    Ok(())
+}
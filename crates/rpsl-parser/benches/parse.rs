@@ -0,0 +1,105 @@
+//! Compares the `BufRead` and in-memory slice parse paths
+//!
+//! Run with `cargo bench -p rpsl-parser`. Parses `fixtures/dbs/*.db` if present,
+//! otherwise a synthetic database.
+
+use std::fs;
+use std::hint::black_box;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use rpsl_parser::{Callbacks, RpslParser};
+
+const ITERATIONS: u32 = 10;
+
+/// Callbacks that touch every name and value, so parsing can't be optimized away
+#[derive(Default)]
+struct Count {
+    attributes: usize,
+    bytes: usize,
+}
+
+impl Callbacks for Count {
+    fn start_object(&mut self) {}
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.attributes += 1;
+        self.bytes += name.len() + value.len();
+    }
+
+    fn end_object(&mut self) {}
+}
+
+fn synthetic() -> Vec<u8> {
+    let mut input = Vec::new();
+    for i in 0..200_000u32 {
+        let object = format!(
+            "route:          10.{}.{}.0/24\n\
+             descr:          Example route {i}\n\
+             \x20               spanning two lines\n\
+             origin:         AS{}\n\
+             mnt-by:         MAINT-AS{}\n\
+             mnt-by:         RIPE-NCC-END-MNT\n\
+             created:        2020-01-01T00:00:00Z\n\
+             source:         RIPE # comment\n\n",
+            i / 256 % 256,
+            i % 256,
+            64512 + i % 1000,
+            64512 + i % 1000,
+        );
+        input.extend_from_slice(object.as_bytes());
+    }
+    input
+}
+
+fn inputs() -> Vec<(String, Vec<u8>)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/dbs");
+    let mut inputs: Vec<_> = fs::read_dir(&dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+        .map(|path| (path.display().to_string(), fs::read(&path).unwrap()))
+        .collect();
+
+    if inputs.is_empty() {
+        inputs.push(("synthetic".to_string(), synthetic()));
+    }
+    inputs
+}
+
+fn bench(name: &str, input: &[u8], mut parse: impl FnMut(&[u8]) -> Count) -> Duration {
+    let mut best = Duration::MAX;
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        black_box(parse(black_box(input)));
+        best = best.min(start.elapsed());
+    }
+
+    let mib_per_s = input.len() as f64 / best.as_secs_f64() / (1024.0 * 1024.0);
+    println!("  {name:<8} {best:>10.2?}  {mib_per_s:>8.1} MiB/s");
+    best
+}
+
+fn main() {
+    for (name, input) in inputs() {
+        println!("{name} ({} bytes)", input.len());
+
+        let buffered = bench("bufread", &input, |input| {
+            let mut parser = RpslParser::new(Count::default());
+            parser.parse(input).unwrap();
+            parser.into_callbacks()
+        });
+        let slice = bench("slice", &input, |input| {
+            let mut parser = RpslParser::new(Count::default());
+            parser.parse_slice(input).unwrap();
+            parser.into_callbacks()
+        });
+
+        println!(
+            "  speedup  {:.2}x",
+            buffered.as_secs_f64() / slice.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::{Callbacks, LineBuffers, LineStep, Lines, ParseError, RpslParser, take_line_part};
+
+impl<C: Callbacks> RpslParser<C> {
+    /// Parse input from an async reader, e.g. a network stream, without blocking a
+    /// thread while waiting for data
+    ///
+    /// Equivalent to [`RpslParser::parse`]; the callbacks are still called
+    /// synchronously, between reads. Use e.g. `tokio::time::timeout` rather than a
+    /// deadline to bound the time spent.
+    pub async fn parse_async<R: AsyncBufRead + Unpin>(
+        &mut self,
+        mut reader: R,
+    ) -> Result<(), ParseError> {
+        self.reset();
+        while self.parse_object_async(&mut reader).await? {}
+        Ok(())
+    }
+
+    /// Async version of [`RpslParser::parse_object`]
+    pub async fn parse_object_async<R: AsyncBufRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+    ) -> Result<bool, ParseError> {
+        // Taken out of the parser while parsing, as the current line is borrowed from them
+        let mut lines = std::mem::replace(&mut self.lines, LineBuffers::empty());
+        let result = self.parse_lines_async(reader, &mut lines).await;
+        self.lines = lines;
+        result
+    }
+
+    /// Async version of `parse_lines`: only reading lines is async, each line is
+    /// handled as in the sync parsers
+    async fn parse_lines_async<R: AsyncBufRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        lines: &mut LineBuffers,
+    ) -> Result<bool, ParseError> {
+        if self.state.done {
+            return Ok(false);
+        }
+
+        let max_line_bytes = self.max_line_bytes;
+        loop {
+            if !lines.advance_async(reader, max_line_bytes).await? {
+                return Ok(self.end_input());
+            }
+            match self.parse_line(lines.line())? {
+                LineStep::Next => {}
+                LineStep::Attribute => {
+                    while lines.peek_async(reader, max_line_bytes).await?
+                        && self.continues_attribute(lines.next())
+                    {
+                        self.fold_line(lines)?;
+                        lines.advance_to_peeked();
+                    }
+                    self.end_attribute(lines)?;
+                }
+                LineStep::EndObject => return Ok(true),
+                LineStep::Boundary => {
+                    lines.unread();
+                    return Ok(true);
+                }
+                LineStep::Eof => {
+                    self.state.done = true;
+                    return Ok(false);
+                }
+            }
+        }
+    }
+}
+
+impl LineBuffers {
+    /// Async version of `LineSource::advance`
+    async fn advance_async<R: AsyncBufRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        max_line_bytes: Option<usize>,
+    ) -> Result<bool, ParseError> {
+        match self.advance_to_peeked() {
+            Some(has_line) => Ok(has_line),
+            None => read_line_async(reader, &mut self.buf, max_line_bytes).await,
+        }
+    }
+
+    /// Async version of `LineSource::peek`
+    async fn peek_async<R: AsyncBufRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        max_line_bytes: Option<usize>,
+    ) -> Result<bool, ParseError> {
+        if let Some(has_line) = self.peeked {
+            return Ok(has_line);
+        }
+        let has_line = read_line_async(reader, &mut self.next_buf, max_line_bytes).await?;
+        self.peeked = Some(has_line);
+        Ok(has_line)
+    }
+}
+
+/// Async version of `read_line`
+async fn read_line_async<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max_line_bytes: Option<usize>,
+) -> Result<bool, ParseError> {
+    buf.clear();
+    // Leave room for a `\r\n` terminator
+    let max = max_line_bytes.map_or(usize::MAX, |max| max.saturating_add(2));
+    while buf.len() < max {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            break;
+        }
+        let (taken, terminator) = take_line_part(buf, available, max - buf.len());
+        reader.consume(taken);
+
+        // The `\n` of a `\r\n` may only arrive with the next refill
+        if terminator == Some(b'\r')
+            && buf.len() < max
+            && reader.fill_buf().await?.first() == Some(&b'\n')
+        {
+            buf.push(b'\n');
+            reader.consume(1);
+        }
+        if terminator.is_some() {
+            break;
+        }
+    }
+    Ok(!buf.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AttributeSpan;
+    use tokio::io::BufReader;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Callbacks for Recorder {
+        fn start_object(&mut self) {
+            self.events.push("start".to_string());
+        }
+
+        fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+            self.events.push(format!(
+                "{}={} @{}+{}",
+                String::from_utf8_lossy(name),
+                String::from_utf8_lossy(value),
+                span.byte_offset,
+                span.byte_len,
+            ));
+        }
+
+        fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+
+        fn end_object(&mut self) {
+            self.events.push("end".to_string());
+        }
+
+        fn comment(&mut self, comment: &[u8]) {
+            self.events
+                .push(format!("comment {}", String::from_utf8_lossy(comment)));
+        }
+    }
+
+    #[test]
+    fn test_parse_async() {
+        let input = b"% header\n\nroute: 192.0.2.0/24\ndescr: first\n  second\n+ third\n\
+            origin: AS1 # comment\n\n\n\
+            aut-num: AS1\r\nas-name: X\r\n\r\n\
+            route: 198.51.100.0/24\norigin: AS2";
+        let new_parser = || RpslParser::new(Recorder::default());
+
+        let mut parser = new_parser();
+        parser.parse(&input[..]).unwrap();
+        let expected = parser.into_callbacks().events;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        // A tiny buffer splits lines (and the continuation lookahead) across reads
+        for capacity in [3, 8192] {
+            let mut parser = new_parser();
+            let reader = BufReader::with_capacity(capacity, &input[..]);
+            runtime.block_on(parser.parse_async(reader)).unwrap();
+            assert_eq!(parser.into_callbacks().events, expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_async_line_endings() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        // Spans are byte offsets, so compare against the same input parsed synchronously
+        for ending in ["\n", "\r\n", "\r"] {
+            let input = [
+                "route: 192.0.2.0/24",
+                "descr: first",
+                "  second",
+                "",
+                "aut-num: AS1",
+                "",
+            ]
+            .join(ending);
+            let mut parser = RpslParser::new(Recorder::default());
+            parser.parse_str(&input).unwrap();
+            let expected = parser.into_callbacks().events;
+            if ending == "\r" {
+                assert_eq!(expected[2], "descr=first second @20+22");
+            }
+
+            for capacity in [1, 2, 8192] {
+                let mut parser = RpslParser::new(Recorder::default());
+                let reader = BufReader::with_capacity(capacity, input.as_bytes());
+                runtime.block_on(parser.parse_async(reader)).unwrap();
+                assert_eq!(parser.into_callbacks().events, expected, "{ending:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_async_limits() {
+        let input = b"aut-num: AS1\r\nremarks: a\n+ bbbb\n+ cccccccccccccccc\n";
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let mut parser = RpslParser::new(Recorder::default()).with_max_line_bytes(12);
+        let reader = BufReader::with_capacity(3, &input[..]);
+        assert!(matches!(
+            runtime.block_on(parser.parse_async(reader)),
+            Err(ParseError::LimitExceeded {
+                line_number: 4,
+                limit: "max_line_bytes"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_object_async() {
+        let input = b"aut-num: AS1\nas-name: X\naut-num: AS2\n\nEOF\naut-num: AS3\n";
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let mut parser =
+            RpslParser::new(Recorder::default()).with_boundary_on_classes(&["aut-num"]);
+        let mut reader = BufReader::new(&input[..]);
+        let mut objects = 0;
+        while runtime
+            .block_on(parser.parse_object_async(&mut reader))
+            .unwrap()
+        {
+            objects += 1;
+        }
+        assert_eq!(objects, 2);
+        assert_eq!(
+            parser.into_callbacks().events,
+            [
+                "start",
+                "aut-num=AS1 @0+13",
+                "as-name=X @13+11",
+                "end",
+                "start",
+                "aut-num=AS2 @24+13",
+                "end",
+            ]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{AttributeSpan, Callbacks, ErrorAction, ParseError};
+
+/// Callbacks decorator that only forwards attributes with the given names
+///
+/// Names are matched case-insensitively, without allocating. Objects are always
+/// forwarded, even if none of their attributes are kept. Comments, raw lines and
+/// sub-attributes of an attribute are dropped along with it.
+pub struct AttributeFilter<C> {
+    inner: C,
+    names: Vec<Vec<u8>>,
+}
+
+impl<C: Callbacks> AttributeFilter<C> {
+    pub fn new<S: AsRef<str>>(inner: C, names: &[S]) -> Self {
+        Self {
+            inner,
+            names: names
+                .iter()
+                .map(|name| name.as_ref().as_bytes().to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    #[inline]
+    fn keep(&self, name: &[u8]) -> bool {
+        self.names.iter().any(|n| name.eq_ignore_ascii_case(n))
+    }
+}
+
+impl<C: Callbacks> Callbacks for AttributeFilter<C> {
+    fn start_object(&mut self) {
+        self.inner.start_object();
+    }
+
+    fn start_object_class(&mut self, class: &[u8]) {
+        self.inner.start_object_class(class);
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        if self.keep(name) {
+            self.inner.attribute(name, value);
+        }
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        if self.keep(name) {
+            self.inner.attribute_at(span, name, value);
+        }
+    }
+
+    fn end_object(&mut self) {
+        self.inner.end_object();
+    }
+
+    fn discard_object(&mut self) {
+        self.inner.discard_object();
+    }
+
+    fn side_line(&mut self, line: &[u8]) {
+        self.inner.side_line(line);
+    }
+
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        if name.is_none_or(|name| self.keep(name)) {
+            self.inner.comment_at(line_number, name, comment);
+        }
+    }
+
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) {
+        if self.keep(name) {
+            self.inner.raw_line(name, line);
+        }
+    }
+
+    fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+        if self.keep(parent) {
+            self.inner.sub_attribute(parent, key, value);
+        }
+    }
+
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        self.inner.on_error(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FirstError, RpslParser, RpslWriter};
+
+    #[test]
+    fn test_attribute_filter() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\nmnt-by: A-MNT\n\n\
+            aut-num: AS1\nas-name: X\n\n\
+            Route: 198.51.100.0/24\nORIGIN: AS2\n";
+
+        let writer = FirstError::new(RpslWriter::new(Vec::new()));
+        let mut parser = RpslParser::new(AttributeFilter::new(writer, &["route", "origin"]));
+        parser.parse(&input[..]).unwrap();
+        let writer = parser.into_callbacks().into_inner().into_result().unwrap();
+
+        // The aut-num object is still there, without attributes
+        assert_eq!(writer.objects(), 3);
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "route: 192.0.2.0/24\norigin: AS1\n\n\nRoute: 198.51.100.0/24\nORIGIN: AS2\n"
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{AttributeSpan, Callbacks};
+
+/// An object held back by a callbacks decorator until it ends, with everything the
+/// parser reported for it, to be forwarded as a whole or dropped
+#[derive(Default)]
+pub(crate) struct ObjectBuffer {
+    attributes: Vec<(AttributeSpan, Vec<u8>, Vec<u8>)>,
+    /// Sub-attributes with the index of their attribute
+    sub_attributes: Vec<(usize, Vec<u8>, Vec<u8>)>,
+    /// Raw lines with the index of their attribute
+    raw_lines: Vec<(usize, Vec<u8>)>,
+    comments: Vec<Comment>,
+    started: bool,
+}
+
+/// A comment of a buffered object
+struct Comment {
+    /// Index of the attribute the comment precedes, `None` if it precedes the object
+    before: Option<usize>,
+
+    line_number: u32,
+
+    /// Name of the attribute the comment trails, if any
+    attribute: Option<Vec<u8>>,
+
+    comment: Vec<u8>,
+}
+
+impl ObjectBuffer {
+    /// Start a new object, dropping what is left of one that did not end (after a
+    /// skipped error) but keeping the comments preceding the new one
+    pub(crate) fn start(&mut self) {
+        self.attributes.clear();
+        self.sub_attributes.clear();
+        self.raw_lines.clear();
+        self.comments.retain(|comment| comment.before.is_none());
+        self.started = true;
+    }
+
+    pub(crate) fn attribute(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        self.attributes.push((span, name.to_vec(), value.to_vec()));
+    }
+
+    /// The attributes buffered so far, in input order
+    pub(crate) fn attributes(&self) -> &[(AttributeSpan, Vec<u8>, Vec<u8>)] {
+        &self.attributes
+    }
+
+    pub(crate) fn raw_line(&mut self, line: &[u8]) {
+        // Always follows its attribute, the last one buffered
+        if let Some(index) = self.attributes.len().checked_sub(1) {
+            self.raw_lines.push((index, line.to_vec()));
+        }
+    }
+
+    pub(crate) fn sub_attribute(&mut self, key: &[u8], value: &[u8]) {
+        // Always follows its attribute, the last one buffered
+        if let Some(index) = self.attributes.len().checked_sub(1) {
+            self.sub_attributes
+                .push((index, key.to_vec(), value.to_vec()));
+        }
+    }
+
+    /// Buffer a comment, see [`Callbacks::comment_at`]. A comment trailing an
+    /// attribute always precedes it, the next one buffered.
+    pub(crate) fn comment(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        self.comments.push(Comment {
+            before: self.started.then_some(self.attributes.len()),
+            line_number,
+            attribute: name.map(<[u8]>::to_vec),
+            comment: comment.to_vec(),
+        });
+    }
+
+    /// Pass the buffered object to `inner`, in the order the parser reported it
+    pub(crate) fn forward<C: Callbacks>(&self, inner: &mut C) {
+        let mut comments = self.comments.iter().peekable();
+        while let Some(comment) = comments.next_if(|comment| comment.before.is_none()) {
+            comment.forward(inner);
+        }
+        let class = self
+            .attributes
+            .first()
+            .map_or(&b""[..], |(_, name, _)| name);
+        inner.start_object_class(class);
+        let mut sub_attributes = self.sub_attributes.iter().peekable();
+        let mut raw_lines = self.raw_lines.iter().peekable();
+        for (index, (span, name, value)) in self.attributes.iter().enumerate() {
+            while let Some(comment) = comments.next_if(|comment| comment.before == Some(index)) {
+                comment.forward(inner);
+            }
+            inner.attribute_at(*span, name, value);
+            if let Some((_, line)) = raw_lines.next_if(|(attribute, _)| *attribute == index) {
+                inner.raw_line(name, line);
+            }
+            while let Some((_, key, sub_value)) =
+                sub_attributes.next_if(|(parent, _, _)| *parent == index)
+            {
+                inner.sub_attribute(name, key, sub_value);
+            }
+        }
+        for comment in comments {
+            comment.forward(inner);
+        }
+        inner.end_object();
+    }
+
+    /// Drop the buffered object, ready for the next one
+    pub(crate) fn clear(&mut self) {
+        self.attributes.clear();
+        self.sub_attributes.clear();
+        self.raw_lines.clear();
+        self.comments.clear();
+        self.started = false;
+    }
+}
+
+impl Comment {
+    fn forward<C: Callbacks>(&self, inner: &mut C) {
+        inner.comment_at(self.line_number, self.attribute.as_deref(), &self.comment);
+    }
+}
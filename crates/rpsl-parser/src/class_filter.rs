@@ -0,0 +1,237 @@
+use crate::{AttributeSpan, Callbacks, ErrorAction, ParseError};
+
+/// Whether the current object is forwarded, decided at its first attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Undecided,
+    Keep,
+    Drop,
+}
+
+/// Callbacks decorator that only forwards objects of the given classes
+///
+/// The class of an object is the name of its first attribute, matched
+/// case-insensitively. Comments are forwarded with the object they precede or
+/// belong to, and dropped along with it. Comments after the last object are
+/// forwarded by [`ClassFilter::into_inner`].
+pub struct ClassFilter<C> {
+    inner: C,
+    classes: Vec<Vec<u8>>,
+    state: State,
+    /// Whether the parser has started the current object
+    started: bool,
+    /// Comments preceding the decision, see [`Callbacks::comment_at`]
+    comments: Vec<Comment>,
+}
+
+/// A comment held back until the class of its object is known
+struct Comment {
+    /// Whether the comment came after the object started, rather than preceding it
+    started: bool,
+
+    line_number: u32,
+
+    /// Name of the attribute the comment trails, if any
+    attribute: Option<Vec<u8>>,
+
+    comment: Vec<u8>,
+}
+
+impl<C: Callbacks> ClassFilter<C> {
+    pub fn new<S: AsRef<str>>(inner: C, classes: &[S]) -> Self {
+        Self {
+            inner,
+            classes: classes
+                .iter()
+                .map(|class| class.as_ref().as_bytes().to_ascii_lowercase())
+                .collect(),
+            state: State::Undecided,
+            started: false,
+            comments: Vec::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Return the inner callbacks, after passing them the comments that follow the
+    /// last object
+    pub fn into_inner(mut self) -> C {
+        self.forward_comments(self.comments.len());
+        self.inner
+    }
+
+    /// Forward the first `count` buffered comments
+    fn forward_comments(&mut self, count: usize) {
+        for comment in self.comments.drain(..count) {
+            self.inner.comment_at(
+                comment.line_number,
+                comment.attribute.as_deref(),
+                &comment.comment,
+            );
+        }
+    }
+}
+
+impl<C: Callbacks> Callbacks for ClassFilter<C> {
+    fn start_object(&mut self) {
+        self.state = State::Undecided;
+        self.started = true;
+        // Drop what is left of an object that did not end (after a skipped error)
+        self.comments.retain(|comment| !comment.started);
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.attribute_at(AttributeSpan::default(), name, value);
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        if self.state == State::Undecided {
+            if self.classes.iter().any(|c| name.eq_ignore_ascii_case(c)) {
+                self.state = State::Keep;
+                // Comments are buffered in order, those preceding the object first
+                let preceding = self
+                    .comments
+                    .iter()
+                    .take_while(|comment| !comment.started)
+                    .count();
+                self.forward_comments(preceding);
+                self.inner.start_object_class(name);
+                self.forward_comments(self.comments.len());
+            } else {
+                self.state = State::Drop;
+                self.comments.clear();
+            }
+        }
+
+        if self.state == State::Keep {
+            self.inner.attribute_at(span, name, value);
+        }
+    }
+
+    fn end_object(&mut self) {
+        if self.state == State::Keep {
+            self.inner.end_object();
+        }
+        self.state = State::Undecided;
+        self.started = false;
+    }
+
+    fn discard_object(&mut self) {
+        if self.state == State::Keep {
+            self.inner.discard_object();
+        }
+        self.state = State::Undecided;
+        self.started = false;
+        self.comments.clear();
+    }
+
+    fn side_line(&mut self, line: &[u8]) {
+        self.inner.side_line(line);
+    }
+
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        match self.state {
+            // Includes comments trailing the first attribute, reported before it
+            State::Undecided => self.comments.push(Comment {
+                started: self.started,
+                line_number,
+                attribute: name.map(<[u8]>::to_vec),
+                comment: comment.to_vec(),
+            }),
+            State::Keep => self.inner.comment_at(line_number, name, comment),
+            State::Drop => {}
+        }
+    }
+
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) {
+        if self.state == State::Keep {
+            self.inner.raw_line(name, line);
+        }
+    }
+
+    fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+        if self.state == State::Keep {
+            self.inner.sub_attribute(parent, key, value);
+        }
+    }
+
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        self.inner.on_error(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FirstError, RpslParser, RpslWriter};
+
+    #[test]
+    fn test_class_filter() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n\n\
+            aut-num: AS1\n\n\
+            Route6: 2001:db8::/32\norigin: AS1\n";
+
+        let writer = FirstError::new(RpslWriter::new(Vec::new()));
+        let mut parser = RpslParser::new(ClassFilter::new(writer, &["route", "route6"]));
+        parser.parse(&input[..]).unwrap();
+        let writer = parser.into_callbacks().into_inner().into_result().unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "route: 192.0.2.0/24\norigin: AS1\n\nRoute6: 2001:db8::/32\norigin: AS1\n"
+        );
+    }
+
+    #[test]
+    fn test_class_filter_comments() {
+        #[derive(Default)]
+        struct Recorder(Vec<String>);
+
+        impl Callbacks for Recorder {
+            fn start_object(&mut self) {
+                self.0.push("start".into());
+            }
+
+            fn attribute(&mut self, name: &[u8], _value: &[u8]) {
+                self.0.push(String::from_utf8_lossy(name).into_owned());
+            }
+
+            fn end_object(&mut self) {
+                self.0.push("end".into());
+            }
+
+            fn attribute_comment(&mut self, name: &[u8], comment: &[u8]) {
+                self.0.push(format!(
+                    "{}:{}",
+                    String::from_utf8_lossy(name),
+                    String::from_utf8_lossy(comment)
+                ));
+            }
+
+            fn comment(&mut self, comment: &[u8]) {
+                self.0.push(String::from_utf8_lossy(comment).into_owned());
+            }
+        }
+
+        let input = b"% about A\nroute: 192.0.2.0/24 # first\norigin: AS1\n\n\
+            % about B\naut-num: AS1 # dropped\n\n\
+            % trailing\n";
+        let mut parser = RpslParser::new(ClassFilter::new(Recorder::default(), &["route"]));
+        parser.parse(&input[..]).unwrap();
+
+        assert_eq!(
+            parser.into_callbacks().into_inner().0,
+            [
+                "% about A",
+                "start",
+                "route:# first",
+                "route",
+                "origin",
+                "end",
+                "% trailing"
+            ]
+        );
+    }
+}
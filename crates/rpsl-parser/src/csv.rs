@@ -0,0 +1,123 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{FirstError, ParseError, RpslParser, TryCallbacks};
+
+/// Writes attributes as CSV rows in long format, one row per attribute:
+/// `object_id,class,key,attr_name,attr_value`
+///
+/// Objects are numbered from 0 in input order. The class and key are the name and
+/// value of the first attribute of the object. Fields are quoted as in RFC 4180
+/// when they contain a comma, quote or line break, and rows end with `\n`.
+pub struct CsvWriter<W> {
+    writer: W,
+    objects: usize,
+    class: Vec<u8>,
+    key: Vec<u8>,
+    first_attribute: bool,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Create a writer, writing the header row
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(b"object_id,class,key,attr_name,attr_value\n")?;
+        Ok(Self {
+            writer,
+            objects: 0,
+            class: Vec::new(),
+            key: Vec::new(),
+            first_attribute: true,
+        })
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> TryCallbacks for CsvWriter<W> {
+    type Error = io::Error;
+
+    fn start_object(&mut self) -> io::Result<()> {
+        self.first_attribute = true;
+        Ok(())
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> io::Result<()> {
+        if self.first_attribute {
+            self.first_attribute = false;
+            self.class.clear();
+            self.class.extend_from_slice(name);
+            self.key.clear();
+            self.key.extend_from_slice(value);
+        }
+
+        write!(self.writer, "{}", self.objects)?;
+        for field in [&self.class[..], &self.key, name, value] {
+            self.writer.write_all(b",")?;
+            write_field(&mut self.writer, field)?;
+        }
+        self.writer.write_all(b"\n")
+    }
+
+    fn end_object(&mut self) -> io::Result<()> {
+        self.objects += 1;
+        Ok(())
+    }
+}
+
+/// Write a field, quoting it if needed
+fn write_field<W: Write>(writer: &mut W, field: &[u8]) -> io::Result<()> {
+    if !field
+        .iter()
+        .any(|b| matches!(b, b',' | b'"' | b'\n' | b'\r'))
+    {
+        return writer.write_all(field);
+    }
+
+    writer.write_all(b"\"")?;
+    for part in field.split_inclusive(|&b| b == b'"') {
+        writer.write_all(part)?;
+        if part.last() == Some(&b'"') {
+            writer.write_all(b"\"")?;
+        }
+    }
+    writer.write_all(b"\"")
+}
+
+/// Convert RPSL to CSV in long format, see [`CsvWriter`]
+///
+/// Multi-line values are folded into a single field, as they are parsed.
+pub fn write_rpsl_csv<R: BufRead, W: Write>(reader: R, writer: W) -> Result<(), ParseError> {
+    let mut parser = RpslParser::new(FirstError::new(CsvWriter::new(writer)?));
+    parser.parse(reader)?;
+
+    let mut csv = parser.into_callbacks().into_result()?;
+    csv.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_rpsl_csv() {
+        let input = b"route: 192.0.2.0/24\ndescr: Example, \"quoted\"\n  second line\n\n\
+            aut-num: AS65000\n";
+
+        let mut output = Vec::new();
+        write_rpsl_csv(&input[..], &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "object_id,class,key,attr_name,attr_value\n\
+             0,route,192.0.2.0/24,route,192.0.2.0/24\n\
+             0,route,192.0.2.0/24,descr,\"Example, \"\"quoted\"\" second line\"\n\
+             1,aut-num,AS65000,aut-num,AS65000\n"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{AttributeSpan, Callbacks, ErrorAction, ParseError, TryCallbacks};
+
+/// Callbacks decorator that records the first error returned by a [`TryCallbacks`]
+///
+/// Once an error has been recorded all further events are ignored, letting the
+/// parse run to completion. The error can be retrieved afterwards with
+/// [`FirstError::error`] or [`FirstError::into_result`].
+pub struct FirstError<C: TryCallbacks> {
+    inner: C,
+    error: Option<C::Error>,
+}
+
+impl<C: TryCallbacks> FirstError<C> {
+    pub fn new(inner: C) -> Self {
+        Self { inner, error: None }
+    }
+
+    /// The first error returned by the wrapped callbacks, if any
+    pub fn error(&self) -> Option<&C::Error> {
+        self.error.as_ref()
+    }
+
+    /// Take the recorded error, if any, so that events are forwarded again
+    pub fn take_error(&mut self) -> Option<C::Error> {
+        self.error.take()
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Return the wrapped callbacks, or the first error they returned
+    pub fn into_result(self) -> Result<C, C::Error> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.inner),
+        }
+    }
+
+    #[inline]
+    fn record(&mut self, result: Result<(), C::Error>) {
+        if let Err(err) = result {
+            self.error = Some(err);
+        }
+    }
+}
+
+impl<C: TryCallbacks> Callbacks for FirstError<C> {
+    fn start_object(&mut self) {
+        if self.error.is_none() {
+            let result = self.inner.start_object();
+            self.record(result);
+        }
+    }
+
+    fn start_object_class(&mut self, class: &[u8]) {
+        if self.error.is_none() {
+            let result = self.inner.start_object_class(class);
+            self.record(result);
+        }
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        if self.error.is_none() {
+            let result = self.inner.attribute(name, value);
+            self.record(result);
+        }
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        if self.error.is_none() {
+            let result = self.inner.attribute_at(span, name, value);
+            self.record(result);
+        }
+    }
+
+    fn end_object(&mut self) {
+        if self.error.is_none() {
+            let result = self.inner.end_object();
+            self.record(result);
+        }
+    }
+
+    fn discard_object(&mut self) {
+        if self.error.is_none() {
+            let result = self.inner.discard_object();
+            self.record(result);
+        }
+    }
+
+    fn side_line(&mut self, line: &[u8]) {
+        if self.error.is_none() {
+            let result = self.inner.side_line(line);
+            self.record(result);
+        }
+    }
+
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        if self.error.is_none() {
+            let result = self.inner.comment_at(line_number, name, comment);
+            self.record(result);
+        }
+    }
+
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) {
+        if self.error.is_none() {
+            let result = self.inner.raw_line(name, line);
+            self.record(result);
+        }
+    }
+
+    fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+        if self.error.is_none() {
+            let result = self.inner.sub_attribute(parent, key, value);
+            self.record(result);
+        }
+    }
+
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        self.inner.on_error(err)
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use crate::{AttributeSpan, Callbacks, ErrorAction, ParseError};
+
+/// Attributes generated by the RIPE database, which must be omitted from updates
+pub const RIPE_GENERATED_ATTRIBUTES: &[&str] = &["created", "last-modified"];
+
+/// What [`GeneratedAttributes`] does with generated attributes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratedAction {
+    /// Don't forward them
+    Drop,
+
+    /// Forward them with the name prefixed by `*`
+    Mark,
+}
+
+/// Callbacks decorator that drops or marks generated (read-only) attributes
+///
+/// Names are matched case-insensitively. All other events are forwarded unchanged.
+pub struct GeneratedAttributes<C> {
+    inner: C,
+    names: HashSet<Vec<u8>>,
+    action: GeneratedAction,
+    name_buf: Vec<u8>,
+}
+
+impl<C: Callbacks> GeneratedAttributes<C> {
+    /// Handle the attributes in [`RIPE_GENERATED_ATTRIBUTES`]
+    pub fn new(inner: C, action: GeneratedAction) -> Self {
+        Self::with_names(inner, action, RIPE_GENERATED_ATTRIBUTES)
+    }
+
+    /// Handle the given attribute names
+    pub fn with_names(inner: C, action: GeneratedAction, names: &[&str]) -> Self {
+        Self {
+            inner,
+            names: names
+                .iter()
+                .map(|name| name.as_bytes().to_ascii_lowercase())
+                .collect(),
+            action,
+            name_buf: Vec::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    #[inline]
+    fn is_generated(&mut self, name: &[u8]) -> bool {
+        self.name_buf.clear();
+        self.name_buf
+            .extend(name.iter().map(u8::to_ascii_lowercase));
+        self.names.contains(&self.name_buf)
+    }
+
+    /// Put the marked name of a generated attribute in `name_buf`, returning `false`
+    /// if the attribute should be dropped instead
+    #[inline]
+    fn mark(&mut self, name: &[u8]) -> bool {
+        match self.action {
+            GeneratedAction::Drop => false,
+            GeneratedAction::Mark => {
+                self.name_buf.clear();
+                self.name_buf.push(b'*');
+                self.name_buf.extend_from_slice(name);
+                true
+            }
+        }
+    }
+}
+
+impl<C: Callbacks> Callbacks for GeneratedAttributes<C> {
+    fn start_object(&mut self) {
+        self.inner.start_object();
+    }
+
+    fn start_object_class(&mut self, class: &[u8]) {
+        self.inner.start_object_class(class);
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        if !self.is_generated(name) {
+            self.inner.attribute(name, value);
+        } else if self.mark(name) {
+            self.inner.attribute(&self.name_buf, value);
+        }
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        if !self.is_generated(name) {
+            self.inner.attribute_at(span, name, value);
+        } else if self.mark(name) {
+            self.inner.attribute_at(span, &self.name_buf, value);
+        }
+    }
+
+    fn end_object(&mut self) {
+        self.inner.end_object();
+    }
+
+    fn discard_object(&mut self) {
+        self.inner.discard_object();
+    }
+
+    fn side_line(&mut self, line: &[u8]) {
+        self.inner.side_line(line);
+    }
+
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        match name {
+            Some(name) if self.is_generated(name) => {
+                if self.mark(name) {
+                    self.inner
+                        .comment_at(line_number, Some(&self.name_buf), comment);
+                }
+            }
+            _ => self.inner.comment_at(line_number, name, comment),
+        }
+    }
+
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) {
+        if !self.is_generated(name) {
+            self.inner.raw_line(name, line);
+        } else if self.mark(name) {
+            self.inner.raw_line(&self.name_buf, line);
+        }
+    }
+
+    fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+        if !self.is_generated(parent) {
+            self.inner.sub_attribute(parent, key, value);
+        } else if self.mark(parent) {
+            self.inner.sub_attribute(&self.name_buf, key, value);
+        }
+    }
+
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        self.inner.on_error(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FirstError, RpslParser, RpslWriter};
+
+    fn filter(action: GeneratedAction, input: &[u8]) -> String {
+        let writer = FirstError::new(RpslWriter::new(Vec::new()));
+        let mut parser = RpslParser::new(GeneratedAttributes::new(writer, action));
+        parser.parse(input).unwrap();
+        let writer = parser.into_callbacks().into_inner();
+        String::from_utf8(writer.into_result().unwrap().into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_generated_attributes() {
+        let input = b"mntner: EXAMPLE-MNT\nCreated: 2020-01-01T00:00:00Z\n\
+            last-modified: 2021-01-01T00:00:00Z\nsource: RIPE\n";
+
+        assert_eq!(
+            filter(GeneratedAction::Drop, input),
+            "mntner: EXAMPLE-MNT\nsource: RIPE\n"
+        );
+        assert_eq!(
+            filter(GeneratedAction::Mark, input),
+            "mntner: EXAMPLE-MNT\n*Created: 2020-01-01T00:00:00Z\n\
+             *last-modified: 2021-01-01T00:00:00Z\nsource: RIPE\n"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::io::{self, BufRead, Read};
+use std::path::{Path, PathBuf};
+
+/// Default limit on how deeply includes nest, see [`IncludeReader::with_max_depth`]
+pub const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Reader that expands `include: path` lines with the contents of the named file
+///
+/// An `include:` line (the name matched case-insensitively, at the start of the
+/// line) is replaced by the lines of the file it names, resolved relative to the
+/// directory of the including file. Inside an object this adds the included
+/// attributes to it; between blank lines it adds whole objects. Included files
+/// may include others, up to [`MAX_INCLUDE_DEPTH`] levels deep by default.
+///
+/// A file that includes itself, directly or through other files, fails with
+/// [`io::ErrorKind::InvalidData`]; the same file can still be included several
+/// times side by side. Line numbers reported by the parser count the lines of
+/// the expanded input.
+pub struct IncludeReader<R, F> {
+    open: F,
+    /// Files being read, each included by the one before it, with their
+    /// canonical paths
+    stack: Vec<(R, PathBuf)>,
+    max_depth: usize,
+    line: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead, F: FnMut(&Path) -> io::Result<R>> IncludeReader<R, F> {
+    /// Read the file at `path`, opening it and any included files with `open`
+    pub fn new(path: impl AsRef<Path>, mut open: F) -> io::Result<Self> {
+        let path = path.as_ref().canonicalize()?;
+        let reader = open(&path)?;
+        Ok(Self {
+            open,
+            stack: vec![(reader, path)],
+            max_depth: MAX_INCLUDE_DEPTH,
+            line: Vec::new(),
+            pos: 0,
+        })
+    }
+
+    /// Fail when includes nest more than this many levels deep
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Read the next line of the expanded input, leaving `line` empty at the end
+    fn next_line(&mut self) -> io::Result<()> {
+        self.line.clear();
+        self.pos = 0;
+
+        while let Some((reader, path)) = self.stack.last_mut() {
+            if reader.read_until(b'\n', &mut self.line)? == 0 {
+                self.stack.pop();
+                continue;
+            }
+
+            let Some(target) = include_target(&self.line) else {
+                // Keep the last line of a file apart from what follows the include
+                if !self.line.ends_with(b"\n") {
+                    self.line.push(b'\n');
+                }
+                return Ok(());
+            };
+            let target = path.parent().unwrap_or(Path::new("")).join(target);
+            self.line.clear();
+            self.include(&target)?;
+        }
+        Ok(())
+    }
+
+    fn include(&mut self, target: &Path) -> io::Result<()> {
+        if self.stack.len() > self.max_depth {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Includes nested more than {} levels deep at {}",
+                    self.max_depth,
+                    target.display()
+                ),
+            ));
+        }
+
+        let path = target
+            .canonicalize()
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", target.display())))?;
+        if self.stack.iter().any(|(_, including)| *including == path) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Include cycle at {}", path.display()),
+            ));
+        }
+
+        let reader = (self.open)(&path)?;
+        self.stack.push((reader, path));
+        Ok(())
+    }
+}
+
+/// The path named by an `include:` line, if it is one
+fn include_target(line: &[u8]) -> Option<PathBuf> {
+    let colon = memchr::memchr(b':', line)?;
+    if !line[..colon].eq_ignore_ascii_case(b"include") {
+        return None;
+    }
+    let target = String::from_utf8_lossy(&line[colon + 1..]);
+    let target = target.trim();
+    (!target.is_empty()).then(|| PathBuf::from(target))
+}
+
+impl<R: BufRead, F: FnMut(&Path) -> io::Result<R>> Read for IncludeReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead, F: FnMut(&Path) -> io::Result<R>> BufRead for IncludeReader<R, F> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.line.len() {
+            self.next_line()?;
+        }
+        Ok(&self.line[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use std::io::BufReader;
+
+    fn open(path: &Path) -> io::Result<BufReader<File>> {
+        Ok(BufReader::new(File::open(path)?))
+    }
+
+    fn read_expanded(path: &Path) -> io::Result<String> {
+        let mut output = String::new();
+        IncludeReader::new(path, open)?.read_to_string(&mut output)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn test_include_reader() {
+        let dir = std::env::temp_dir().join(format!("rpsl-include-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(
+            dir.join("main.txt"),
+            "route: 192.0.2.0/24\nINCLUDE: sub/common.txt\n\ninclude: sub/objects.txt\n",
+        )
+        .unwrap();
+        fs::write(dir.join("sub/common.txt"), "mnt-by: EXAMPLE-MNT").unwrap();
+        fs::write(
+            dir.join("sub/objects.txt"),
+            "aut-num: AS1\ninclude: common.txt\n",
+        )
+        .unwrap();
+        fs::write(dir.join("cycle.txt"), "include: sub/cycle.txt\n").unwrap();
+        fs::write(dir.join("sub/cycle.txt"), "include: ../cycle.txt\n").unwrap();
+
+        let expanded = read_expanded(&dir.join("main.txt"));
+        let cycle = read_expanded(&dir.join("cycle.txt"));
+        let shallow = IncludeReader::new(dir.join("main.txt"), open)
+            .unwrap()
+            .with_max_depth(1)
+            .read_to_string(&mut String::new());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            expanded.unwrap(),
+            "route: 192.0.2.0/24\nmnt-by: EXAMPLE-MNT\n\naut-num: AS1\nmnt-by: EXAMPLE-MNT\n"
+        );
+        assert_eq!(cycle.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(shallow.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,17 +1,455 @@
-use std::io::BufRead;
+use std::io::{BufRead, BufReader, Read};
+use std::ops::Range;
+use std::time::Instant;
 
 use thiserror::Error;
 
+#[cfg(feature = "tokio")]
+mod async_parse;
+mod attribute_filter;
+mod buffer;
+mod class_filter;
+mod csv;
+mod first_error;
+mod generated;
+mod include;
+mod metadata;
+mod model;
+mod modified;
+mod object;
+mod object_filter;
+mod pretty;
+mod repair;
+mod route_origin;
+mod shard;
+mod stats;
+mod tee;
+mod template;
+mod validate;
+mod writer;
+
+pub use attribute_filter::AttributeFilter;
+pub use class_filter::ClassFilter;
+pub use csv::{CsvWriter, write_rpsl_csv};
+pub use first_error::FirstError;
+pub use generated::{GeneratedAction, GeneratedAttributes, RIPE_GENERATED_ATTRIBUTES};
+pub use include::{IncludeReader, MAX_INCLUDE_DEPTH};
+pub use metadata::{DumpMetadata, read_dump_metadata};
+pub use model::{Inet6num, Inetnum, IpRange, ModelError, Source};
+pub use modified::ModifiedSince;
+pub use object::{RpslObject, RpslObjects, parse_into_vec, rpsl_objects};
+pub use object_filter::ObjectFilter;
+pub use pretty::PrettyPrinter;
+pub use repair::{RepairOptions, repair_rpsl};
+pub use route_origin::{
+    MalformedRoute, RouteOriginCollector, RouteOriginReport, route_origin_report,
+};
+pub use shard::{parse_parallel, split_rpsl_offsets};
+pub use stats::{
+    AttributeStats, AttributeStatsCollector, ObjectStats, ObjectStatsCollector,
+    attribute_value_stats, object_stats,
+};
+pub use tee::Tee;
+pub use template::{MAX_VOCABULARY, ObjectCheck, TemplateValidator};
+pub use validate::validate_streaming;
+pub use writer::{LineEnding, RpslWriter};
+
 /// Callback trait for RPSL parsing events
 pub trait Callbacks {
-    /// Called when a new object starts
+    /// Called when a new object starts, right before its first attribute. The
+    /// object starts on the line of that attribute, see [`Callbacks::attribute_at`].
     fn start_object(&mut self);
 
+    /// Called by the parser when a new object starts, with its class (the name of
+    /// its first attribute, as written). Defaults to calling
+    /// [`Callbacks::start_object`].
+    #[inline]
+    fn start_object_class(&mut self, class: &[u8]) {
+        let _ = class;
+        self.start_object();
+    }
+
     /// Called for each attribute with its name and value
     fn attribute(&mut self, name: &[u8], value: &[u8]);
 
-    /// Called when an object ends
+    /// Called by the parser for each attribute, with where it was found in the
+    /// input. Defaults to calling [`Callbacks::attribute`].
+    #[inline]
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        let _ = span;
+        self.attribute(name, value);
+    }
+
+    /// Called when an object ends. The object ends on the last line of its last
+    /// attribute, i.e. at `line_number + continuation_lines` of its span.
     fn end_object(&mut self);
+
+    /// Called instead of [`Callbacks::end_object`] when a skipped syntax error cuts
+    /// the current object short, see [`Callbacks::on_error`]. Ignores it by
+    /// default.
+    #[inline]
+    fn discard_object(&mut self) {}
+
+    /// Called for each side line, see [`RpslParser::with_side_lines`]. Ignores
+    /// the line by default.
+    #[inline]
+    fn side_line(&mut self, line: &[u8]) {
+        let _ = line;
+    }
+
+    /// Called for each comment-only line, with the comment from its `%` or `#` to
+    /// the end of the line, through the default [`Callbacks::comment_at`]. Ignores
+    /// the comment by default.
+    #[inline]
+    fn comment(&mut self, comment: &[u8]) {
+        let _ = comment;
+    }
+
+    /// Called for a comment trailing an attribute line or one of its continuation
+    /// lines, with the name of the attribute, before the attribute itself is
+    /// reported, through the default [`Callbacks::comment_at`]. Calls
+    /// [`Callbacks::comment`] by default.
+    #[inline]
+    fn attribute_comment(&mut self, name: &[u8], comment: &[u8]) {
+        let _ = name;
+        self.comment(comment);
+    }
+
+    /// Called by the parser for each comment, with its line number, the name of
+    /// the attribute it trails if any, and the comment from its `%` or `#` to the
+    /// end of the line. Calls [`Callbacks::attribute_comment`] or
+    /// [`Callbacks::comment`] by default.
+    #[inline]
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        let _ = line_number;
+        match name {
+            Some(name) => self.attribute_comment(name, comment),
+            None => self.comment(comment),
+        }
+    }
+
+    /// Called right after each attribute with its name and the input it was parsed
+    /// from: the attribute line and any continuation lines, as written, including
+    /// indentation, comments and line endings. This is the input covered by the
+    /// attribute's span. Ignores the line by default.
+    #[inline]
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) {
+        let _ = (name, line);
+    }
+
+    /// Called after an attribute configured with [`RpslParser::with_sub_attributes`]
+    /// whose value holds a `key: value` sub-field. Ignores it by default.
+    #[inline]
+    fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+        let _ = (parent, key, value);
+    }
+
+    /// Called for each syntax error ([`ParseError::InvalidSyntax`]) to decide
+    /// whether parsing goes on. Aborts by default.
+    ///
+    /// With [`ErrorAction::Skip`], lines are skipped up to the next blank line and
+    /// parsing resumes with the next object. An object the error occurred in is
+    /// not ended: [`Callbacks::discard_object`] is called instead, so that
+    /// callbacks that skip can drop what they collected for it.
+    #[inline]
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        let _ = err;
+        ErrorAction::Abort
+    }
+}
+
+/// What the parser does after a syntax error, see [`Callbacks::on_error`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorAction {
+    /// Stop parsing and return the error
+    #[default]
+    Abort,
+
+    /// Skip to the next blank line and carry on with the next object
+    Skip,
+}
+
+/// Fallible variant of [`Callbacks`]
+///
+/// Wrap an implementation in [`FirstError`] to use it with [`RpslParser`].
+pub trait TryCallbacks {
+    type Error;
+
+    /// Called when a new object starts
+    fn start_object(&mut self) -> Result<(), Self::Error>;
+
+    /// Called when a new object starts, with its class. Defaults to calling
+    /// [`TryCallbacks::start_object`].
+    #[inline]
+    fn start_object_class(&mut self, class: &[u8]) -> Result<(), Self::Error> {
+        let _ = class;
+        self.start_object()
+    }
+
+    /// Called for each attribute with its name and value
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> Result<(), Self::Error>;
+
+    /// Called for each attribute with where it was found in the input. Defaults to
+    /// calling [`TryCallbacks::attribute`].
+    #[inline]
+    fn attribute_at(
+        &mut self,
+        span: AttributeSpan,
+        name: &[u8],
+        value: &[u8],
+    ) -> Result<(), Self::Error> {
+        let _ = span;
+        self.attribute(name, value)
+    }
+
+    /// Called when an object ends
+    fn end_object(&mut self) -> Result<(), Self::Error>;
+
+    /// Called when a skipped syntax error cuts the current object short, see
+    /// [`Callbacks::discard_object`]. Ignores it by default.
+    #[inline]
+    fn discard_object(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Called for each side line, see [`RpslParser::with_side_lines`]. Ignores
+    /// the line by default.
+    #[inline]
+    fn side_line(&mut self, line: &[u8]) -> Result<(), Self::Error> {
+        let _ = line;
+        Ok(())
+    }
+
+    /// Called for each comment, see [`Callbacks::comment`]. Ignores the comment by
+    /// default.
+    #[inline]
+    fn comment(&mut self, comment: &[u8]) -> Result<(), Self::Error> {
+        let _ = comment;
+        Ok(())
+    }
+
+    /// Called for each comment trailing an attribute, see
+    /// [`Callbacks::attribute_comment`]. Calls [`TryCallbacks::comment`] by default.
+    #[inline]
+    fn attribute_comment(&mut self, name: &[u8], comment: &[u8]) -> Result<(), Self::Error> {
+        let _ = name;
+        self.comment(comment)
+    }
+
+    /// Called for each comment with its line number, see [`Callbacks::comment_at`].
+    /// Calls [`TryCallbacks::attribute_comment`] or [`TryCallbacks::comment`] by
+    /// default.
+    #[inline]
+    fn comment_at(
+        &mut self,
+        line_number: u32,
+        name: Option<&[u8]>,
+        comment: &[u8],
+    ) -> Result<(), Self::Error> {
+        let _ = line_number;
+        match name {
+            Some(name) => self.attribute_comment(name, comment),
+            None => self.comment(comment),
+        }
+    }
+
+    /// Called for the input of each attribute, see [`Callbacks::raw_line`]. Ignores
+    /// the line by default.
+    #[inline]
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) -> Result<(), Self::Error> {
+        let _ = (name, line);
+        Ok(())
+    }
+
+    /// Called for each sub-attribute, see [`Callbacks::sub_attribute`]. Ignores it
+    /// by default.
+    #[inline]
+    fn sub_attribute(
+        &mut self,
+        parent: &[u8],
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), Self::Error> {
+        let _ = (parent, key, value);
+        Ok(())
+    }
+
+    /// Called for each syntax error, see [`Callbacks::on_error`]. Aborts by
+    /// default.
+    #[inline]
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        let _ = err;
+        ErrorAction::Abort
+    }
+}
+
+/// Where an attribute was found in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AttributeSpan {
+    /// Line number (1-based) the attribute starts on
+    pub line_number: u32,
+
+    /// Number of continuation lines folded into the value
+    pub continuation_lines: u32,
+
+    /// Offset of the first byte of the attribute's line from the start of the
+    /// input, so that `&input[byte_offset..byte_offset + byte_len]` is its text
+    pub byte_offset: u64,
+
+    /// Number of bytes of input the attribute and its continuation lines take up,
+    /// including their line endings and trailing comments
+    pub byte_len: u64,
+}
+
+impl AttributeSpan {
+    #[inline]
+    fn new(line_number: u32, last_line_number: u32, byte_offset: u64, byte_len: u64) -> Self {
+        Self {
+            line_number,
+            continuation_lines: last_line_number - line_number,
+            byte_offset,
+            byte_len,
+        }
+    }
+
+    /// Whether the value was folded from continuation lines, even ones that add
+    /// nothing to it (such as a lone `+`)
+    #[inline]
+    pub fn is_continued(&self) -> bool {
+        self.continuation_lines > 0
+    }
+}
+
+impl<C: Callbacks + ?Sized> Callbacks for &mut C {
+    #[inline]
+    fn start_object(&mut self) {
+        (**self).start_object();
+    }
+
+    #[inline]
+    fn start_object_class(&mut self, class: &[u8]) {
+        (**self).start_object_class(class);
+    }
+
+    #[inline]
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        (**self).attribute(name, value);
+    }
+
+    #[inline]
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        (**self).attribute_at(span, name, value);
+    }
+
+    #[inline]
+    fn end_object(&mut self) {
+        (**self).end_object();
+    }
+
+    #[inline]
+    fn discard_object(&mut self) {
+        (**self).discard_object();
+    }
+
+    #[inline]
+    fn side_line(&mut self, line: &[u8]) {
+        (**self).side_line(line);
+    }
+
+    #[inline]
+    fn comment(&mut self, comment: &[u8]) {
+        (**self).comment(comment);
+    }
+
+    #[inline]
+    fn attribute_comment(&mut self, name: &[u8], comment: &[u8]) {
+        (**self).attribute_comment(name, comment);
+    }
+
+    #[inline]
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        (**self).comment_at(line_number, name, comment);
+    }
+
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) {
+        (**self).raw_line(name, line);
+    }
+
+    #[inline]
+    fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+        (**self).sub_attribute(parent, key, value);
+    }
+
+    #[inline]
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        (**self).on_error(err)
+    }
+}
+
+impl<C: Callbacks + ?Sized> Callbacks for Box<C> {
+    #[inline]
+    fn start_object(&mut self) {
+        (**self).start_object();
+    }
+
+    #[inline]
+    fn start_object_class(&mut self, class: &[u8]) {
+        (**self).start_object_class(class);
+    }
+
+    #[inline]
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        (**self).attribute(name, value);
+    }
+
+    #[inline]
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        (**self).attribute_at(span, name, value);
+    }
+
+    #[inline]
+    fn end_object(&mut self) {
+        (**self).end_object();
+    }
+
+    #[inline]
+    fn discard_object(&mut self) {
+        (**self).discard_object();
+    }
+
+    #[inline]
+    fn side_line(&mut self, line: &[u8]) {
+        (**self).side_line(line);
+    }
+
+    #[inline]
+    fn comment(&mut self, comment: &[u8]) {
+        (**self).comment(comment);
+    }
+
+    #[inline]
+    fn attribute_comment(&mut self, name: &[u8], comment: &[u8]) {
+        (**self).attribute_comment(name, comment);
+    }
+
+    #[inline]
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        (**self).comment_at(line_number, name, comment);
+    }
+
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) {
+        (**self).raw_line(name, line);
+    }
+
+    #[inline]
+    fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+        (**self).sub_attribute(parent, key, value);
+    }
+
+    #[inline]
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        (**self).on_error(err)
+    }
 }
 
 /// No-op implementation of callbacks
@@ -44,185 +482,1714 @@ impl Callbacks for Printer {
     }
 }
 
-/// RPSL Parser
-pub struct RpslParser<C> {
-    callbacks: C,
+/// Parse RPSL data into callbacks chosen at runtime, with default parser options
+///
+/// Every callback goes through dynamic dispatch, which costs an indirect call per
+/// attribute and keeps the callbacks from being inlined into the parse loop. For
+/// most inputs this is small next to the cost of reading and splitting lines, but
+/// use [`RpslParser`] with a concrete type where throughput matters.
+/// `RpslParser<Box<dyn Callbacks>>` works too, to also configure the parser.
+pub fn parse_dyn<R: BufRead>(reader: R, callbacks: &mut dyn Callbacks) -> Result<(), ParseError> {
+    RpslParser::new(callbacks).parse(reader)
 }
 
-#[derive(Error, Debug)]
-pub enum ParseError {
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
+/// How indentation shared by every line of the input is removed before parsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dedent {
+    /// Lines are parsed as-is
+    #[default]
+    None,
 
-    #[error("Unexpected end of file at line {line_number}")]
-    UnexpectedEof { line_number: u32 },
+    /// Strip up to this many leading spaces/tabs from every line
+    Fixed(usize),
 
-    #[error("InvalidSyntax: {message} at line {line_number}: {line}")]
-    InvalidSyntax {
-        line_number: u32,
-        message: &'static str,
-        line: String,
-    },
+    /// Use the indentation of the first non-blank line as the common indentation
+    Auto,
 }
 
-impl<C: Callbacks> RpslParser<C> {
-    pub fn new(callbacks: C) -> Self {
-        Self { callbacks }
-    }
-
-    pub fn into_callbacks(self) -> C {
-        self.callbacks
-    }
-
-    pub fn parse<R: BufRead>(&mut self, mut reader: R) -> Result<(), ParseError> {
-        let mut buf = Vec::with_capacity(8192);
-        let mut cont_buf = Vec::with_capacity(8192);
-        let mut in_object = false;
-        let mut line_number = 0;
+/// How continuation lines of multi-line attribute values are recognised
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContinuationStyle {
+    /// Lines starting with a space, tab or `+` continue the previous attribute
+    #[default]
+    Leading,
 
-        loop {
-            buf.clear();
+    /// Lines whose first non-whitespace character is at or beyond this (0-based)
+    /// column continue the previous attribute. Lines indented by less start a
+    /// new attribute, with the indentation ignored.
+    ColumnAligned(usize),
+}
 
-            let Some(line) = Self::read_line(&mut reader, &mut buf)? else {
-                if in_object {
-                    self.callbacks.end_object();
-                }
-                return Ok(());
-            };
-            line_number += 1;
+impl ContinuationStyle {
+    /// Whether a line continues the previous attribute
+    #[inline]
+    fn is_continuation(self, line: &[u8]) -> bool {
+        match self {
+            ContinuationStyle::Leading => matches!(line.first(), Some(b'+' | b' ' | b'\t')),
+            ContinuationStyle::ColumnAligned(column) => line
+                .iter()
+                .position(|&b| b != b' ' && b != b'\t')
+                .is_some_and(|n| n >= column),
+        }
+    }
 
-            let Some(clean_line) = Self::strip_comment(line) else {
-                continue;
-            };
+    /// Remove indentation from a line that starts an attribute
+    #[inline]
+    fn strip_indent(self, line: &[u8]) -> &[u8] {
+        match self {
+            ContinuationStyle::Leading => line,
+            ContinuationStyle::ColumnAligned(_) => trim_start(line),
+        }
+    }
 
-            if clean_line.is_empty() {
-                if in_object {
-                    self.callbacks.end_object();
-                    in_object = false;
+    /// The value contributed by a (non-empty) continuation line
+    ///
+    /// Indented lines lose all their indentation. A `+` is dropped along with the
+    /// whitespace after it, except for one space or tab if `keep_space_after_plus`.
+    #[inline]
+    fn continuation_value(self, line: &[u8], keep_space_after_plus: bool) -> &[u8] {
+        match (self, line.first()) {
+            (ContinuationStyle::Leading, Some(b'+')) => {
+                let rest = &line[1..];
+                let text = trim_start(rest);
+                if keep_space_after_plus && text.len() < rest.len() {
+                    &rest[rest.len() - text.len() - 1..]
+                } else {
+                    text
                 }
-                continue;
             }
+            _ => trim_start(line),
+        }
+    }
+}
 
-            if Self::is_continuation(clean_line[0]) {
-                return Err(ParseError::InvalidSyntax {
-                    line_number,
-                    message: "Unexpected continuation line",
-                    line: String::from_utf8_lossy(line).into(),
-                });
-            }
+/// How continuation lines are joined into a multi-line value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContinuationJoin {
+    /// Join lines with a space, giving a single-line value
+    #[default]
+    Space,
 
-            let Some(colon_pos) = memchr::memchr(b':', clean_line) else {
-                // Handle special EOF literal found in APNIC files
-                if clean_line == [b'E', b'O', b'F'] {
-                    return Ok(());
-                }
+    /// Join lines with `\n`, keeping the line structure of the value
+    Newline,
+}
 
-                return Err(ParseError::InvalidSyntax {
-                    line_number,
-                    message: "Expected an attribute",
-                    line: String::from_utf8_lossy(line).into(),
-                });
-            };
+impl ContinuationJoin {
+    #[inline]
+    fn separator(self) -> u8 {
+        match self {
+            ContinuationJoin::Space => b' ',
+            ContinuationJoin::Newline => b'\n',
+        }
+    }
+}
 
-            if colon_pos < 1 {
-                return Err(ParseError::InvalidSyntax {
-                    line_number,
-                    message: "Empty attribute name",
-                    line: String::from_utf8_lossy(line).into(),
-                });
-            }
+/// What to do with a continuation line that has no attribute to continue, e.g.
+/// at the start of an object whose class line was lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrayContinuation {
+    /// Fail with [`ParseError::InvalidSyntax`]
+    #[default]
+    Error,
 
-            if !in_object {
-                self.callbacks.start_object();
-                in_object = true;
-            }
+    /// Ignore the line
+    Skip,
 
-            let attr_name = &clean_line[0..colon_pos];
-            let attr_value = &clean_line[colon_pos + 1..];
+    /// Parse the line as an attribute, with its `+` and indentation removed
+    Attribute,
+}
 
-            if !Self::next_is_continuation(&mut reader)? {
-                self.callbacks.attribute(attr_name, Self::trim(attr_value));
-            } else {
-                let mut accumulated = Vec::with_capacity(512);
-                accumulated.extend_from_slice(Self::trim(attr_value));
-
-                loop {
-                    cont_buf.clear();
-                    let Some(cont_line) = Self::read_line(&mut reader, &mut cont_buf)? else {
-                        break;
-                    };
-                    line_number += 1;
-
-                    if let Some(clean_cont) = Self::strip_comment(cont_line) {
-                        if !clean_cont.is_empty() {
-                            accumulated.push(b' ');
-                            accumulated.extend_from_slice(Self::trim(&clean_cont[1..]));
-                        }
-                    }
+/// RPSL Parser
+pub struct RpslParser<C> {
+    callbacks: C,
+    dedent: Dedent,
+    continuation: ContinuationStyle,
+    separator: Option<fn(&[u8]) -> bool>,
+    class_boundary: Option<ClassBoundary>,
+    known_classes: Option<Vec<Vec<u8>>>,
+    side_line_prefix: Option<u8>,
+    max_continuation_lines: Option<u32>,
+    whitespace_separators: bool,
+    sub_attributes: Option<Vec<Vec<u8>>>,
+    sub_attribute_separator: u8,
+    comment_chars: CommentChars,
+    keep_space_after_plus: bool,
+    verbatim_attributes: Vec<Vec<u8>>,
+    stray_continuation: StrayContinuation,
+    continuation_join: ContinuationJoin,
+    validate_utf8: bool,
+    max_line_bytes: Option<usize>,
+    max_attribute_bytes: Option<usize>,
+    max_attributes_per_object: Option<usize>,
+    normalize_names: bool,
+    trim_values: bool,
+    stop_sentinel: Option<Vec<u8>>,
+    state: ParseState,
+    lines: LineBuffers,
+}
 
-                    if !Self::next_is_continuation(&mut reader)? {
-                        break;
-                    }
-                }
+/// Characters that start a comment, with the fastest search for their number
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CommentChars {
+    None,
+    One(u8),
+    Two(u8, u8),
+    Three(u8, u8, u8),
+    Many(Vec<u8>),
+}
 
-                self.callbacks.attribute(attr_name, &accumulated);
-            }
+impl CommentChars {
+    fn new(chars: &[u8]) -> Self {
+        match *chars {
+            [] => CommentChars::None,
+            [a] => CommentChars::One(a),
+            [a, b] => CommentChars::Two(a, b),
+            [a, b, c] => CommentChars::Three(a, b, c),
+            _ => CommentChars::Many(chars.to_vec()),
         }
     }
 
+    /// Position of the first comment character in a line
     #[inline]
-    fn next_is_continuation<R: BufRead>(reader: &mut R) -> Result<bool, ParseError> {
-        match Self::peek(reader)? {
-            Some(ch) => Ok(Self::is_continuation(ch)),
-            _ => Ok(false),
+    fn find(&self, line: &[u8]) -> Option<usize> {
+        match self {
+            CommentChars::None => None,
+            CommentChars::One(a) => memchr::memchr(*a, line),
+            CommentChars::Two(a, b) => memchr::memchr2(*a, *b, line),
+            CommentChars::Three(a, b, c) => memchr::memchr3(*a, *b, *c, line),
+            CommentChars::Many(chars) => line.iter().position(|b| chars.contains(b)),
         }
     }
+}
 
-    #[inline]
-    fn is_continuation(ch: u8) -> bool {
-        matches!(ch, b'+' | b' ' | b'\t')
-    }
+/// Attributes that start a new object even without a preceding blank line
+enum ClassBoundary {
+    /// The first attribute name of the current object
+    Repeat,
 
-    #[inline]
-    fn trim(buf: &[u8]) -> &[u8] {
-        match buf.iter().position(|&b| !b.is_ascii_whitespace()) {
-            Some(n) => &buf[n..],
-            None => buf,
-        }
-    }
+    /// Any of these names (lowercased)
+    Names(Vec<Vec<u8>>),
+}
 
+impl ClassBoundary {
+    /// Whether an attribute starts a new object, given the class of the current one
     #[inline]
-    fn peek<R: BufRead>(reader: &mut R) -> Result<Option<u8>, ParseError> {
-        match reader.fill_buf() {
-            Ok(buf) if buf.is_empty() => Ok(None),
-            Ok(buf) => Ok(Some(buf[0])),
-            Err(e) => Err(ParseError::Io(e)),
+    fn starts_object(&self, class: &[u8], name: &[u8]) -> bool {
+        match self {
+            ClassBoundary::Repeat => name.eq_ignore_ascii_case(class),
+            ClassBoundary::Names(names) => names.iter().any(|n| name.eq_ignore_ascii_case(n)),
         }
     }
+}
 
-    fn read_line<'a, R: BufRead>(
-        reader: &mut R,
-        buf: &'a mut Vec<u8>,
-    ) -> Result<Option<&'a [u8]>, ParseError> {
-        match reader.read_until(b'\n', buf) {
-            Ok(0) => Ok(None),
-            Ok(n) if n >= 2 && buf[n - 2] == b'\r' && buf[n - 1] == b'\n' => {
-                Ok(Some(&buf[0..n - 2]))
-            }
-            Ok(n) if n >= 1 && buf[n - 1] == b'\n' => Ok(Some(&buf[0..n - 1])),
-            Ok(n) => Ok(Some(&buf[0..n])), // EOF without newline
-            Err(e) => Err(ParseError::Io(e)),
-        }
-    }
+/// Parser state carried between calls to [`RpslParser::parse_object`]
+struct ParseState {
+    /// Accumulated value of a multi-line attribute
+    value_buf: Vec<u8>,
 
-    fn strip_comment(line: &[u8]) -> Option<&[u8]> {
-        match memchr::memchr2(b'%', b'#', line) {
-            None => Some(line),
-            Some(0) => None,
-            Some(n) => Some(&line[0..n]),
-        }
-    }
-}
+    /// Lowercased name of an attribute (see [`RpslParser::with_normalize_names`]),
+    /// or the name of a multi-line attribute
+    name_buf: Vec<u8>,
+
+    /// Input of a multi-line attribute, see [`Callbacks::raw_line`]
+    raw_buf: Vec<u8>,
+
+    line_number: u32,
+
+    /// Number of input bytes up to and including the current line
+    byte_offset: u64,
+
+    /// Indentation to strip from every line, `None` until detected
+    indent: Option<usize>,
+
+    /// Class (first attribute name) of the current object, tracked when objects can
+    /// start at a class boundary
+    class: Vec<u8>,
+
+    /// Whether an object has been started and not yet ended
+    in_object: bool,
+
+    /// Whether lines are skipped up to the next blank line, after a syntax error
+    skipping: bool,
+
+    /// Number of attributes of the current object
+    attributes: usize,
+
+    /// The attribute being parsed, see [`RpslParser::parse_line`]
+    attribute: PendingAttribute,
+
+    /// Whether the end of input (or a stop sentinel) has been reached
+    done: bool,
+
+    /// Time after which parsing fails with [`ParseError::Timeout`]
+    deadline: Option<Instant>,
+
+    /// Number of attributes whose continuation lines were truncated
+    truncated_attributes: u64,
+}
+
+impl ParseState {
+    fn new(dedent: Dedent) -> Self {
+        Self {
+            value_buf: Vec::with_capacity(512),
+            name_buf: Vec::new(),
+            raw_buf: Vec::new(),
+            line_number: 0,
+            byte_offset: 0,
+            indent: Self::initial_indent(dedent),
+            class: Vec::new(),
+            in_object: false,
+            skipping: false,
+            attributes: 0,
+            attribute: PendingAttribute::default(),
+            done: false,
+            deadline: None,
+            truncated_attributes: 0,
+        }
+    }
+
+    /// Reset to the state of a new parse, keeping the buffers' allocations
+    fn reset(&mut self, dedent: Dedent) {
+        let Self {
+            value_buf,
+            name_buf,
+            raw_buf,
+            line_number,
+            byte_offset,
+            indent,
+            class,
+            in_object,
+            skipping,
+            attributes,
+            attribute,
+            done,
+            deadline,
+            truncated_attributes,
+        } = self;
+        value_buf.clear();
+        name_buf.clear();
+        raw_buf.clear();
+        *line_number = 0;
+        *byte_offset = 0;
+        *indent = Self::initial_indent(dedent);
+        class.clear();
+        *in_object = false;
+        *skipping = false;
+        *attributes = 0;
+        *attribute = PendingAttribute::default();
+        *done = false;
+        *deadline = None;
+        *truncated_attributes = 0;
+    }
+
+    fn initial_indent(dedent: Dedent) -> Option<usize> {
+        match dedent {
+            Dedent::None => Some(0),
+            Dedent::Fixed(n) => Some(n),
+            Dedent::Auto => None,
+        }
+    }
+}
+
+/// An attribute whose line has been parsed, waiting to be passed to the callbacks
+/// once it is known whether the following lines continue it
+#[derive(Default)]
+struct PendingAttribute {
+    /// Line number of the attribute line
+    line_number: u32,
+
+    /// Offset of the attribute line in the input
+    byte_offset: u64,
+
+    /// Number of bytes of input the attribute takes up so far
+    byte_len: u64,
+
+    /// Range of the name in the attribute line, `None` if it is in `name_buf`
+    name: Option<Range<usize>>,
+
+    /// Range of the value in the attribute line
+    value: Range<usize>,
+
+    /// Whether continuation lines have been folded into `value_buf`, after copying
+    /// the name and value of the attribute line into the state's buffers (and its
+    /// input, unless the whole input is in memory)
+    continued: bool,
+
+    /// Whether the value is folded verbatim, see [`RpslParser::with_verbatim_attributes`]
+    verbatim: bool,
+
+    /// Whether trailing whitespace is stripped, see [`RpslParser::with_trim_values`]
+    trim: bool,
+}
+
+/// Line buffers of a parser reading from a [`BufRead`], kept between calls to
+/// [`RpslParser::parse_object`] for the lookahead line
+struct LineBuffers {
+    /// Current line
+    buf: Vec<u8>,
+
+    /// Lookahead line, valid when `peeked` is `Some(true)`
+    next_buf: Vec<u8>,
+
+    /// Whether the next line has been read into `next_buf`, and if so whether
+    /// there was one (`false` at end of input)
+    peeked: Option<bool>,
+}
+
+impl LineBuffers {
+    fn new() -> Self {
+        Self {
+            buf: Vec::with_capacity(8192),
+            next_buf: Vec::with_capacity(8192),
+            peeked: None,
+        }
+    }
+
+    /// Buffers without allocations, to stand in for the parser's while it parses
+    fn empty() -> Self {
+        Self {
+            buf: Vec::new(),
+            next_buf: Vec::new(),
+            peeked: None,
+        }
+    }
+
+    /// Forget the current and lookahead lines, keeping the buffers' allocations
+    fn clear(&mut self) {
+        self.buf.clear();
+        self.next_buf.clear();
+        self.peeked = None;
+    }
+
+    /// Move to the lookahead line, if one has been read
+    #[inline]
+    fn advance_to_peeked(&mut self) -> Option<bool> {
+        let has_line = self.peeked.take()?;
+        std::mem::swap(&mut self.buf, &mut self.next_buf);
+        Some(has_line)
+    }
+
+    /// Put the current line back as the lookahead line, see [`LineSource::unread`]
+    #[inline]
+    fn unread(&mut self) {
+        std::mem::swap(&mut self.buf, &mut self.next_buf);
+        self.peeked = Some(true);
+    }
+}
+
+/// The current and lookahead lines of the input, as seen by the parser
+trait Lines {
+    /// The current line, including its terminator
+    fn line(&self) -> &[u8];
+
+    /// The line after the current one, once looked at
+    fn next(&self) -> &[u8];
+
+    /// The input from byte `offset` to the end of the current line, if the whole
+    /// input is in memory, so that multi-line attributes need not be copied
+    fn input_from(&self, offset: u64) -> Option<&[u8]>;
+}
+
+/// Lines of the input, with one line of lookahead
+trait LineSource: Lines {
+    /// Move to the next line. Returns `false` at end of input.
+    fn advance(&mut self) -> Result<bool, ParseError>;
+
+    /// Look at the line after the current one, if not done yet. Returns `false` at
+    /// end of input.
+    fn peek(&mut self) -> Result<bool, ParseError>;
+
+    /// Put the current line back, to be returned again by the next call to
+    /// [`advance`](Self::advance). Only valid before a [`peek`](Self::peek).
+    fn unread(&mut self);
+}
+
+impl Lines for LineBuffers {
+    #[inline]
+    fn line(&self) -> &[u8] {
+        &self.buf
+    }
+
+    #[inline]
+    fn next(&self) -> &[u8] {
+        &self.next_buf
+    }
+
+    #[inline]
+    fn input_from(&self, _offset: u64) -> Option<&[u8]> {
+        None
+    }
+}
+
+/// Lines read from a [`BufRead`] into [`LineBuffers`]
+struct ReaderLines<'a, R> {
+    reader: &'a mut R,
+    buffers: &'a mut LineBuffers,
+    max_line_bytes: Option<usize>,
+}
+
+impl<R> Lines for ReaderLines<'_, R> {
+    #[inline]
+    fn line(&self) -> &[u8] {
+        self.buffers.line()
+    }
+
+    #[inline]
+    fn next(&self) -> &[u8] {
+        self.buffers.next()
+    }
+
+    #[inline]
+    fn input_from(&self, _offset: u64) -> Option<&[u8]> {
+        None
+    }
+}
+
+impl<R: BufRead> LineSource for ReaderLines<'_, R> {
+    #[inline]
+    fn advance(&mut self) -> Result<bool, ParseError> {
+        match self.buffers.advance_to_peeked() {
+            Some(has_line) => Ok(has_line),
+            None => Ok(read_line(
+                self.reader,
+                &mut self.buffers.buf,
+                self.max_line_bytes,
+            )?),
+        }
+    }
+
+    #[inline]
+    fn peek(&mut self) -> Result<bool, ParseError> {
+        if let Some(has_line) = self.buffers.peeked {
+            return Ok(has_line);
+        }
+        let has_line = read_line(self.reader, &mut self.buffers.next_buf, self.max_line_bytes)?;
+        self.buffers.peeked = Some(has_line);
+        Ok(has_line)
+    }
+
+    #[inline]
+    fn unread(&mut self) {
+        self.buffers.unread();
+    }
+}
+
+/// Lines of an in-memory input, found in place
+struct SliceLines<'a> {
+    input: &'a [u8],
+
+    /// Current line
+    line: &'a [u8],
+
+    /// Input after the current line
+    rest: &'a [u8],
+
+    /// Line after the current one, once peeked
+    next: Option<&'a [u8]>,
+}
+
+impl<'a> SliceLines<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        Self {
+            input,
+            line: &input[..0],
+            rest: input,
+            next: None,
+        }
+    }
+}
+
+impl Lines for SliceLines<'_> {
+    #[inline]
+    fn line(&self) -> &[u8] {
+        self.line
+    }
+
+    #[inline]
+    fn next(&self) -> &[u8] {
+        self.next.unwrap_or_default()
+    }
+
+    #[inline]
+    fn input_from(&self, offset: u64) -> Option<&[u8]> {
+        let end = subslice_range(self.input, self.line).end;
+        Some(&self.input[offset as usize..end])
+    }
+}
+
+impl LineSource for SliceLines<'_> {
+    #[inline]
+    fn advance(&mut self) -> Result<bool, ParseError> {
+        if self.rest.is_empty() {
+            return Ok(false);
+        }
+        let line = match self.next.take() {
+            Some(line) => line,
+            None => &self.rest[..first_line_len(self.rest)],
+        };
+        self.line = line;
+        self.rest = &self.rest[line.len()..];
+        Ok(true)
+    }
+
+    #[inline]
+    fn peek(&mut self) -> Result<bool, ParseError> {
+        if self.rest.is_empty() {
+            return Ok(false);
+        }
+        if self.next.is_none() {
+            self.next = Some(&self.rest[..first_line_len(self.rest)]);
+        }
+        Ok(true)
+    }
+
+    #[inline]
+    fn unread(&mut self) {
+        let start = subslice_range(self.input, self.line).start;
+        self.rest = &self.input[start..];
+        self.line = &self.input[start..start];
+        self.next = None;
+    }
+}
+
+/// What comes after a line passed to [`RpslParser::parse_line`]
+enum LineStep {
+    /// Nothing, go on with the next line
+    Next,
+
+    /// The line starts an attribute, which the following lines may continue
+    Attribute,
+
+    /// The line ended the current object
+    EndObject,
+
+    /// The line starts a new object at a class boundary, after ending the current
+    /// one. It is to be parsed again as the first line of the new object.
+    Boundary,
+
+    /// The line is the stop sentinel, ending the input
+    Eof,
+}
+
+/// Meaning of a line that does not continue an attribute
+enum LineKind<'a> {
+    /// Comment-only line, with the comment
+    Comment(&'a [u8]),
+
+    /// Blank line or separator, ending the current object
+    Separator,
+
+    /// The stop sentinel, see [`RpslParser::with_stop_sentinel`]
+    Eof,
+
+    /// Line to ignore, see [`StrayContinuation::Skip`]
+    Skip,
+
+    /// Start of an attribute, with its name, (untrimmed) value and trailing comment
+    Attribute(&'a [u8], &'a [u8], Option<&'a [u8]>),
+}
+
+#[derive(Error, Debug)]
+pub enum ParseError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Timed out at line {line_number}")]
+    Timeout { line_number: u32 },
+
+    #[error("Unexpected end of file at line {line_number}")]
+    UnexpectedEof { line_number: u32 },
+
+    #[error("InvalidSyntax: {message} at line {line_number}: {line}")]
+    InvalidSyntax {
+        line_number: u32,
+        message: &'static str,
+        line: String,
+    },
+
+    /// An attribute `name` or `value` is not valid UTF-8, see
+    /// [`RpslParser::with_utf8_validation`]
+    #[error("Invalid UTF-8 in attribute {field} at line {line_number}")]
+    InvalidUtf8 {
+        line_number: u32,
+        field: &'static str,
+    },
+
+    /// A size `limit` set on the parser, e.g. `max_line_bytes`, was exceeded
+    #[error("Limit {limit} exceeded at line {line_number}")]
+    LimitExceeded {
+        line_number: u32,
+        limit: &'static str,
+    },
+}
+
+impl<C: Callbacks> RpslParser<C> {
+    pub fn new(callbacks: C) -> Self {
+        Self {
+            callbacks,
+            dedent: Dedent::None,
+            continuation: ContinuationStyle::Leading,
+            separator: None,
+            class_boundary: None,
+            known_classes: None,
+            side_line_prefix: None,
+            max_continuation_lines: None,
+            whitespace_separators: false,
+            sub_attributes: None,
+            sub_attribute_separator: b':',
+            comment_chars: CommentChars::Two(b'%', b'#'),
+            keep_space_after_plus: false,
+            verbatim_attributes: vec![b"certif".to_vec()],
+            stray_continuation: StrayContinuation::Error,
+            continuation_join: ContinuationJoin::Space,
+            validate_utf8: false,
+            max_line_bytes: None,
+            max_attribute_bytes: None,
+            max_attributes_per_object: None,
+            normalize_names: false,
+            trim_values: false,
+            stop_sentinel: Some(b"EOF".to_vec()),
+            state: ParseState::new(Dedent::None),
+            lines: LineBuffers::new(),
+        }
+    }
+
+    /// Strip indentation common to every line before parsing, e.g. for RPSL
+    /// embedded in a larger document
+    pub fn with_dedent(mut self, dedent: Dedent) -> Self {
+        self.dedent = dedent;
+        self.state = ParseState::new(dedent);
+        self
+    }
+
+    /// Set how continuation lines are recognised
+    pub fn with_continuation_style(mut self, continuation: ContinuationStyle) -> Self {
+        self.continuation = continuation;
+        self
+    }
+
+    /// Treat lines matching `predicate` as object separators, in addition to blank lines
+    pub fn with_separator(mut self, predicate: fn(&[u8]) -> bool) -> Self {
+        self.separator = Some(predicate);
+        self
+    }
+
+    /// Treat YAML-style `---` document markers as object separators
+    pub fn with_yaml_separators(self) -> Self {
+        self.with_separator(is_yaml_separator)
+    }
+
+    /// Start a new object whenever the class (first attribute name) of the current
+    /// object appears again, even without a blank line in between, e.g. to salvage
+    /// dumps with no separators at all
+    pub fn with_boundary_on_class_change(mut self) -> Self {
+        self.class_boundary = Some(ClassBoundary::Repeat);
+        self
+    }
+
+    /// Start a new object whenever one of these attribute names appears after the
+    /// first attribute of an object, even without a blank line in between
+    pub fn with_boundary_on_classes(mut self, classes: &[&str]) -> Self {
+        let names = classes
+            .iter()
+            .map(|class| class.as_bytes().to_ascii_lowercase())
+            .collect();
+        self.class_boundary = Some(ClassBoundary::Names(names));
+        self
+    }
+
+    /// Treat lines containing only whitespace as blank lines, ending the current
+    /// object, rather than as (empty) continuation lines
+    pub fn with_whitespace_separators(mut self) -> Self {
+        self.whitespace_separators = true;
+        self
+    }
+
+    /// Best-effort preset for salvaging badly formatted dumps that fail to parse
+    /// strictly: objects also end at whitespace-only lines
+    /// ([`with_whitespace_separators`](Self::with_whitespace_separators)) and when
+    /// their class reappears
+    /// ([`with_boundary_on_class_change`](Self::with_boundary_on_class_change)).
+    ///
+    /// These are heuristics and can split objects wrongly, e.g. an object that
+    /// legitimately repeats its class attribute, so use this as a last resort.
+    pub fn best_effort(self) -> Self {
+        self.with_whitespace_separators()
+            .with_boundary_on_class_change()
+    }
+
+    /// Require the first attribute of every object to be one of these classes,
+    /// failing with [`ParseError::InvalidSyntax`] otherwise, e.g. to catch stray
+    /// attributes or misaligned input early. By default any attribute can start an
+    /// object.
+    pub fn require_known_class(mut self, classes: &[&str]) -> Self {
+        let names = classes
+            .iter()
+            .map(|class| class.as_bytes().to_ascii_lowercase())
+            .collect();
+        self.known_classes = Some(names);
+        self
+    }
+
+    /// Pass lines starting with `prefix` that occur between objects to
+    /// [`Callbacks::side_line`] instead of parsing them, e.g. `b'{'` for JSON status
+    /// lines interleaved with RPSL. Such lines inside an object are parsed as usual.
+    pub fn with_side_lines(mut self, prefix: u8) -> Self {
+        self.side_line_prefix = Some(prefix);
+        self
+    }
+
+    /// Keep at most this many continuation lines of an attribute, skipping any
+    /// further ones, to bound the size of runaway multi-line values. Skipped lines
+    /// still count towards [`AttributeSpan::continuation_lines`], and truncated
+    /// attributes are counted by [`RpslParser::truncated_attributes`].
+    pub fn with_max_continuation_lines(mut self, max: u32) -> Self {
+        self.max_continuation_lines = Some(max);
+        self
+    }
+
+    /// Split the values of these attributes (matched case-insensitively) once more,
+    /// at the first sub-attribute separator (`:` by default), and pass the trimmed
+    /// key and value to [`Callbacks::sub_attribute`] after the attribute itself,
+    /// e.g. `remarks: contact: noc@example.net`. Values without the separator or
+    /// with an empty key have no sub-attribute. By default no attributes are split.
+    pub fn with_sub_attributes(mut self, names: &[&str]) -> Self {
+        let names = names
+            .iter()
+            .map(|name| name.as_bytes().to_ascii_lowercase())
+            .collect();
+        self.sub_attributes = Some(names);
+        self
+    }
+
+    /// Keep one space (or tab) between a `+` continuation marker and the text after
+    /// it, e.g. to preserve the layout of `+ indented` lines. By default the `+`
+    /// and all whitespace after it are dropped, just like the indentation of lines
+    /// continued with a space or tab. Either way, continuation lines are joined to
+    /// the value with a single space.
+    pub fn with_space_after_plus(mut self, keep: bool) -> Self {
+        self.keep_space_after_plus = keep;
+        self
+    }
+
+    /// Fold the continuation lines of these attributes (matched case-insensitively)
+    /// with `\n` instead of a space, and without looking for comments in them, so
+    /// that multi-line blocks such as the PEM or PGP key in `certif:` keep their
+    /// line structure. Indentation and `+` markers are still removed, and lines
+    /// that are empty after them are kept as empty lines. Defaults to `certif`;
+    /// pass an empty slice to fold every attribute with spaces.
+    pub fn with_verbatim_attributes(mut self, names: &[&str]) -> Self {
+        self.verbatim_attributes = names
+            .iter()
+            .map(|name| name.as_bytes().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Characters that start a comment, by default `%` and `#`. A comment runs to
+    /// the end of the line, on comment-only lines as well as after an attribute.
+    /// Pass an empty slice to disable comments, e.g. for values containing `#`.
+    ///
+    /// Up to three characters are searched for with a vectorized scan; larger
+    /// sets fall back to checking every byte, which is noticeably slower.
+    pub fn with_comment_chars(mut self, chars: &[u8]) -> Self {
+        self.comment_chars = CommentChars::new(chars);
+        self
+    }
+
+    /// How continuation lines are joined into a multi-line value. Defaults to
+    /// [`ContinuationJoin::Space`]. Each line is trimmed and stripped of comments
+    /// as usual; see [`with_verbatim_attributes`](Self::with_verbatim_attributes)
+    /// to keep values exactly as written.
+    pub fn with_continuation_join(mut self, join: ContinuationJoin) -> Self {
+        self.continuation_join = join;
+        self
+    }
+
+    /// Fail with [`ParseError::InvalidUtf8`] on attribute names or values that are
+    /// not valid UTF-8, rather than leaving them to be decoded lossily. Disabled by
+    /// default.
+    pub fn with_utf8_validation(mut self, validate: bool) -> Self {
+        self.validate_utf8 = validate;
+        self
+    }
+
+    /// Lowercase (ASCII) attribute names before passing them to the callbacks, as
+    /// names are case-insensitive but dumps mix e.g. `Origin` and `origin`. Values
+    /// are left as they are. Disabled by default.
+    pub fn with_normalize_names(mut self, normalize: bool) -> Self {
+        self.normalize_names = normalize;
+        self
+    }
+
+    /// Line that ends the input, ignoring anything after it. Defaults to `EOF`, which
+    /// ends APNIC dumps; `None` parses such a line like any other (failing, as it
+    /// has no colon).
+    pub fn with_stop_sentinel(mut self, sentinel: Option<Vec<u8>>) -> Self {
+        self.stop_sentinel = sentinel;
+        self
+    }
+
+    /// Strip trailing whitespace from attribute values, e.g. from files edited on
+    /// Windows. Leading whitespace is always stripped. In multi-line values each
+    /// line is trimmed before joining, so spacing between lines is kept. Values of
+    /// [verbatim attributes](Self::with_verbatim_attributes) are left as they are.
+    /// Disabled by default.
+    pub fn with_trim_values(mut self, trim: bool) -> Self {
+        self.trim_values = trim;
+        self
+    }
+
+    /// Fail with [`ParseError::LimitExceeded`] on lines longer than `max` bytes,
+    /// not counting the line terminator. Unlimited by default.
+    ///
+    /// Like the other limits, this guards against untrusted input using unbounded
+    /// memory; the error is not passed to [`Callbacks::on_error`] and always stops
+    /// parsing.
+    pub fn with_max_line_bytes(mut self, max: usize) -> Self {
+        self.max_line_bytes = Some(max);
+        self
+    }
+
+    /// Fail with [`ParseError::LimitExceeded`] on attribute values longer than
+    /// `max` bytes, checked as continuation lines are joined. Unlimited by default.
+    pub fn with_max_attribute_bytes(mut self, max: usize) -> Self {
+        self.max_attribute_bytes = Some(max);
+        self
+    }
+
+    /// Fail with [`ParseError::LimitExceeded`] on objects with more than `max`
+    /// attributes. Unlimited by default.
+    pub fn with_max_attributes_per_object(mut self, max: usize) -> Self {
+        self.max_attributes_per_object = Some(max);
+        self
+    }
+
+    /// What to do with continuation lines that don't follow an attribute. Defaults
+    /// to [`StrayContinuation::Error`]; the other strategies recover from input
+    /// where an object lost its class line, at the cost of hiding that damage.
+    pub fn with_stray_continuation(mut self, stray_continuation: StrayContinuation) -> Self {
+        self.stray_continuation = stray_continuation;
+        self
+    }
+
+    /// Separator between the key and value of sub-attributes, see
+    /// [`RpslParser::with_sub_attributes`]. Defaults to `b':'`.
+    pub fn with_sub_attribute_separator(mut self, separator: u8) -> Self {
+        self.sub_attribute_separator = separator;
+        self
+    }
+
+    /// Number of attributes truncated by
+    /// [`with_max_continuation_lines`](Self::with_max_continuation_lines) since the
+    /// current parse started
+    pub fn truncated_attributes(&self) -> u64 {
+        self.state.truncated_attributes
+    }
+
+    pub fn into_callbacks(self) -> C {
+        self.callbacks
+    }
+
+    /// The same parser with other callbacks, ready to parse a new input
+    pub(crate) fn replace_callbacks<D>(self, callbacks: D) -> RpslParser<D> {
+        RpslParser {
+            callbacks,
+            dedent: self.dedent,
+            continuation: self.continuation,
+            separator: self.separator,
+            class_boundary: self.class_boundary,
+            known_classes: self.known_classes,
+            side_line_prefix: self.side_line_prefix,
+            max_continuation_lines: self.max_continuation_lines,
+            whitespace_separators: self.whitespace_separators,
+            sub_attributes: self.sub_attributes,
+            sub_attribute_separator: self.sub_attribute_separator,
+            comment_chars: self.comment_chars,
+            keep_space_after_plus: self.keep_space_after_plus,
+            verbatim_attributes: self.verbatim_attributes,
+            stray_continuation: self.stray_continuation,
+            continuation_join: self.continuation_join,
+            validate_utf8: self.validate_utf8,
+            max_line_bytes: self.max_line_bytes,
+            max_attribute_bytes: self.max_attribute_bytes,
+            max_attributes_per_object: self.max_attributes_per_object,
+            normalize_names: self.normalize_names,
+            trim_values: self.trim_values,
+            stop_sentinel: self.stop_sentinel,
+            state: ParseState::new(self.dedent),
+            lines: LineBuffers::new(),
+        }
+    }
+
+    /// Mutable access to the callbacks, e.g. to inspect or drain them between objects
+    pub fn callbacks_mut(&mut self) -> &mut C {
+        &mut self.callbacks
+    }
+
+    /// Reset the parse state, to continue with another reader after
+    /// [`parse_object`](Self::parse_object), and return the callbacks so that they
+    /// can be reset too
+    ///
+    /// [`parse`](Self::parse) and the other methods parsing a whole input start from
+    /// a fresh state anyway. Either way the parser keeps its buffers, so one parser
+    /// can be reused for many inputs without reallocating.
+    pub fn reset(&mut self) -> &mut C {
+        self.state.reset(self.dedent);
+        self.lines.clear();
+        &mut self.callbacks
+    }
+
+    pub fn parse<R: BufRead>(&mut self, reader: R) -> Result<(), ParseError> {
+        self.parse_with_deadline(reader, None)
+    }
+
+    /// Parse input, failing with [`ParseError::Timeout`] once `deadline` has passed
+    ///
+    /// The deadline is only checked between lines, so a read that blocks is not
+    /// interrupted; it fails when (and if) the read returns. Objects completed
+    /// before the timeout have already been passed to the callbacks.
+    pub fn parse_with_deadline<R: BufRead>(
+        &mut self,
+        mut reader: R,
+        deadline: Option<Instant>,
+    ) -> Result<(), ParseError> {
+        self.reset();
+        self.state.deadline = deadline;
+        while self.parse_object(&mut reader)? {}
+        Ok(())
+    }
+
+    /// Parse an unbuffered reader, e.g. a decompressor, by wrapping it in a
+    /// [`BufReader`]
+    ///
+    /// The parser reads line by line, so an unbuffered reader would be very slow.
+    /// Prefer [`RpslParser::parse`] if the reader is already buffered.
+    pub fn parse_unbuffered<R: Read>(&mut self, reader: R) -> Result<(), ParseError> {
+        self.parse(BufReader::new(reader))
+    }
+
+    /// Parse a string, e.g. a small input in a test, see [`RpslParser::parse_slice`]
+    ///
+    /// ```
+    /// use rpsl_parser::{Printer, RpslParser};
+    ///
+    /// RpslParser::new(Printer)
+    ///     .parse_str("route: 192.0.2.0/24\norigin: AS1\n")
+    ///     .unwrap();
+    /// ```
+    pub fn parse_str(&mut self, input: &str) -> Result<(), ParseError> {
+        self.parse_slice(input.as_bytes())
+    }
+
+    /// Parse input that is entirely in memory, e.g. a memory-mapped file
+    ///
+    /// Equivalent to [`RpslParser::parse`], but lines are found in place rather than
+    /// copied into a line buffer, and attribute names and values are passed to the
+    /// callbacks as slices of `input` (except for multi-line values, which are
+    /// still joined in a buffer).
+    pub fn parse_slice(&mut self, input: &[u8]) -> Result<(), ParseError> {
+        self.reset();
+        let mut lines = SliceLines::new(input);
+        while self.parse_lines(&mut lines)? {}
+        Ok(())
+    }
+
+    /// Parse input up to and including the next object, calling the callbacks for it.
+    ///
+    /// Returns `false` when there are no more objects. Parse state (line numbers,
+    /// lookahead) carries over between calls, so the same reader must be passed
+    /// each time.
+    pub fn parse_object<R: BufRead>(&mut self, reader: &mut R) -> Result<bool, ParseError> {
+        // Taken out of the parser while parsing, as the current line is borrowed from them
+        let mut buffers = std::mem::replace(&mut self.lines, LineBuffers::empty());
+        let mut lines = ReaderLines {
+            reader,
+            buffers: &mut buffers,
+            max_line_bytes: self.max_line_bytes,
+        };
+        let result = self.parse_lines(&mut lines);
+        self.lines = buffers;
+        result
+    }
+
+    /// Parse lines up to and including the next object, see
+    /// [`parse_object`](Self::parse_object)
+    fn parse_lines<L: LineSource>(&mut self, lines: &mut L) -> Result<bool, ParseError> {
+        if self.state.done {
+            return Ok(false);
+        }
+
+        loop {
+            if !lines.advance()? {
+                return Ok(self.end_input());
+            }
+            match self.parse_line(lines.line())? {
+                LineStep::Next => {}
+                LineStep::Attribute => {
+                    while lines.peek()? && self.continues_attribute(lines.next()) {
+                        self.fold_line(lines)?;
+                        lines.advance()?;
+                    }
+                    self.end_attribute(lines)?;
+                }
+                LineStep::EndObject => return Ok(true),
+                LineStep::Boundary => {
+                    lines.unread();
+                    return Ok(true);
+                }
+                LineStep::Eof => {
+                    self.state.done = true;
+                    return Ok(false);
+                }
+            }
+        }
+    }
+
+    /// Handle the end of input, ending the current object if any. Returns whether
+    /// there was one.
+    fn end_input(&mut self) -> bool {
+        let state = &mut self.state;
+        state.done = true;
+        if !state.in_object {
+            return false;
+        }
+        state.in_object = false;
+        self.callbacks.end_object();
+        true
+    }
+
+    /// Parse a line (including its terminator) that does not continue an
+    /// attribute
+    ///
+    /// An attribute started by the line is passed to the callbacks by
+    /// [`end_attribute`](Self::end_attribute), after any continuation lines have
+    /// been folded into it with [`fold_line`](Self::fold_line).
+    #[inline]
+    fn parse_line(&mut self, raw_line: &[u8]) -> Result<LineStep, ParseError> {
+        let state = &mut self.state;
+        Self::check_deadline(state.deadline, state.line_number)?;
+        state.line_number += 1;
+        let line_number = state.line_number;
+        Self::check_limit(
+            self.max_line_bytes,
+            Self::line(raw_line).len(),
+            "max_line_bytes",
+            line_number,
+        )?;
+        let line_offset = state.byte_offset;
+        state.byte_offset += raw_line.len() as u64;
+
+        let line = Self::dedent_line(Self::line(raw_line), &mut state.indent);
+
+        if state.skipping {
+            state.skipping = !Self::is_blank(self.separator, line);
+            return Ok(LineStep::Next);
+        }
+
+        if !state.in_object
+            && self
+                .side_line_prefix
+                .is_some_and(|p| line.first() == Some(&p))
+        {
+            self.callbacks.side_line(line);
+            return Ok(LineStep::Next);
+        }
+
+        let kind = Self::classify_line(
+            self.continuation,
+            self.separator,
+            self.whitespace_separators,
+            &self.comment_chars,
+            self.stray_continuation,
+            self.stop_sentinel.as_deref(),
+            line,
+            line_number,
+        );
+        let (name, value, comment) = match kind {
+            Ok(LineKind::Attribute(name, value, comment)) => (name, value, comment),
+            Ok(LineKind::Comment(comment)) => {
+                self.callbacks.comment_at(line_number, None, comment);
+                return Ok(LineStep::Next);
+            }
+            Ok(LineKind::Skip) => return Ok(LineStep::Next),
+            Ok(LineKind::Separator) if state.in_object => {
+                state.in_object = false;
+                self.callbacks.end_object();
+                return Ok(LineStep::EndObject);
+            }
+            Ok(LineKind::Separator) => return Ok(LineStep::Next),
+            Ok(LineKind::Eof) => return Ok(LineStep::Eof),
+            Err(err) => {
+                self.skip_error(err)?;
+                return Ok(LineStep::Next);
+            }
+        };
+        let value = trim_start(value);
+        let in_name_buf = self.normalize_names;
+        let name = if in_name_buf {
+            state.name_buf.clear();
+            state
+                .name_buf
+                .extend(name.iter().map(u8::to_ascii_lowercase));
+            state.name_buf.as_slice()
+        } else {
+            name
+        };
+
+        if let Some(boundary) = &self.class_boundary {
+            if state.in_object && boundary.starts_object(&state.class, name) {
+                state.in_object = false;
+                self.callbacks.end_object();
+
+                // Parse the line again as the start of the next object
+                state.line_number -= 1;
+                state.byte_offset = line_offset;
+                return Ok(LineStep::Boundary);
+            }
+            if !state.in_object {
+                state.class.clear();
+                state.class.extend_from_slice(name);
+            }
+        }
+
+        if !state.in_object {
+            let known_classes = self.known_classes.as_deref();
+            if let Err(err) = Self::check_class(known_classes, name, line, line_number) {
+                self.skip_error(err)?;
+                return Ok(LineStep::Next);
+            }
+            state.attributes = 0;
+            state.in_object = true;
+            self.callbacks.start_object_class(name);
+        }
+        state.attributes += 1;
+        Self::check_limit(
+            self.max_attributes_per_object,
+            state.attributes,
+            "max_attributes_per_object",
+            line_number,
+        )?;
+        if let Some(comment) = comment {
+            self.callbacks.comment_at(line_number, Some(name), comment);
+        }
+
+        let verbatim = Self::is_verbatim(&self.verbatim_attributes, name);
+        let trim = self.trim_values && !verbatim;
+        let value = Self::trim_end(trim, value);
+        state.attribute = PendingAttribute {
+            line_number,
+            byte_offset: line_offset,
+            byte_len: raw_line.len() as u64,
+            name: (!in_name_buf).then(|| subslice_range(raw_line, name)),
+            value: subslice_range(raw_line, value),
+            continued: false,
+            verbatim,
+            trim,
+        };
+        Ok(LineStep::Attribute)
+    }
+
+    /// Whether a line (including its terminator) continues the current attribute
+    #[inline]
+    fn continues_attribute(&mut self, raw_line: &[u8]) -> bool {
+        let line = Self::dedent_line(Self::line(raw_line), &mut self.state.indent);
+        Self::is_continuation(self.continuation, self.whitespace_separators, line)
+    }
+
+    /// Fold the next line (a continuation line) into the current attribute
+    #[inline]
+    fn fold_line(&mut self, lines: &impl Lines) -> Result<(), ParseError> {
+        let (previous, raw_line) = (lines.line(), lines.next());
+        let in_memory = lines.input_from(0).is_some();
+        let state = &mut self.state;
+        Self::check_deadline(state.deadline, state.line_number)?;
+        let attribute = &mut state.attribute;
+
+        if !attribute.continued {
+            // The line source may not keep the attribute line around
+            attribute.continued = true;
+            if let Some(name) = &attribute.name {
+                state.name_buf.clear();
+                state.name_buf.extend_from_slice(&previous[name.clone()]);
+            }
+            state.value_buf.clear();
+            state
+                .value_buf
+                .extend_from_slice(&previous[attribute.value.clone()]);
+            state.raw_buf.clear();
+            if !in_memory {
+                state.raw_buf.extend_from_slice(previous);
+            }
+        }
+
+        state.line_number += 1;
+        Self::check_limit(
+            self.max_line_bytes,
+            Self::line(raw_line).len(),
+            "max_line_bytes",
+            state.line_number,
+        )?;
+        attribute.byte_len += raw_line.len() as u64;
+        if !in_memory {
+            state.raw_buf.extend_from_slice(raw_line);
+        }
+        state.byte_offset += raw_line.len() as u64;
+
+        let line = Self::dedent_line(Self::line(raw_line), &mut state.indent);
+        let (clean_line, comment) = if attribute.verbatim {
+            (line, None)
+        } else {
+            Self::split_comment(&self.comment_chars, line)
+        };
+        if let Some(comment) = comment {
+            self.callbacks
+                .comment_at(state.line_number, Some(&state.name_buf), comment);
+        }
+        if (attribute.verbatim || !clean_line.is_empty())
+            && self
+                .max_continuation_lines
+                .is_none_or(|max| state.line_number - attribute.line_number <= max)
+        {
+            // No separator before a value that starts on a continuation line
+            if !state.value_buf.is_empty() {
+                state.value_buf.push(if attribute.verbatim {
+                    b'\n'
+                } else {
+                    self.continuation_join.separator()
+                });
+            }
+            state.value_buf.extend_from_slice(Self::trim_end(
+                attribute.trim,
+                self.continuation
+                    .continuation_value(clean_line, self.keep_space_after_plus),
+            ));
+        }
+        Self::check_limit(
+            self.max_attribute_bytes,
+            state.value_buf.len(),
+            "max_attribute_bytes",
+            state.line_number,
+        )
+    }
+
+    /// Pass the current attribute to the callbacks, once the current line is its
+    /// last one
+    #[inline]
+    fn end_attribute(&mut self, lines: &impl Lines) -> Result<(), ParseError> {
+        let line = lines.line();
+        let state = &mut self.state;
+        let attribute = &state.attribute;
+        let (name, value, raw_line) = if attribute.continued {
+            if attribute.trim {
+                // A trailing empty continuation line leaves a separator behind
+                state
+                    .value_buf
+                    .truncate(state.value_buf.trim_ascii_end().len());
+            }
+            if self
+                .max_continuation_lines
+                .is_some_and(|max| state.line_number - attribute.line_number > max)
+            {
+                state.truncated_attributes += 1;
+            }
+            (
+                state.name_buf.as_slice(),
+                state.value_buf.as_slice(),
+                lines
+                    .input_from(attribute.byte_offset)
+                    .unwrap_or(&state.raw_buf),
+            )
+        } else {
+            let name = match &attribute.name {
+                Some(name) => &line[name.clone()],
+                None => state.name_buf.as_slice(),
+            };
+            (name, &line[attribute.value.clone()], line)
+        };
+
+        Self::check_limit(
+            self.max_attribute_bytes,
+            value.len(),
+            "max_attribute_bytes",
+            attribute.line_number,
+        )?;
+        if let Err(err) = Self::check_utf8(self.validate_utf8, name, value, attribute.line_number) {
+            return self.skip_error(err);
+        }
+        let span = AttributeSpan::new(
+            attribute.line_number,
+            state.line_number,
+            attribute.byte_offset,
+            attribute.byte_len,
+        );
+        self.callbacks.attribute_at(span, name, value);
+        self.callbacks.raw_line(name, raw_line);
+        Self::sub_attribute(
+            &mut self.callbacks,
+            self.sub_attributes.as_deref(),
+            self.sub_attribute_separator,
+            name,
+            value,
+        );
+        Ok(())
+    }
+
+    /// Ask the callbacks what to do about a syntax error, returning it unless they
+    /// skip it, in which case lines are skipped up to the next blank line
+    #[inline]
+    fn skip_error(&mut self, err: ParseError) -> Result<(), ParseError> {
+        match self.callbacks.on_error(&err) {
+            ErrorAction::Skip => {
+                self.state.skipping = true;
+                if self.state.in_object {
+                    self.state.in_object = false;
+                    self.callbacks.discard_object();
+                }
+                Ok(())
+            }
+            ErrorAction::Abort => Err(err),
+        }
+    }
+
+    #[inline]
+    fn check_deadline(deadline: Option<Instant>, line_number: u32) -> Result<(), ParseError> {
+        match deadline {
+            Some(deadline) if Instant::now() >= deadline => {
+                Err(ParseError::Timeout { line_number })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Pass the sub-attribute in the value of a configured attribute, if any
+    #[inline]
+    fn sub_attribute(
+        callbacks: &mut C,
+        names: Option<&[Vec<u8>]>,
+        separator: u8,
+        name: &[u8],
+        value: &[u8],
+    ) {
+        let Some(names) = names else {
+            return;
+        };
+        if !names.iter().any(|n| name.eq_ignore_ascii_case(n)) {
+            return;
+        }
+        if let Some(pos) = memchr::memchr(separator, value) {
+            let key = value[..pos].trim_ascii();
+            if !key.is_empty() {
+                callbacks.sub_attribute(name, key, value[pos + 1..].trim_ascii());
+            }
+        }
+    }
+
+    /// Whether the continuation lines of an attribute are folded verbatim, see
+    /// [`RpslParser::with_verbatim_attributes`]
+    #[inline]
+    fn is_verbatim(verbatim_attributes: &[Vec<u8>], name: &[u8]) -> bool {
+        verbatim_attributes
+            .iter()
+            .any(|verbatim| name.eq_ignore_ascii_case(verbatim))
+    }
+
+    /// Strip trailing whitespace from (part of) a value, if enabled
+    #[inline]
+    fn trim_end(trim: bool, value: &[u8]) -> &[u8] {
+        if trim { value.trim_ascii_end() } else { value }
+    }
+
+    /// Fail with [`ParseError::LimitExceeded`] if `size` is over the `max` set for
+    /// `limit`, if any
+    #[inline]
+    fn check_limit(
+        max: Option<usize>,
+        size: usize,
+        limit: &'static str,
+        line_number: u32,
+    ) -> Result<(), ParseError> {
+        match max {
+            Some(max) if size > max => Err(ParseError::LimitExceeded { line_number, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that an attribute name and value are valid UTF-8, if enabled
+    #[inline]
+    fn check_utf8(
+        validate: bool,
+        name: &[u8],
+        value: &[u8],
+        line_number: u32,
+    ) -> Result<(), ParseError> {
+        if !validate {
+            return Ok(());
+        }
+        let field = if std::str::from_utf8(name).is_err() {
+            "name"
+        } else if std::str::from_utf8(value).is_err() {
+            "value"
+        } else {
+            return Ok(());
+        };
+        Err(ParseError::InvalidUtf8 { line_number, field })
+    }
+
+    /// Whether a (dedented) line ends the lines skipped after a syntax error
+    #[inline]
+    fn is_blank(separator: Option<fn(&[u8]) -> bool>, line: &[u8]) -> bool {
+        trim_start(line).is_empty() || separator.is_some_and(|sep| sep(line))
+    }
+
+    /// Check the first attribute of an object against the required classes, if any
+    #[inline]
+    fn check_class(
+        known_classes: Option<&[Vec<u8>]>,
+        name: &[u8],
+        line: &[u8],
+        line_number: u32,
+    ) -> Result<(), ParseError> {
+        match known_classes {
+            Some(classes) if !classes.iter().any(|c| name.eq_ignore_ascii_case(c)) => {
+                Err(ParseError::InvalidSyntax {
+                    line_number,
+                    message: "Unknown object class",
+                    line: String::from_utf8_lossy(line).into(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Classify a (dedented) line that does not continue an attribute
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    fn classify_line<'a>(
+        continuation: ContinuationStyle,
+        separator: Option<fn(&[u8]) -> bool>,
+        whitespace_separators: bool,
+        comment_chars: &CommentChars,
+        stray_continuation: StrayContinuation,
+        stop_sentinel: Option<&[u8]>,
+        line: &'a [u8],
+        line_number: u32,
+    ) -> Result<LineKind<'a>, ParseError> {
+        let (clean_line, comment) = Self::split_comment(comment_chars, line);
+        if clean_line.is_empty()
+            && let Some(comment) = comment
+        {
+            return Ok(LineKind::Comment(comment));
+        }
+
+        if whitespace_separators && trim_start(clean_line).is_empty() {
+            return Ok(LineKind::Separator);
+        }
+
+        let clean_line = if continuation.is_continuation(clean_line) {
+            match stray_continuation {
+                StrayContinuation::Error => {
+                    return Err(ParseError::InvalidSyntax {
+                        line_number,
+                        message: "Unexpected continuation line",
+                        line: String::from_utf8_lossy(line).into(),
+                    });
+                }
+                StrayContinuation::Skip => return Ok(LineKind::Skip),
+                StrayContinuation::Attribute => continuation.continuation_value(clean_line, false),
+            }
+        } else {
+            continuation.strip_indent(clean_line)
+        };
+
+        if clean_line.is_empty() || separator.is_some_and(|sep| sep(clean_line)) {
+            return Ok(LineKind::Separator);
+        }
+
+        let Some(colon_pos) = memchr::memchr(b':', clean_line) else {
+            if stop_sentinel.is_some_and(|sentinel| clean_line == sentinel) {
+                return Ok(LineKind::Eof);
+            }
+
+            return Err(ParseError::InvalidSyntax {
+                line_number,
+                message: "Expected an attribute",
+                line: String::from_utf8_lossy(line).into(),
+            });
+        };
+
+        if colon_pos < 1 {
+            return Err(ParseError::InvalidSyntax {
+                line_number,
+                message: "Empty attribute name",
+                line: String::from_utf8_lossy(line).into(),
+            });
+        }
+
+        Ok(LineKind::Attribute(
+            &clean_line[0..colon_pos],
+            &clean_line[colon_pos + 1..],
+            comment,
+        ))
+    }
+
+    /// Whether a (dedented) line continues the current attribute
+    #[inline]
+    fn is_continuation(style: ContinuationStyle, whitespace_separators: bool, line: &[u8]) -> bool {
+        style.is_continuation(line) && !(whitespace_separators && trim_start(line).is_empty())
+    }
+
+    /// Strip up to `indent` leading spaces/tabs from a line. When the indentation
+    /// is not yet known it is taken from the first non-blank line.
+    #[inline]
+    fn dedent_line<'a>(line: &'a [u8], indent: &mut Option<usize>) -> &'a [u8] {
+        match *indent {
+            Some(0) => line,
+            Some(n) => {
+                let leading = line[..n.min(line.len())]
+                    .iter()
+                    .position(|&b| b != b' ' && b != b'\t')
+                    .unwrap_or(n.min(line.len()));
+                &line[leading..]
+            }
+            None => {
+                let leading = line
+                    .iter()
+                    .position(|&b| b != b' ' && b != b'\t')
+                    .unwrap_or(line.len());
+                if leading < line.len() {
+                    *indent = Some(leading);
+                }
+                &line[leading..]
+            }
+        }
+    }
+
+    /// The contents of a line read by `read_line` or `SliceLines`, without its
+    /// terminator
+    #[inline]
+    fn line(buf: &[u8]) -> &[u8] {
+        let n = buf.len();
+        if n >= 2 && buf[n - 2] == b'\r' && buf[n - 1] == b'\n' {
+            &buf[0..n - 2]
+        } else if n >= 1 && (buf[n - 1] == b'\n' || buf[n - 1] == b'\r') {
+            &buf[0..n - 1]
+        } else {
+            buf // EOF without newline
+        }
+    }
+
+    /// Split a line into the part before its comment, and the comment (if any)
+    #[inline]
+    fn split_comment<'a>(
+        comment_chars: &CommentChars,
+        line: &'a [u8],
+    ) -> (&'a [u8], Option<&'a [u8]>) {
+        match comment_chars.find(line) {
+            None => (line, None),
+            Some(n) => (&line[0..n], Some(&line[n..])),
+        }
+    }
+}
+
+/// Range of `inner`, a subslice of `outer`, within `outer`
+#[inline]
+fn subslice_range(outer: &[u8], inner: &[u8]) -> Range<usize> {
+    let start = inner.as_ptr() as usize - outer.as_ptr() as usize;
+    start..start + inner.len()
+}
+
+/// Length of the first line of `data`, including its terminator (`\n`, `\r\n` or a
+/// lone `\r`) if it has one
+#[inline]
+fn first_line_len(data: &[u8]) -> usize {
+    match memchr::memchr2(b'\n', b'\r', data) {
+        // A `\r` ends a line on its own (classic Mac line endings) unless followed
+        // by `\n`
+        Some(n) if data[n] == b'\r' && data.get(n + 1) == Some(&b'\n') => n + 2,
+        Some(n) => n + 1,
+        // EOF without newline
+        None => data.len(),
+    }
+}
+
+/// Read a line, including its terminator (`\n`, `\r\n` or a lone `\r`), into
+/// `buf`. Returns `false` at end of input.
+///
+/// With a maximum line length, reading stops just past it so that an overlong
+/// line is detected without being buffered in full.
+pub(crate) fn read_line<R: BufRead>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max_line_bytes: Option<usize>,
+) -> std::io::Result<bool> {
+    buf.clear();
+    // Leave room for a `\r\n` terminator
+    let max = max_line_bytes.map_or(usize::MAX, |max| max.saturating_add(2));
+    while buf.len() < max {
+        let available = match reader.fill_buf() {
+            Ok(available) => available,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if available.is_empty() {
+            break;
+        }
+        let (taken, terminator) = take_line_part(buf, available, max - buf.len());
+        reader.consume(taken);
+
+        if terminator == Some(b'\r') && buf.len() < max {
+            // The `\n` of a `\r\n` may only arrive with the next refill
+            let next = loop {
+                match reader.fill_buf() {
+                    Ok(available) => break available.first().copied(),
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            };
+            if next == Some(b'\n') {
+                buf.push(b'\n');
+                reader.consume(1);
+            }
+        }
+        if terminator.is_some() {
+            break;
+        }
+    }
+    Ok(!buf.is_empty())
+}
+
+/// Append `available` to `buf` up to and including the first `\n` or `\r`, but
+/// at most `room` bytes. Returns the number of bytes taken and the terminator
+/// found, if any.
+#[inline]
+fn take_line_part(buf: &mut Vec<u8>, available: &[u8], room: usize) -> (usize, Option<u8>) {
+    let available = &available[..available.len().min(room)];
+    match memchr::memchr2(b'\n', b'\r', available) {
+        Some(n) => {
+            buf.extend_from_slice(&available[..=n]);
+            (n + 1, Some(available[n]))
+        }
+        None => {
+            buf.extend_from_slice(available);
+            (available.len(), None)
+        }
+    }
+}
+
+/// Strip leading whitespace
+#[inline]
+fn trim_start(buf: &[u8]) -> &[u8] {
+    match buf.iter().position(|&b| !b.is_ascii_whitespace()) {
+        Some(n) => &buf[n..],
+        None => &buf[buf.len()..],
+    }
+}
+
+/// Whether a line is a YAML document marker (`---`, optionally followed by whitespace)
+pub fn is_yaml_separator(line: &[u8]) -> bool {
+    line.starts_with(b"---") && line[3..].iter().all(|b| b.is_ascii_whitespace())
+}
 
 #[cfg(test)]
 mod tests {
@@ -232,17 +2199,1530 @@ mod tests {
     use std::path::PathBuf;
 
     #[test]
-    fn test_parse_empty() {
-        let input = b"";
+    fn test_parse_empty() {
+        let input = b"";
+        let mut parser = RpslParser::new(Noop);
+        parser.parse(&input[..]).unwrap();
+    }
+
+    #[test]
+    fn test_parse_unbuffered() {
+        let input: &[u8] = b"route: 192.0.2.0/24\norigin: AS65000\n";
+        let mut parser = RpslParser::new(Recorder::default());
+        parser
+            .parse_unbuffered(std::io::Cursor::new(input))
+            .unwrap();
+        assert_eq!(
+            parser.into_callbacks().events,
+            ["start", "route=192.0.2.0/24", "origin=AS65000", "end"]
+        );
+    }
+
+    #[test]
+    fn test_parse_tiny_buffers() {
+        /// Reader that returns one byte per read, so that every buffer refill
+        /// happens at a different point in the input
+        struct OneByte<'a>(&'a [u8]);
+
+        impl Read for OneByte<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let Some((&first, rest)) = self.0.split_first() else {
+                    return Ok(0);
+                };
+                if buf.is_empty() {
+                    return Ok(0);
+                }
+                buf[0] = first;
+                self.0 = rest;
+                Ok(1)
+            }
+        }
+
+        let input = b"remarks: first\n second\n\tthird\n+fourth\n+\n fifth\r\n  sixth\r\n\
+            origin: AS1\n\nroute: 192.0.2.0/24\n x\n\n\n+\n";
+        let new_parser = || {
+            RpslParser::new(Recorder::default()).with_stray_continuation(StrayContinuation::Skip)
+        };
+        let mut parser = new_parser();
+        parser.parse_slice(input).unwrap();
+        let expected = parser.into_callbacks().events;
+        assert_eq!(
+            expected[1],
+            "remarks=first second third fourth  fifth sixth"
+        );
+
+        for capacity in 1..=16 {
+            let mut parser = new_parser();
+            parser
+                .parse(BufReader::with_capacity(capacity, OneByte(input)))
+                .unwrap();
+            assert_eq!(
+                parser.into_callbacks().events,
+                expected,
+                "capacity {capacity}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_line_endings() {
+        let input = "% header\n\nroute: 192.0.2.0/24\ndescr: first\n  second\n+\n\
+            origin: AS1 # comment\n\n\n\
+            aut-num: AS1\nas-name: X\n";
+        let new_parser = || RpslParser::new(Recorder::default());
+
+        let mut parser = new_parser();
+        parser.parse_str(input).unwrap();
+        let expected = parser.into_callbacks().events;
+
+        for ending in ["\r\n", "\r"] {
+            let input = input.replace('\n', ending);
+
+            let mut parser = new_parser();
+            parser.parse_str(&input).unwrap();
+            assert_eq!(parser.into_callbacks().events, expected, "{ending:?}");
+
+            // A tiny buffer splits a `\r\n` across refills
+            for capacity in [1, 2, 3, 8192] {
+                let mut parser = new_parser();
+                parser
+                    .parse(BufReader::with_capacity(capacity, input.as_bytes()))
+                    .unwrap();
+                assert_eq!(
+                    parser.into_callbacks().events,
+                    expected,
+                    "{ending:?}, capacity {capacity}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_with_deadline() {
+        let input = b"route: 192.0.2.0/24\norigin: AS65000\n";
+
+        let mut parser = RpslParser::new(Recorder::default());
+        let result = parser.parse_with_deadline(&input[..], Some(Instant::now()));
+        assert!(matches!(
+            result,
+            Err(ParseError::Timeout { line_number: 0 })
+        ));
+        assert!(parser.into_callbacks().events.is_empty());
+
+        let deadline = Instant::now() + std::time::Duration::from_secs(60);
+        let mut parser = RpslParser::new(Recorder::default());
+        parser
+            .parse_with_deadline(&input[..], Some(deadline))
+            .unwrap();
+        assert_eq!(parser.into_callbacks().events.len(), 4);
+    }
+
+    #[test]
+    fn test_parse_single_object() {
+        let input = b"route: 192.0.2.0/24\norigin: AS65000\n\n";
+        let mut parser = RpslParser::new(Noop);
+        parser.parse(&input[..]).unwrap();
+    }
+
+    struct FailOnName {
+        name: &'static [u8],
+        objects: usize,
+    }
+
+    impl TryCallbacks for FailOnName {
+        type Error = String;
+
+        fn start_object(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn attribute(&mut self, name: &[u8], _value: &[u8]) -> Result<(), String> {
+            if name == self.name {
+                return Err(String::from_utf8_lossy(name).into());
+            }
+            Ok(())
+        }
+
+        fn end_object(&mut self) -> Result<(), String> {
+            self.objects += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_first_error_stops_after_error() {
+        let input = b"route: 192.0.2.0/24\n\nbad: 1\nbad: 2\n\nroute: 198.51.100.0/24\n";
+        let callbacks = FailOnName {
+            name: b"bad",
+            objects: 0,
+        };
+        let mut parser = RpslParser::new(FirstError::new(callbacks));
+        parser.parse(&input[..]).unwrap();
+
+        let first_error = parser.into_callbacks();
+        assert_eq!(first_error.error(), Some(&"bad".to_string()));
+        // The object with `bad` and the route after it are not seen
+        assert_eq!(first_error.inner().objects, 1);
+        assert!(first_error.into_result().is_err());
+    }
+
+    #[test]
+    fn test_first_error_without_error() {
+        let input = b"route: 192.0.2.0/24\n\nroute: 198.51.100.0/24\n";
+        let callbacks = FailOnName {
+            name: b"bad",
+            objects: 0,
+        };
+        let mut parser = RpslParser::new(FirstError::new(callbacks));
+        parser.parse(&input[..]).unwrap();
+
+        let callbacks = parser.into_callbacks().into_result().unwrap();
+        assert_eq!(callbacks.objects, 2);
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Callbacks for Recorder {
+        fn start_object(&mut self) {
+            self.events.push("start".into());
+        }
+
+        fn attribute(&mut self, name: &[u8], value: &[u8]) {
+            self.events.push(format!(
+                "{}={}",
+                String::from_utf8_lossy(name),
+                String::from_utf8_lossy(value)
+            ));
+        }
+
+        fn end_object(&mut self) {
+            self.events.push("end".into());
+        }
+
+        fn side_line(&mut self, line: &[u8]) {
+            self.events
+                .push(format!("side={}", String::from_utf8_lossy(line)));
+        }
+    }
+
+    fn record(mut parser: RpslParser<Recorder>, input: &[u8]) -> Vec<String> {
+        parser.parse(input).unwrap();
+        parser.into_callbacks().events
+    }
+
+    #[test]
+    fn test_parse_continuation() {
+        let input = b"remarks: first\n second\n+third\n\tfourth\norigin: AS1\n";
+        let events = record(RpslParser::new(Recorder::default()), input);
+        assert_eq!(
+            events,
+            [
+                "start",
+                "remarks=first second third fourth",
+                "origin=AS1",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_stray_continuation() {
+        let input = b"+route: 192.0.2.0/24\n  descr: lost class\norigin: AS1\n\naut-num: AS1\n";
+        let parser = |stray| RpslParser::new(Recorder::default()).with_stray_continuation(stray);
+
+        assert!(parser(StrayContinuation::Error).parse(&input[..]).is_err());
+        assert_eq!(
+            record(parser(StrayContinuation::Skip), input),
+            ["start", "origin=AS1", "end", "start", "aut-num=AS1", "end"]
+        );
+        assert_eq!(
+            record(parser(StrayContinuation::Attribute), input),
+            [
+                "start",
+                "route=192.0.2.0/24 descr: lost class",
+                "origin=AS1",
+                "end",
+                "start",
+                "aut-num=AS1",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_skip_errors() {
+        #[derive(Default)]
+        struct SkipErrors {
+            objects: Vec<Vec<String>>,
+            current: Vec<String>,
+            errors: Vec<u32>,
+            discarded: usize,
+        }
+
+        impl Callbacks for SkipErrors {
+            fn start_object(&mut self) {}
+
+            fn attribute(&mut self, name: &[u8], _value: &[u8]) {
+                self.current.push(String::from_utf8_lossy(name).into());
+            }
+
+            fn end_object(&mut self) {
+                self.objects.push(std::mem::take(&mut self.current));
+            }
+
+            fn discard_object(&mut self) {
+                self.current.clear();
+                self.discarded += 1;
+            }
+
+            fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+                if let ParseError::InvalidSyntax { line_number, .. } = err {
+                    self.errors.push(*line_number);
+                }
+                ErrorAction::Skip
+            }
+        }
+
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n\nroute: 198.51.100.0/24\nno colon\n\
+            origin: AS2\n\nstray\nroute: 203.0.113.0/24\n\naut-num: AS3\n\n\
+            route: 192.0.2.128/25\nno colon\n";
+        let expected = [vec!["route", "origin"], vec!["aut-num"]];
+
+        let mut parser = RpslParser::new(SkipErrors::default());
+        parser.parse(&input[..]).unwrap();
+        let callbacks = parser.into_callbacks();
+        assert_eq!(callbacks.objects, expected);
+        assert_eq!(callbacks.errors, [5, 8, 14]);
+        // Errors in objects discard them, even the last one
+        assert_eq!(callbacks.discarded, 2);
+        assert!(callbacks.current.is_empty());
+
+        let mut parser = RpslParser::new(SkipErrors::default());
+        parser.parse_slice(input).unwrap();
+        let callbacks = parser.into_callbacks();
+        assert_eq!(callbacks.objects, expected);
+        assert_eq!(callbacks.errors, [5, 8, 14]);
+        assert_eq!(callbacks.discarded, 2);
+        assert!(callbacks.current.is_empty());
+    }
+
+    #[test]
+    fn test_parse_continuation_join() {
+        let input = b"remarks: first\n  second # comment\n+\n+ third\norigin: AS1\n";
+        let parse = |join| {
+            let mut parser = RpslParser::new(Recorder::default()).with_continuation_join(join);
+            parser.parse(&input[..]).unwrap();
+            let events = parser.into_callbacks().events;
+
+            let mut parser = RpslParser::new(Recorder::default()).with_continuation_join(join);
+            parser.parse_slice(input).unwrap();
+            assert_eq!(parser.into_callbacks().events, events);
+            events
+        };
+
+        assert_eq!(
+            parse(ContinuationJoin::Space),
+            ["start", "remarks=first second   third", "origin=AS1", "end"]
+        );
+        assert_eq!(
+            parse(ContinuationJoin::Newline),
+            [
+                "start",
+                "remarks=first\nsecond \n\nthird",
+                "origin=AS1",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_utf8_validation() {
+        let single = b"route: 192.0.2.0/24\ndescr: caf\xff\n";
+        let multi = b"route: 192.0.2.0/24\ndescr: cafe\n  \xff\n";
+
+        for input in [&single[..], &multi[..]] {
+            assert!(RpslParser::new(Noop).parse(input).is_ok());
+            assert!(RpslParser::new(Noop).parse_slice(input).is_ok());
+
+            let mut parser = RpslParser::new(Noop).with_utf8_validation(true);
+            assert!(matches!(
+                parser.parse(input),
+                Err(ParseError::InvalidUtf8 {
+                    line_number: 2,
+                    field: "value"
+                })
+            ));
+            let mut parser = RpslParser::new(Noop).with_utf8_validation(true);
+            assert!(matches!(
+                parser.parse_slice(input),
+                Err(ParseError::InvalidUtf8 {
+                    line_number: 2,
+                    field: "value"
+                })
+            ));
+        }
+
+        let mut parser = RpslParser::new(Noop).with_utf8_validation(true);
+        assert!(matches!(
+            parser.parse(&b"r\xffoute: 192.0.2.0/24\n"[..]),
+            Err(ParseError::InvalidUtf8 {
+                line_number: 1,
+                field: "name"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        fn parse(
+            configure: fn(RpslParser<Noop>) -> RpslParser<Noop>,
+            input: &[u8],
+        ) -> Option<(u32, &'static str)> {
+            let limit = |result: Result<(), ParseError>| match result {
+                Ok(()) => None,
+                Err(ParseError::LimitExceeded { line_number, limit }) => Some((line_number, limit)),
+                Err(err) => panic!("{err}"),
+            };
+            let streamed = limit(configure(RpslParser::new(Noop)).parse(input));
+            let sliced = limit(configure(RpslParser::new(Noop)).parse_slice(input));
+            assert_eq!(streamed, sliced);
+            streamed
+        }
+
+        let input = b"aut-num: AS1\r\nremarks: a\n+ bbbb\n+ cccccccccccccccc\n";
+        assert_eq!(parse(|p| p, input), None);
+        assert_eq!(
+            parse(|p| p.with_max_line_bytes(12), input),
+            Some((4, "max_line_bytes"))
+        );
+        assert_eq!(parse(|p| p.with_max_line_bytes(18), input), None);
+        assert_eq!(
+            parse(|p| p.with_max_attribute_bytes(8), input),
+            Some((4, "max_attribute_bytes"))
+        );
+        assert_eq!(
+            parse(|p| p.with_max_attribute_bytes(2), input),
+            Some((1, "max_attribute_bytes"))
+        );
+
+        let input = b"aut-num: AS1\nas-name: X\n\naut-num: AS2\nas-name: Y\nremarks: z\n";
+        assert_eq!(parse(|p| p.with_max_attributes_per_object(3), input), None);
+        assert_eq!(
+            parse(|p| p.with_max_attributes_per_object(2), input),
+            Some((6, "max_attributes_per_object"))
+        );
+    }
+
+    #[test]
+    fn test_parse_object_class() {
+        #[derive(Default)]
+        struct Classes(Vec<String>);
+
+        impl Callbacks for Classes {
+            fn start_object(&mut self) {
+                unreachable!("the parser passes the class");
+            }
+
+            fn start_object_class(&mut self, class: &[u8]) {
+                self.0.push(String::from_utf8_lossy(class).into_owned());
+            }
+
+            fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+
+            fn end_object(&mut self) {}
+        }
+
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n\naut-num: AS1\n";
+
+        let mut parser = RpslParser::new(Classes::default());
+        parser.parse(&input[..]).unwrap();
+        assert_eq!(parser.into_callbacks().0, ["route", "aut-num"]);
+
+        let mut parser = RpslParser::new(Classes::default());
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().0, ["route", "aut-num"]);
+    }
+
+    #[test]
+    fn test_parse_normalize_names() {
+        let input = b"Route: 192.0.2.0/24\nOrigin: AS1\nORIGIN: AS2\norigin: As3\n";
+
+        let parser = RpslParser::new(Recorder::default());
+        assert_eq!(
+            record(parser, input),
+            [
+                "start",
+                "Route=192.0.2.0/24",
+                "Origin=AS1",
+                "ORIGIN=AS2",
+                "origin=As3",
+                "end"
+            ]
+        );
+
+        let parser = RpslParser::new(Recorder::default()).with_normalize_names(true);
+        assert_eq!(
+            record(parser, input),
+            [
+                "start",
+                "route=192.0.2.0/24",
+                "origin=AS1",
+                "origin=AS2",
+                "origin=As3",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_trim_values() {
+        let input = b"route: 192.0.2.0/24\t\norigin: AS1   \nremarks: a  b \n  c # comment\n+\n\n";
+        let parse = |trim| {
+            let mut parser = RpslParser::new(Recorder::default()).with_trim_values(trim);
+            parser.parse(&input[..]).unwrap();
+            let events = parser.into_callbacks().events;
+
+            let mut parser = RpslParser::new(Recorder::default()).with_trim_values(trim);
+            parser.parse_slice(input).unwrap();
+            assert_eq!(parser.into_callbacks().events, events);
+            events
+        };
+
+        assert_eq!(
+            parse(false),
+            [
+                "start",
+                "route=192.0.2.0/24\t",
+                "origin=AS1   ",
+                "remarks=a  b  c  ",
+                "end"
+            ]
+        );
+        assert_eq!(
+            parse(true),
+            [
+                "start",
+                "route=192.0.2.0/24",
+                "origin=AS1",
+                "remarks=a  b c",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_stop_sentinel() {
+        let input = b"route: 192.0.2.0/24\n\nEOF\nroute: 198.51.100.0/24\n";
+        assert_eq!(
+            record(RpslParser::new(Recorder::default()), input),
+            ["start", "route=192.0.2.0/24", "end"]
+        );
+
+        let parser = RpslParser::new(Recorder::default()).with_stop_sentinel(Some(b"END".to_vec()));
+        assert_eq!(
+            record(
+                parser,
+                b"route: 192.0.2.0/24\n\nEND\nroute: 198.51.100.0/24\n"
+            ),
+            ["start", "route=192.0.2.0/24", "end"]
+        );
+
+        let mut parser = RpslParser::new(Noop).with_stop_sentinel(None);
+        assert!(matches!(
+            parser.parse(&input[..]),
+            Err(ParseError::InvalidSyntax { line_number: 3, .. })
+        ));
+    }
+
+    #[derive(Default)]
+    struct LineRecorder {
+        lines: Vec<(u32, u32, String)>,
+    }
+
+    impl Callbacks for LineRecorder {
+        fn start_object(&mut self) {}
+
+        fn attribute(&mut self, _name: &[u8], _value: &[u8]) {
+            unreachable!("the parser reports attributes with their line number")
+        }
+
+        fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], _value: &[u8]) {
+            self.lines.push((
+                span.line_number,
+                span.continuation_lines,
+                String::from_utf8_lossy(name).into(),
+            ));
+        }
+
+        fn end_object(&mut self) {}
+    }
+
+    #[test]
+    fn test_parse_attribute_line_numbers() {
+        let input = b"% comment
+remarks: first
+ second
+origin: AS1
+
+route: 192.0.2.0/24
+";
+        let mut parser = RpslParser::new(LineRecorder::default());
+        parser.parse(&input[..]).unwrap();
+        assert_eq!(
+            parser.into_callbacks().lines,
+            [
+                (2, 1, "remarks".to_string()),
+                (4, 0, "origin".to_string()),
+                (6, 0, "route".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_object_line_ranges() {
+        /// Records `(line, name)` pairs and each object's first and last line
+        #[derive(Default)]
+        struct ObjectLines {
+            attributes: Vec<(u32, String)>,
+            objects: Vec<(u32, u32)>,
+            lines: Option<(u32, u32)>,
+        }
+
+        impl Callbacks for ObjectLines {
+            fn start_object(&mut self) {
+                self.lines = None;
+            }
+
+            fn attribute(&mut self, _name: &[u8], _value: &[u8]) {
+                unreachable!("the parser reports attributes with their line number")
+            }
+
+            fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], _value: &[u8]) {
+                self.attributes
+                    .push((span.line_number, String::from_utf8_lossy(name).into()));
+                let last = span.line_number + span.continuation_lines;
+                let (first, _) = self.lines.unwrap_or((span.line_number, last));
+                self.lines = Some((first, last));
+            }
+
+            fn end_object(&mut self) {
+                self.objects.extend(self.lines.take());
+            }
+        }
+
+        let input = b"% header
+route: 192.0.2.0/24
+descr: first
+ second
+
+
+aut-num: AS1
+remarks: a
++
+\tb
+";
+        let mut parser = RpslParser::new(ObjectLines::default());
+        parser.parse(&input[..]).unwrap();
+        let recorded = parser.into_callbacks();
+        assert_eq!(
+            recorded.attributes,
+            [
+                (2, "route".to_string()),
+                (3, "descr".to_string()),
+                (7, "aut-num".to_string()),
+                (8, "remarks".to_string())
+            ]
+        );
+        assert_eq!(recorded.objects, [(2, 4), (7, 10)]);
+    }
+
+    #[test]
+    fn test_parse_attribute_is_continued() {
+        struct Continued(Vec<bool>);
+
+        impl Callbacks for Continued {
+            fn start_object(&mut self) {}
+            fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+            fn end_object(&mut self) {}
+
+            fn attribute_at(&mut self, span: AttributeSpan, _name: &[u8], _value: &[u8]) {
+                self.0.push(span.is_continued());
+            }
+        }
+
+        let input = b"remarks: first\n second\norigin: AS1\ndescr: x\n+\n\nroute: 192.0.2.0/24";
+        let expected = [true, false, true, false];
+
+        let mut parser = RpslParser::new(Continued(Vec::new()));
+        parser.parse(&input[..]).unwrap();
+        assert_eq!(parser.into_callbacks().0, expected);
+
+        let mut parser = RpslParser::new(Continued(Vec::new()));
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().0, expected);
+    }
+
+    #[test]
+    fn test_parse_attribute_byte_spans() {
+        struct SpanRecorder(Vec<(u64, u64)>);
+
+        impl Callbacks for SpanRecorder {
+            fn start_object(&mut self) {}
+            fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+            fn end_object(&mut self) {}
+
+            fn attribute_at(&mut self, span: AttributeSpan, _name: &[u8], _value: &[u8]) {
+                self.0.push((span.byte_offset, span.byte_len));
+            }
+        }
+
+        let input = b"% comment\nroute: 192.0.2.0/24\r\nremarks: a # c\n b\n+\n\norigin: AS1";
+        let expected = [(10, 21), (31, 20), (52, 11)];
+        let texts = [
+            &b"route: 192.0.2.0/24\r\n"[..],
+            b"remarks: a # c\n b\n+\n",
+            b"origin: AS1",
+        ];
+        let slices: Vec<_> = expected
+            .iter()
+            .map(|&(offset, len)| &input[offset as usize..(offset + len) as usize])
+            .collect();
+        assert_eq!(slices, texts);
+
+        let mut parser = RpslParser::new(SpanRecorder(Vec::new()));
+        parser.parse(&input[..]).unwrap();
+        assert_eq!(parser.into_callbacks().0, expected);
+
+        let mut parser = RpslParser::new(SpanRecorder(Vec::new()));
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().0, expected);
+
+        // A line put back at a class boundary is only counted once
+        let input = b"route: 192.0.2.0/24\nroute: 198.51.100.0/24\n";
+        let mut parser = RpslParser::new(SpanRecorder(Vec::new())).with_boundary_on_class_change();
+        parser.parse(&input[..]).unwrap();
+        assert_eq!(parser.into_callbacks().0, [(0, 20), (20, 23)]);
+    }
+
+    #[test]
+    fn test_parse_slice_matches_parse() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"route: 192.0.2.0/24\norigin: AS65000\n\n\n",
+            b"remarks: first\r\n second # comment\r\n+third\r\n\r\norigin: AS1",
+            b"% header\n\nroute: 192.0.2.0/24\n  \nEOF\nroute: 198.51.100.0/24\n",
+            b"descr: a\n\tb % comment\n\n\n\nmnt-by: X\nmnt-by: Y\r",
+        ];
+
+        for input in inputs {
+            let mut parser = RpslParser::new(Recorder::default());
+            parser.parse_slice(input).unwrap();
+            assert_eq!(
+                parser.into_callbacks().events,
+                record(RpslParser::new(Recorder::default()), input),
+                "{}",
+                String::from_utf8_lossy(input)
+            );
+        }
+
+        let input = b"    route: 192.0.2.0/24\n      folded\n\n    origin: AS1\n";
+        let mut parser = RpslParser::new(Recorder::default()).with_dedent(Dedent::Auto);
+        parser.parse_slice(input).unwrap();
+        assert_eq!(
+            parser.into_callbacks().events,
+            record(
+                RpslParser::new(Recorder::default()).with_dedent(Dedent::Auto),
+                input
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_entry_points_agree() {
+        #[derive(Default)]
+        struct EventRecorder(Vec<String>);
+
+        impl Callbacks for EventRecorder {
+            fn start_object(&mut self) {
+                self.0.push("start".into());
+            }
+
+            fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+
+            fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+                self.0.push(format!(
+                    "{span:?} {}={}",
+                    String::from_utf8_lossy(name),
+                    String::from_utf8_lossy(value)
+                ));
+            }
+
+            fn end_object(&mut self) {
+                self.0.push("end".into());
+            }
+
+            fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+                self.0.push(format!(
+                    "{line_number} {}:{}",
+                    String::from_utf8_lossy(name.unwrap_or_default()),
+                    String::from_utf8_lossy(comment)
+                ));
+            }
+
+            fn side_line(&mut self, line: &[u8]) {
+                self.0
+                    .push(format!("side={}", String::from_utf8_lossy(line)));
+            }
+        }
+
+        let input = b"% header\n\nroute: 192.0.2.0/24 # first\r\ndescr: a\n\tb % folded\n+c\n\
+            origin: AS1\n\n\n! side\n% between\naut-num: AS1\r\nremarks:\n  last";
+        let parser = || RpslParser::new(EventRecorder::default()).with_side_lines(b'!');
+
+        let mut parsed = parser();
+        parsed.parse(&input[..]).unwrap();
+        let parsed = parsed.into_callbacks().0;
+
+        let mut sliced = parser();
+        sliced.parse_slice(input).unwrap();
+
+        let mut objects = parser();
+        let mut reader = &input[..];
+        while objects.parse_object(&mut reader).unwrap() {}
+
+        assert!(parsed.len() > 10, "{parsed:?}");
+        assert_eq!(sliced.into_callbacks().0, parsed);
+        assert_eq!(objects.into_callbacks().0, parsed);
+    }
+
+    #[test]
+    fn test_parse_slice_line_numbers() {
+        let input = b"% comment\nremarks: first\n second\norigin: AS1\n\nroute: 192.0.2.0/24\n";
+        let mut parser = RpslParser::new(LineRecorder::default());
+        parser.parse_slice(input).unwrap();
+        assert_eq!(
+            parser.into_callbacks().lines,
+            [
+                (2, 1, "remarks".to_string()),
+                (4, 0, "origin".to_string()),
+                (6, 0, "route".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_boundary_on_class_change() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\nRoute: 198.51.100.0/24\n\
+            origin: AS2\n\nroute: 203.0.113.0/24\n";
+        let expected = [
+            "start",
+            "route=192.0.2.0/24",
+            "origin=AS1",
+            "end",
+            "start",
+            "Route=198.51.100.0/24",
+            "origin=AS2",
+            "end",
+            "start",
+            "route=203.0.113.0/24",
+            "end",
+        ];
+
+        let parser = RpslParser::new(Recorder::default()).with_boundary_on_class_change();
+        assert_eq!(record(parser, input), expected);
+
+        let mut parser = RpslParser::new(Recorder::default()).with_boundary_on_class_change();
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().events, expected);
+    }
+
+    #[test]
+    fn test_parse_boundary_on_classes() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\naut-num: AS1\nroute: 198.51.100.0/24\n";
+        let parser =
+            RpslParser::new(Recorder::default()).with_boundary_on_classes(&["route", "aut-num"]);
+        assert_eq!(
+            record(parser, input),
+            [
+                "start",
+                "route=192.0.2.0/24",
+                "origin=AS1",
+                "end",
+                "start",
+                "aut-num=AS1",
+                "end",
+                "start",
+                "route=198.51.100.0/24",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_require_known_class() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n\nRoute6: 2001:db8::/32\n";
+        let parser = RpslParser::new(Recorder::default()).require_known_class(&["route", "route6"]);
+        assert_eq!(record(parser, input).len(), 7);
+
+        let input = b"% header\norigin: AS1\n\nroute: 192.0.2.0/24\n";
+        let mut parser = RpslParser::new(Noop).require_known_class(&["route"]);
+        assert!(matches!(
+            parser.parse(&input[..]),
+            Err(ParseError::InvalidSyntax { line_number: 2, .. })
+        ));
+        assert!(matches!(
+            parser.parse_slice(input),
+            Err(ParseError::InvalidSyntax { line_number: 2, .. })
+        ));
+
+        let mut parser = RpslParser::new(Noop);
+        assert!(parser.parse(&input[..]).is_ok());
+    }
+
+    #[test]
+    fn test_parse_side_lines() {
+        let input = b"{\"status\": \"ok\"}\nroute: 192.0.2.0/24\n\n{\"seq\": 2}\norigin: AS1\n";
+        let expected = [
+            "side={\"status\": \"ok\"}",
+            "start",
+            "route=192.0.2.0/24",
+            "end",
+            "side={\"seq\": 2}",
+            "start",
+            "origin=AS1",
+            "end",
+        ];
+
+        let parser = RpslParser::new(Recorder::default()).with_side_lines(b'{');
+        assert_eq!(record(parser, input), expected);
+
+        let mut parser = RpslParser::new(Recorder::default()).with_side_lines(b'{');
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().events, expected);
+
         let mut parser = RpslParser::new(Noop);
+        assert!(matches!(
+            parser.parse(&b"{}\nroute: 192.0.2.0/24\n"[..]),
+            Err(ParseError::InvalidSyntax { line_number: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_max_continuation_lines() {
+        let input = b"remarks: a\n b\n c\n d\norigin: AS1\n\nremarks: e\n f\n";
+        let expected = [
+            "start",
+            "remarks=a b",
+            "origin=AS1",
+            "end",
+            "start",
+            "remarks=e f",
+            "end",
+        ];
+
+        let mut parser = RpslParser::new(Recorder::default()).with_max_continuation_lines(1);
         parser.parse(&input[..]).unwrap();
+        assert_eq!(parser.truncated_attributes(), 1);
+        assert_eq!(parser.into_callbacks().events, expected);
+
+        let mut parser = RpslParser::new(Recorder::default()).with_max_continuation_lines(1);
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.truncated_attributes(), 1);
+        assert_eq!(parser.into_callbacks().events, expected);
     }
 
     #[test]
-    fn test_parse_single_object() {
-        let input = b"route: 192.0.2.0/24\norigin: AS65000\n\n";
+    fn test_parse_raw_line() {
+        #[derive(Default)]
+        struct RawLines(Vec<(AttributeSpan, Vec<u8>)>);
+
+        impl Callbacks for RawLines {
+            fn start_object(&mut self) {}
+            fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+            fn attribute_at(&mut self, span: AttributeSpan, _name: &[u8], _value: &[u8]) {
+                self.0.push((span, Vec::new()));
+            }
+            fn end_object(&mut self) {}
+            fn raw_line(&mut self, _name: &[u8], line: &[u8]) {
+                self.0.last_mut().unwrap().1 = line.to_vec();
+            }
+        }
+
+        let input = b"% header\n\nremarks:   first  # note\r\n  second\n+\n\tthird # x\n\
+            origin: AS1\n\n\naut-num: AS1 # y\nas-name: X\n continued";
+        let expected: [&[u8]; 4] = [
+            b"remarks:   first  # note\r\n  second\n+\n\tthird # x\n",
+            b"origin: AS1\n",
+            b"aut-num: AS1 # y\n",
+            b"as-name: X\n continued",
+        ];
+
+        let mut parser = RpslParser::new(RawLines::default());
+        parser.parse(&input[..]).unwrap();
+        let streamed = std::mem::take(&mut parser.callbacks_mut().0);
+        parser.parse_slice(input).unwrap();
+        let sliced = parser.into_callbacks().0;
+
+        assert_eq!(streamed, sliced);
+        assert_eq!(streamed.len(), expected.len());
+        for ((span, line), expected) in streamed.iter().zip(expected) {
+            assert_eq!(line, expected);
+            let start = span.byte_offset as usize;
+            assert_eq!(&input[start..start + span.byte_len as usize], expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_reset() {
+        let first = b"route: 192.0.2.0/24\n\naut-num: AS1\n";
+        let second = b"origin: AS2\n x\n";
+        let mut parser = RpslParser::new(Recorder::default()).with_max_continuation_lines(0);
+
+        // Stop after the first object, with the next line already read ahead
+        let mut reader = &first[..];
+        assert!(parser.parse_object(&mut reader).unwrap());
+        parser.reset();
+        let mut reader = &second[..];
+        while parser.parse_object(&mut reader).unwrap() {}
+        assert_eq!(parser.truncated_attributes(), 1);
+
+        parser.reset().events.push("reset".into());
+        parser.parse(&first[..]).unwrap();
+        parser.parse_slice(second).unwrap();
+        assert_eq!(
+            parser.into_callbacks().events,
+            [
+                "start",
+                "route=192.0.2.0/24",
+                "end",
+                "start",
+                "origin=AS2",
+                "end",
+                "reset",
+                "start",
+                "route=192.0.2.0/24",
+                "end",
+                "start",
+                "aut-num=AS1",
+                "end",
+                "start",
+                "origin=AS2",
+                "end",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_best_effort() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n  \nroute: 198.51.100.0/24\n\
+            route: 203.0.113.0/24\n \t\n";
+        let expected = [
+            "start",
+            "route=192.0.2.0/24",
+            "origin=AS1",
+            "end",
+            "start",
+            "route=198.51.100.0/24",
+            "end",
+            "start",
+            "route=203.0.113.0/24",
+            "end",
+        ];
+
+        assert_eq!(
+            record(RpslParser::new(Recorder::default()).best_effort(), input),
+            expected
+        );
+
+        let mut parser = RpslParser::new(Recorder::default()).best_effort();
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().events, expected);
+
+        let mut parser = RpslParser::new(Noop);
+        assert!(parser.parse(&b" \nroute: 192.0.2.0/24\n"[..]).is_err());
+    }
+
+    /// Recorder that also records comments
+    #[derive(Default)]
+    struct CommentRecorder(Recorder);
+
+    impl Callbacks for CommentRecorder {
+        fn start_object(&mut self) {
+            self.0.start_object();
+        }
+
+        fn attribute(&mut self, name: &[u8], value: &[u8]) {
+            self.0.attribute(name, value);
+        }
+
+        fn end_object(&mut self) {
+            self.0.end_object();
+        }
+
+        fn comment(&mut self, comment: &[u8]) {
+            self.0
+                .events
+                .push(format!("comment={}", String::from_utf8_lossy(comment)));
+        }
+    }
+
+    #[test]
+    fn test_parse_dyn() {
+        let input = b"route: 192.0.2.0/24\n\naut-num: AS1\n";
+
+        let mut recorder = Recorder::default();
+        let callbacks: &mut dyn Callbacks = &mut recorder;
+        parse_dyn(&input[..], callbacks).unwrap();
+        assert_eq!(recorder.events.len(), 6);
+
+        let mut parser: RpslParser<Box<dyn Callbacks>> = RpslParser::new(Box::new(Noop));
+        parser.parse(&input[..]).unwrap();
+    }
+
+    #[test]
+    fn test_parse_sub_attributes() {
+        struct SubRecorder(Recorder);
+
+        impl Callbacks for SubRecorder {
+            fn start_object(&mut self) {}
+            fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+            fn end_object(&mut self) {}
+
+            fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+                self.0.events.push(format!(
+                    "{}.{}={}",
+                    String::from_utf8_lossy(parent),
+                    String::from_utf8_lossy(key),
+                    String::from_utf8_lossy(value)
+                ));
+            }
+        }
+
+        let input = b"route: 192.0.2.0/24\nremarks: contact: noc@example.net\n\
+            REMARKS: no sub-field\nremarks:\n  ticket = 42\ndescr: a: b\n";
+
+        let mut parser =
+            RpslParser::new(SubRecorder(Recorder::default())).with_sub_attributes(&["remarks"]);
+        parser.parse(&input[..]).unwrap();
+        assert_eq!(
+            parser.callbacks_mut().0.events,
+            ["remarks.contact=noc@example.net"]
+        );
+
+        let mut parser = RpslParser::new(SubRecorder(Recorder::default()))
+            .with_sub_attributes(&["remarks"])
+            .with_sub_attribute_separator(b'=');
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.callbacks_mut().0.events, ["remarks.ticket=42"]);
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let input = b"% header\nroute: 192.0.2.0/24 # trailing\ndescr: a\n b # continued\n\
+            # full line\n\norigin: AS1\n";
+        let expected = [
+            "comment=% header",
+            "start",
+            "comment=# trailing",
+            "route=192.0.2.0/24 ",
+            "comment=# continued",
+            "descr=a b ",
+            "comment=# full line",
+            "end",
+            "start",
+            "origin=AS1",
+            "end",
+        ];
+
+        let mut parser = RpslParser::new(CommentRecorder::default());
+        parser.parse(&input[..]).unwrap();
+        assert_eq!(parser.into_callbacks().0.events, expected);
+
+        let mut parser = RpslParser::new(CommentRecorder::default());
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().0.events, expected);
+    }
+
+    /// A `key-cert` object with a PEM certificate (and a `+` blank line) in
+    /// `certif:`, in the RIPE 16 column layout
+    const KEY_CERT: &[u8] = br"key-cert:       X509-1
+method:         X509
+certif:
+                -----BEGIN CERTIFICATE-----
+                MIIBgDCCASegAwIBAgIUXlit9jdCt2R1cZ3+h46IubYB6h8wCgYIKoZIzj0EAwIw
+                FjEUMBIGA1UEAwwLZXhhbXBsZS5uZXQwHhcNMjYxMDE2MDMxNDQyWhcNMzYxMDEz
+                MDMxNDQyWjAWMRQwEgYDVQQDDAtleGFtcGxlLm5ldDBZMBMGByqGSM49AgEGCCqG
+                SM49AwEHA0IABKB7xOLFnlyIij8JPPcqEwZc6TAPbaGMG58YwIRvCqZ4hmSX9UMa
++
+                H18ZHXyFf5nedB/OwH2xeicd1C2aU8sAcQejUzBRMB0GA1UdDgQWBBSgKSOVXkqo
+                4508ePVYGXuTNOM/EDAfBgNVHSMEGDAWgBSgKSOVXkqo4508ePVYGXuTNOM/EDAP
+                BgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0cAMEQCIHGxzrvsO7jG8S4Mykmt
+                6RYtk5JVy6nfBLLRMeZa/pq2AiBCsUhVWwkyXjlRg5eQmvr5WNHDeaVxPXxUxwYE
+                XtThSg==
+                -----END CERTIFICATE-----
+remarks:        first
+                second
+";
+
+    #[test]
+    fn test_parse_verbatim_attributes() {
+        let pem = &KEY_CERT[KEY_CERT.windows(5).position(|w| w == b"-----").unwrap()..];
+        let expected: Vec<u8> = pem
+            .split(|&b| b == b'\n')
+            .take_while(|line| !line.starts_with(b"remarks"))
+            .map(|line| trim_start(line.strip_prefix(b"+").unwrap_or(line)))
+            .collect::<Vec<_>>()
+            .join(&b'\n');
+        assert!(expected.ends_with(b"-----END CERTIFICATE-----"));
+
+        let mut parser = RpslParser::new(Recorder::default());
+        parser.parse(KEY_CERT).unwrap();
+        let events = parser.into_callbacks().events;
+        let certif = format!("certif={}", String::from_utf8_lossy(&expected));
+        assert_eq!(events[3], certif);
+        assert_eq!(events[4], "remarks=first second");
+
+        let mut parser = RpslParser::new(Recorder::default());
+        parser.parse_slice(KEY_CERT).unwrap();
+        assert_eq!(parser.into_callbacks().events[3], certif);
+
+        // Writing the folded value back gives the same value when parsed again
+        let mut writer = RpslWriter::new(Vec::new()).with_value_column(16);
+        writer
+            .write_object(&[(b"certif", expected.as_slice())])
+            .unwrap();
+        let mut parser = RpslParser::new(Recorder::default());
+        parser.parse(writer.into_inner().as_slice()).unwrap();
+        assert_eq!(parser.into_callbacks().events[1], certif);
+
+        let mut parser = RpslParser::new(Recorder::default()).with_verbatim_attributes(&[]);
+        parser.parse(KEY_CERT).unwrap();
+        assert!(!parser.into_callbacks().events[3].contains('\n'));
+    }
+
+    #[test]
+    fn test_parse_attribute_comments() {
+        struct AttributeCommentRecorder(Vec<String>);
+
+        impl Callbacks for AttributeCommentRecorder {
+            fn start_object(&mut self) {}
+            fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+            fn end_object(&mut self) {}
+
+            fn comment(&mut self, comment: &[u8]) {
+                self.0.push(String::from_utf8_lossy(comment).into_owned());
+            }
+
+            fn attribute_comment(&mut self, name: &[u8], comment: &[u8]) {
+                self.0.push(format!(
+                    "{}: {}",
+                    String::from_utf8_lossy(name),
+                    String::from_utf8_lossy(comment)
+                ));
+            }
+        }
+
+        let input = b"route: 192.0.2.0/24 # first\nremarks: a # second\n b # third\n\
+            +c % fourth\n# full line\n";
+        let expected = [
+            "route: # first",
+            "remarks: # second",
+            "remarks: # third",
+            "remarks: % fourth",
+            "# full line",
+        ];
+
+        let mut parser = RpslParser::new(AttributeCommentRecorder(Vec::new()));
+        parser.parse(&input[..]).unwrap();
+        assert_eq!(parser.into_callbacks().0, expected);
+
+        let mut parser = RpslParser::new(AttributeCommentRecorder(Vec::new()));
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().0, expected);
+    }
+
+    #[test]
+    fn test_parse_comment_at() {
+        struct CommentAtRecorder(Vec<String>);
+
+        impl Callbacks for CommentAtRecorder {
+            fn start_object(&mut self) {}
+            fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+            fn end_object(&mut self) {}
+
+            fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+                self.0.push(format!(
+                    "{line_number} {}:{}",
+                    String::from_utf8_lossy(name.unwrap_or_default()),
+                    String::from_utf8_lossy(comment)
+                ));
+            }
+        }
+
+        let input = b"% TODO header\n\nroute: 192.0.2.0/24 #first\nremarks: a\n b % TODO fix\n\
+            # full line\n%\n";
+        let expected = [
+            "1 :% TODO header",
+            "3 route:#first",
+            "5 remarks:% TODO fix",
+            "6 :# full line",
+            "7 :%",
+        ];
+
+        let mut parser = RpslParser::new(CommentAtRecorder(Vec::new()));
+        parser.parse(&input[..]).unwrap();
+        assert_eq!(parser.into_callbacks().0, expected);
+
+        let mut parser = RpslParser::new(CommentAtRecorder(Vec::new()));
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().0, expected);
+    }
+
+    #[test]
+    fn test_parse_value_on_continuation_lines() {
+        let input =
+            b"route: 192.0.2.0/24\ndescr:\n    first\n    second\nremarks:\n+ third\norigin: AS1\n";
+        let expected = [
+            "start",
+            "route=192.0.2.0/24",
+            "descr=first second",
+            "remarks=third",
+            "origin=AS1",
+            "end",
+        ];
+
+        assert_eq!(
+            record(RpslParser::new(Recorder::default()), input),
+            expected
+        );
+
+        let mut parser = RpslParser::new(Recorder::default());
+        parser.parse_slice(input).unwrap();
+        assert_eq!(parser.into_callbacks().events, expected);
+    }
+
+    #[test]
+    fn test_parse_continuation_at_eof() {
+        // Truncated input ending in a continuation line, with or without a newline
+        for (input, descr) in [
+            (
+                &b"route: 192.0.2.0/24\ndescr: first\n second\n"[..],
+                "descr=first second",
+            ),
+            (
+                b"route: 192.0.2.0/24\ndescr: first\n second",
+                "descr=first second",
+            ),
+            (b"route: 192.0.2.0/24\ndescr:\n second", "descr=second"),
+        ] {
+            let expected = ["start", "route=192.0.2.0/24", descr, "end"];
+            assert_eq!(
+                record(RpslParser::new(Recorder::default()), input),
+                expected
+            );
+
+            let mut parser = RpslParser::new(Recorder::default());
+            parser.parse_slice(input).unwrap();
+            assert_eq!(parser.into_callbacks().events, expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_comment_chars() {
+        let input = b"; header\nroute: 192.0.2.0/24 ; note\ndescr: 50% # not a comment\n";
+        let mut parser = RpslParser::new(Recorder::default()).with_comment_chars(b";");
+        parser.parse_slice(input).unwrap();
+        assert_eq!(
+            parser.into_callbacks().events,
+            [
+                "start",
+                "route=192.0.2.0/24 ",
+                "descr=50% # not a comment",
+                "end"
+            ]
+        );
+
+        let input = b"remarks: 100% up # note\n";
+        let parse = |chars: &[u8]| {
+            let mut parser = RpslParser::new(Recorder::default()).with_comment_chars(chars);
+            parser.parse(&input[..]).unwrap();
+            parser.into_callbacks().events
+        };
+        assert_eq!(parse(b"%#"), ["start", "remarks=100", "end"]);
+        assert_eq!(parse(b"#"), ["start", "remarks=100% up ", "end"]);
+        assert_eq!(parse(b""), ["start", "remarks=100% up # note", "end"]);
+
+        let chars = CommentChars::new(b"%#;!");
+        assert_eq!(chars.find(b"a: b ! c ; d"), Some(5));
+        assert_eq!(CommentChars::new(b"").find(b"a: b # c"), None);
+    }
+
+    #[test]
+    fn test_parse_continuation_prefixes() {
+        let input = b"remarks: a\n+b\n+ c\n+\n   d\n\te\n";
+
+        let events = record(RpslParser::new(Recorder::default()), input);
+        assert_eq!(events, ["start", "remarks=a b c  d e", "end"]);
+
+        let mut parser = RpslParser::new(Recorder::default()).with_space_after_plus(true);
+        parser.parse_slice(input).unwrap();
+        assert_eq!(
+            parser.into_callbacks().events,
+            ["start", "remarks=a b  c  d e", "end"]
+        );
+    }
+
+    #[test]
+    fn test_parse_whitespace_only_value() {
+        let input = b"route: 192.0.2.0/24\ndescr:    \ndescr:\n";
+        let events = record(RpslParser::new(Recorder::default()), input);
+        assert_eq!(
+            events,
+            ["start", "route=192.0.2.0/24", "descr=", "descr=", "end"]
+        );
+    }
+
+    #[test]
+    fn test_parse_indented_without_dedent() {
+        let input = b"    route: 192.0.2.0/24\n";
+        let mut parser = RpslParser::new(Noop);
+        assert!(matches!(
+            parser.parse(&input[..]),
+            Err(ParseError::InvalidSyntax { line_number: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_dedent_auto() {
+        let input = b"    route: 192.0.2.0/24\n    descr: first\n      second\n    origin: AS1\n\n    route: 198.51.100.0/24\n";
+        let parser = RpslParser::new(Recorder::default()).with_dedent(Dedent::Auto);
+        assert_eq!(
+            record(parser, input),
+            [
+                "start",
+                "route=192.0.2.0/24",
+                "descr=first second",
+                "origin=AS1",
+                "end",
+                "start",
+                "route=198.51.100.0/24",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dedent_fixed() {
+        let input = b"  route: 192.0.2.0/24\n  origin: AS1\n  \n  route: 198.51.100.0/24\n";
+        let parser = RpslParser::new(Recorder::default()).with_dedent(Dedent::Fixed(2));
+        assert_eq!(
+            record(parser, input),
+            [
+                "start",
+                "route=192.0.2.0/24",
+                "origin=AS1",
+                "end",
+                "start",
+                "route=198.51.100.0/24",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_yaml_separators() {
+        let input = b"---\nroute: 192.0.2.0/24\norigin: AS1\n--- \nroute: 198.51.100.0/24\n---\n";
+        let parser = RpslParser::new(Recorder::default()).with_yaml_separators();
+        assert_eq!(
+            record(parser, input),
+            [
+                "start",
+                "route=192.0.2.0/24",
+                "origin=AS1",
+                "end",
+                "start",
+                "route=198.51.100.0/24",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_yaml_separator_disabled_by_default() {
+        let input = b"route: 192.0.2.0/24\n---\n";
         let mut parser = RpslParser::new(Noop);
+        assert!(matches!(
+            parser.parse(&input[..]),
+            Err(ParseError::InvalidSyntax { line_number: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_tee() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n";
+        let mut parser = RpslParser::new(Tee::new(Recorder::default(), Recorder::default()));
         parser.parse(&input[..]).unwrap();
+
+        let (first, second) = parser.into_callbacks().into_inner();
+        assert_eq!(
+            first.events,
+            ["start", "route=192.0.2.0/24", "origin=AS1", "end"]
+        );
+        assert_eq!(first.events, second.events);
+    }
+
+    #[test]
+    fn test_parse_object_incrementally() {
+        let input = b"route: 192.0.2.0/24\n\n\n% comment\nroute: 198.51.100.0/24\norigin: AS1\n";
+        let mut reader = &input[..];
+        let mut parser = RpslParser::new(Recorder::default());
+
+        assert!(parser.parse_object(&mut reader).unwrap());
+        assert_eq!(
+            parser.callbacks_mut().events,
+            ["start", "route=192.0.2.0/24", "end"]
+        );
+
+        parser.callbacks_mut().events.clear();
+        assert!(parser.parse_object(&mut reader).unwrap());
+        assert_eq!(
+            parser.callbacks_mut().events,
+            ["start", "route=198.51.100.0/24", "origin=AS1", "end"]
+        );
+
+        assert!(!parser.parse_object(&mut reader).unwrap());
+        assert!(!parser.parse_object(&mut reader).unwrap());
+    }
+
+    #[test]
+    fn test_parse_column_aligned_continuation() {
+        let input = b"descr:    first\n          second\n  remarks: indented\nsource:   TEST\n";
+        let parser = RpslParser::new(Recorder::default())
+            .with_continuation_style(ContinuationStyle::ColumnAligned(10));
+        assert_eq!(
+            record(parser, input),
+            [
+                "start",
+                "descr=first second",
+                "remarks=indented",
+                "source=TEST",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_column_aligned_unexpected_continuation() {
+        let input = b"          descr: first\n";
+        let mut parser =
+            RpslParser::new(Noop).with_continuation_style(ContinuationStyle::ColumnAligned(10));
+        assert!(matches!(
+            parser.parse(&input[..]),
+            Err(ParseError::InvalidSyntax { line_number: 1, .. })
+        ));
     }
 
     fn fixtures_dir() -> PathBuf {
@@ -286,7 +3766,7 @@ mod tests {
                 fs::File::open(&path).unwrap_or_else(|_| panic!("Failed to open {:?}", path));
 
             let result = if path.extension().map(|s| s == "gz").unwrap_or(false) {
-                let reader = BufReader::new(flate2::read::GzDecoder::new(file));
+                let reader = BufReader::new(flate2::read::MultiGzDecoder::new(file));
                 let mut parser = RpslParser::new(Noop);
                 parser.parse(reader)
             } else {
@@ -0,0 +1,128 @@
+use std::io::BufRead;
+use std::time::SystemTime;
+
+use crate::modified::parse_last_modified;
+use crate::{Callbacks, ParseError, RpslParser};
+
+/// Information about a database dump, taken from the comments before its first
+/// object
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DumpMetadata {
+    /// Serial number of the dump, e.g. for NRTM mirroring
+    pub serial: Option<u64>,
+
+    /// When the dump was generated
+    pub generated: Option<SystemTime>,
+
+    /// Name of the registry the dump comes from
+    pub source: Option<String>,
+}
+
+impl DumpMetadata {
+    /// Take what a header comment says, keeping values already found. Recognizes
+    /// `serial: 12345` (or `serial 12345`), `generated: 2024-01-01T00:00:00Z`,
+    /// `source: RIPE` and NRTM-style `START Version: 3 RIPE 100-200` lines, with
+    /// case-insensitive keys.
+    fn read_comment(&mut self, comment: &str) {
+        let text = comment.trim_start_matches(['%', '#']).trim();
+
+        let lower = text.to_ascii_lowercase();
+        if let Some(rest) = lower.strip_prefix("start version:") {
+            // Version, source and serial range, e.g. `3 RIPE 100-200`
+            let rest = &text[text.len() - rest.len()..];
+            let mut fields = rest.split_whitespace().skip(1);
+            if let Some(source) = fields.next() {
+                self.source.get_or_insert_with(|| source.to_string());
+            }
+            if let Some(serial) = fields.next().and_then(|range| range.rsplit('-').next()) {
+                self.serial = self.serial.or(serial.parse().ok());
+            }
+            return;
+        }
+
+        let Some((key, value)) = text
+            .split_once(':')
+            .or_else(|| text.split_once(char::is_whitespace))
+        else {
+            return;
+        };
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "serial" | "current serial" | "serial number" => {
+                let serial = value.split_whitespace().next().and_then(|s| s.parse().ok());
+                self.serial = self.serial.or(serial);
+            }
+            "generated" | "generated at" | "created" | "timestamp" => {
+                self.generated = self.generated.or(parse_last_modified(value.as_bytes()));
+            }
+            "source" if !value.is_empty() => {
+                self.source.get_or_insert_with(|| value.to_string());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Callbacks collecting [`DumpMetadata`] from the comments before the first object
+struct MetadataCollector {
+    metadata: DumpMetadata,
+    in_header: bool,
+}
+
+impl Callbacks for MetadataCollector {
+    fn start_object(&mut self) {
+        self.in_header = false;
+    }
+
+    fn attribute(&mut self, _name: &[u8], _value: &[u8]) {}
+
+    fn end_object(&mut self) {}
+
+    fn comment(&mut self, comment: &[u8]) {
+        if self.in_header {
+            self.metadata
+                .read_comment(&String::from_utf8_lossy(comment));
+        }
+    }
+}
+
+/// Read the [`DumpMetadata`] of a dump from its header, i.e. the comments before
+/// the first object. Reading stops after the first object.
+pub fn read_dump_metadata<R: BufRead>(mut reader: R) -> Result<DumpMetadata, ParseError> {
+    let mut parser = RpslParser::new(MetadataCollector {
+        metadata: DumpMetadata::default(),
+        in_header: true,
+    });
+    parser.parse_object(&mut reader)?;
+    Ok(parser.into_callbacks().metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_read_dump_metadata() {
+        let input = b"% RIPE database dump\n% Serial: 12345\n\
+            # Generated: 2020-03-01T01:02:03Z\n% source: RIPE\n\n\
+            route: 192.0.2.0/24\n% serial: 99999\n";
+
+        let metadata = read_dump_metadata(&input[..]).unwrap();
+        assert_eq!(
+            metadata,
+            DumpMetadata {
+                serial: Some(12345),
+                generated: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_583_024_523)),
+                source: Some("RIPE".to_string()),
+            }
+        );
+
+        let metadata = read_dump_metadata(&b"%START Version: 3 RADB 100-200\n"[..]).unwrap();
+        assert_eq!(metadata.serial, Some(200));
+        assert_eq!(metadata.source.as_deref(), Some("RADB"));
+
+        let metadata = read_dump_metadata(&b"# serial 42\n"[..]).unwrap();
+        assert_eq!(metadata.serial, Some(42));
+    }
+}
@@ -0,0 +1,344 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use thiserror::Error;
+
+use crate::RpslObject;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ModelError {
+    #[error("Expected a '{expected}' object, got '{class}'")]
+    WrongClass {
+        expected: &'static str,
+        class: String,
+    },
+
+    #[error("Invalid value for attribute '{attribute}': {value}")]
+    InvalidValue {
+        attribute: &'static str,
+        value: String,
+    },
+}
+
+/// An inclusive range of IP addresses of one family
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpRange {
+    start: IpAddr,
+    end: IpAddr,
+}
+
+impl IpRange {
+    /// Create a range, returning `None` if the addresses are of different families
+    /// or `start` is after `end`
+    pub fn new(start: IpAddr, end: IpAddr) -> Option<Self> {
+        let valid = match (start, end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => start <= end,
+            (IpAddr::V6(start), IpAddr::V6(end)) => start <= end,
+            _ => false,
+        };
+        valid.then_some(Self { start, end })
+    }
+
+    pub fn start(&self) -> IpAddr {
+        self.start
+    }
+
+    pub fn end(&self) -> IpAddr {
+        self.end
+    }
+
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.start, self.end, addr) {
+            (IpAddr::V4(start), IpAddr::V4(end), IpAddr::V4(addr)) => start <= addr && addr <= end,
+            (IpAddr::V6(start), IpAddr::V6(end), IpAddr::V6(addr)) => start <= addr && addr <= end,
+            _ => false,
+        }
+    }
+
+    /// Whether `other` lies entirely within this range
+    pub fn contains_range(&self, other: &IpRange) -> bool {
+        self.contains(other.start) && self.contains(other.end)
+    }
+
+    /// The prefix length if the range is exactly one CIDR prefix
+    pub fn prefix_len(&self) -> Option<u8> {
+        let (start, end, bits) = match (self.start, self.end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => {
+                (u32::from(start) as u128, u32::from(end) as u128, 32)
+            }
+            (IpAddr::V6(start), IpAddr::V6(end)) => (u128::from(start), u128::from(end), 128),
+            _ => return None,
+        };
+
+        // A prefix has a size that is a power of two, and starts at a multiple of it
+        let host_mask = end - start;
+        if host_mask & host_mask.wrapping_add(1) != 0 || start & host_mask != 0 {
+            return None;
+        }
+        Some((bits - host_mask.count_ones()) as u8)
+    }
+
+    /// Parse a `start - end` range or an `address/length` prefix. IPv4 prefixes may
+    /// omit trailing zero octets, as in `192.0.2/24`.
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Some((start, end)) = value.split_once('-') {
+            let start: IpAddr = start.trim().parse().ok()?;
+            let end: IpAddr = end.trim().parse().ok()?;
+            return Self::new(start, end);
+        }
+
+        let (addr, len) = value.split_once('/')?;
+        let (addr, len) = (addr.trim(), len.trim().parse::<u8>().ok()?);
+
+        if addr.contains(':') {
+            let addr = u128::from(addr.parse::<Ipv6Addr>().ok()?);
+            let mask = u128::MAX
+                .checked_shl(128_u32.checked_sub(len.into())?)
+                .unwrap_or(0);
+            Self::new(
+                IpAddr::V6(Ipv6Addr::from(addr & mask)),
+                IpAddr::V6(Ipv6Addr::from(addr | !mask)),
+            )
+        } else {
+            let addr = u32::from(parse_ipv4_abbreviated(addr)?);
+            let mask = u32::MAX
+                .checked_shl(32_u32.checked_sub(len.into())?)
+                .unwrap_or(0);
+            Self::new(
+                IpAddr::V4(Ipv4Addr::from(addr & mask)),
+                IpAddr::V4(Ipv4Addr::from(addr | !mask)),
+            )
+        }
+    }
+}
+
+/// Parse an IPv4 address that may omit trailing zero octets
+fn parse_ipv4_abbreviated(value: &str) -> Option<Ipv4Addr> {
+    let mut octets = [0u8; 4];
+    let mut parts = value.split('.');
+    for octet in &mut octets {
+        match parts.next() {
+            Some(part) => *octet = part.parse().ok()?,
+            None => break,
+        }
+    }
+    parts.next().is_none().then_some(Ipv4Addr::from(octets))
+}
+
+/// The registry an object comes from, as named by its `source` attribute
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Source {
+    Afrinic,
+    Apnic,
+    Arin,
+    Lacnic,
+    Ripe,
+    Radb,
+    Altdb,
+    Nttcom,
+
+    /// Any other registry, with the value as given
+    Other(String),
+}
+
+impl Source {
+    /// The registry name as used in `source` attributes
+    pub fn as_str(&self) -> &str {
+        match self {
+            Source::Afrinic => "AFRINIC",
+            Source::Apnic => "APNIC",
+            Source::Arin => "ARIN",
+            Source::Lacnic => "LACNIC",
+            Source::Ripe => "RIPE",
+            Source::Radb => "RADB",
+            Source::Altdb => "ALTDB",
+            Source::Nttcom => "NTTCOM",
+            Source::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for Source {
+    /// Parse a `source` value, matching known registries case-insensitively
+    fn from(value: &str) -> Self {
+        let value = value.trim();
+        [
+            Source::Afrinic,
+            Source::Apnic,
+            Source::Arin,
+            Source::Lacnic,
+            Source::Ripe,
+            Source::Radb,
+            Source::Altdb,
+            Source::Nttcom,
+        ]
+        .into_iter()
+        .find(|source| source.as_str().eq_ignore_ascii_case(value))
+        .unwrap_or_else(|| Source::Other(value.to_string()))
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A typed `inetnum` (IPv4 address block) object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inetnum {
+    pub range: IpRange,
+    pub netname: Option<String>,
+    pub country: Vec<String>,
+    pub status: Option<String>,
+    pub source: Option<Source>,
+}
+
+/// A typed `inet6num` (IPv6 address block) object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inet6num {
+    pub range: IpRange,
+    pub netname: Option<String>,
+    pub country: Vec<String>,
+    pub status: Option<String>,
+    pub source: Option<Source>,
+}
+
+/// Parse the range attribute of an `inetnum`/`inet6num` object of the given family
+fn address_block(
+    object: &RpslObject,
+    class: &'static str,
+    v4: bool,
+) -> Result<IpRange, ModelError> {
+    match object.class() {
+        Some(c) if c.eq_ignore_ascii_case(class) => {}
+        c => {
+            return Err(ModelError::WrongClass {
+                expected: class,
+                class: c.unwrap_or_default().to_string(),
+            });
+        }
+    }
+
+    let value = object.key().unwrap_or_default();
+    IpRange::parse(value)
+        .filter(|range| range.start.is_ipv4() == v4)
+        .ok_or_else(|| ModelError::InvalidValue {
+            attribute: class,
+            value: value.to_string(),
+        })
+}
+
+impl TryFrom<&RpslObject> for Inetnum {
+    type Error = ModelError;
+
+    fn try_from(object: &RpslObject) -> Result<Self, ModelError> {
+        Ok(Self {
+            range: address_block(object, "inetnum", true)?,
+            netname: object.get("netname").map(str::to_string),
+            country: object.get_all("country").map(str::to_string).collect(),
+            status: object.get("status").map(str::to_string),
+            source: object.get("source").map(Source::from),
+        })
+    }
+}
+
+impl TryFrom<&RpslObject> for Inet6num {
+    type Error = ModelError;
+
+    fn try_from(object: &RpslObject) -> Result<Self, ModelError> {
+        Ok(Self {
+            range: address_block(object, "inet6num", false)?,
+            netname: object.get("netname").map(str::to_string),
+            country: object.get_all("country").map(str::to_string).collect(),
+            status: object.get("status").map(str::to_string),
+            source: object.get("source").map(Source::from),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(attributes: &[(&str, &str)]) -> RpslObject {
+        RpslObject {
+            attributes: attributes
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_ip_range_parse() {
+        let range = IpRange::parse("192.0.2.0 - 192.0.2.255").unwrap();
+        assert_eq!(range, IpRange::parse("192.0.2.0/24").unwrap());
+        assert_eq!(range, IpRange::parse("192.0.2/24").unwrap());
+        assert_eq!(range, IpRange::parse("192.0.2.17/24").unwrap());
+        assert_eq!(range.prefix_len(), Some(24));
+        assert!(range.contains("192.0.2.42".parse().unwrap()));
+        assert!(!range.contains("192.0.3.0".parse().unwrap()));
+        assert!(!range.contains("::1".parse().unwrap()));
+
+        let range = IpRange::parse("192.0.2.0 - 192.0.2.4").unwrap();
+        assert_eq!(range.prefix_len(), None);
+        assert_eq!(IpRange::parse("0.0.0.0/0").unwrap().prefix_len(), Some(0));
+        assert_eq!(IpRange::parse("::/0").unwrap().prefix_len(), Some(0));
+
+        let range = IpRange::parse("2001:db8::/32").unwrap();
+        assert_eq!(
+            range.end(),
+            "2001:db8:ffff:ffff:ffff:ffff:ffff:ffff"
+                .parse::<IpAddr>()
+                .unwrap()
+        );
+        assert_eq!(range.prefix_len(), Some(32));
+
+        assert_eq!(IpRange::parse("192.0.2.255 - 192.0.2.0"), None);
+        assert_eq!(IpRange::parse("192.0.2.0 - 2001:db8::"), None);
+        assert_eq!(IpRange::parse("192.0.2.0/33"), None);
+    }
+
+    #[test]
+    fn test_source() {
+        assert_eq!(Source::from("ripe"), Source::Ripe);
+        assert_eq!(Source::from(" RADB "), Source::Radb);
+        assert_eq!(
+            Source::from("RIPE-NONAUTH"),
+            Source::Other("RIPE-NONAUTH".to_string())
+        );
+        assert_eq!(Source::from("Apnic").to_string(), "APNIC");
+    }
+
+    #[test]
+    fn test_inetnum() {
+        let inetnum = Inetnum::try_from(&object(&[
+            ("inetnum", "192.0.2.0 - 192.0.2.255"),
+            ("netname", "EXAMPLE-NET"),
+            ("country", "NL"),
+            ("country", "DE"),
+            ("source", "RIPE"),
+        ]))
+        .unwrap();
+        assert_eq!(inetnum.range, IpRange::parse("192.0.2.0/24").unwrap());
+        assert_eq!(inetnum.netname.as_deref(), Some("EXAMPLE-NET"));
+        assert_eq!(inetnum.country, ["NL", "DE"]);
+        assert_eq!(inetnum.source, Some(Source::Ripe));
+
+        let inet6num = Inet6num::try_from(&object(&[("inet6num", "2001:db8::/48")])).unwrap();
+        assert!(inet6num.range.contains("2001:db8::1".parse().unwrap()));
+
+        assert_eq!(
+            Inetnum::try_from(&object(&[("inetnum", "2001:db8::/48")])),
+            Err(ModelError::InvalidValue {
+                attribute: "inetnum",
+                value: "2001:db8::/48".to_string()
+            })
+        );
+        assert!(matches!(
+            Inet6num::try_from(&object(&[("route", "192.0.2.0/24")])),
+            Err(ModelError::WrongClass { .. })
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+use std::time::{Duration, SystemTime};
+
+use crate::buffer::ObjectBuffer;
+use crate::{AttributeSpan, Callbacks, ErrorAction, ParseError};
+
+/// Callbacks decorator that only forwards objects modified at or after a cutoff
+///
+/// The modification time of an object is taken from its `last-modified` attribute
+/// (`2020-01-01T12:00:00Z`), or else from the latest date of its `changed`
+/// attributes (`hostmaster@example.net 20200101`). Each object is buffered until
+/// it ends, then forwarded as a whole or dropped, along with the comments
+/// preceding it and within it.
+pub struct ModifiedSince<C> {
+    inner: C,
+    cutoff: SystemTime,
+    keep_undated: bool,
+    object: ObjectBuffer,
+    last_modified: Option<SystemTime>,
+    changed: Option<SystemTime>,
+}
+
+impl<C: Callbacks> ModifiedSince<C> {
+    pub fn new(inner: C, cutoff: SystemTime) -> Self {
+        Self {
+            inner,
+            cutoff,
+            keep_undated: true,
+            object: ObjectBuffer::default(),
+            last_modified: None,
+            changed: None,
+        }
+    }
+
+    /// Whether to forward objects without a parseable modification time. Defaults
+    /// to `true`.
+    pub fn with_keep_undated(mut self, keep_undated: bool) -> Self {
+        self.keep_undated = keep_undated;
+        self
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Callbacks> Callbacks for ModifiedSince<C> {
+    fn start_object(&mut self) {
+        self.object.start();
+        self.last_modified = None;
+        self.changed = None;
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.attribute_at(AttributeSpan::default(), name, value);
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        if name.eq_ignore_ascii_case(b"last-modified") {
+            self.last_modified = self.last_modified.or(parse_last_modified(value));
+        } else if name.eq_ignore_ascii_case(b"changed")
+            && let Some(changed) = parse_changed(value)
+        {
+            self.changed = self.changed.max(Some(changed));
+        }
+        self.object.attribute(span, name, value);
+    }
+
+    fn end_object(&mut self) {
+        let keep = match self.last_modified.or(self.changed) {
+            Some(modified) => modified >= self.cutoff,
+            None => self.keep_undated,
+        };
+        if keep {
+            self.object.forward(&mut self.inner);
+        }
+        self.object.clear();
+    }
+
+    fn discard_object(&mut self) {
+        // Nothing of the object has been forwarded yet
+        self.object.clear();
+    }
+
+    fn side_line(&mut self, line: &[u8]) {
+        self.inner.side_line(line);
+    }
+
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        self.object.comment(line_number, name, comment);
+    }
+
+    fn raw_line(&mut self, _name: &[u8], line: &[u8]) {
+        self.object.raw_line(line);
+    }
+
+    fn sub_attribute(&mut self, _parent: &[u8], key: &[u8], value: &[u8]) {
+        self.object.sub_attribute(key, value);
+    }
+
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        self.inner.on_error(err)
+    }
+}
+
+/// Parse a `last-modified` value such as `2020-01-01T12:00:00Z`
+pub(crate) fn parse_last_modified(value: &[u8]) -> Option<SystemTime> {
+    let value = std::str::from_utf8(value).ok()?.trim();
+    let (date, time) = value.split_once('T')?;
+    let time = time.strip_suffix('Z')?;
+
+    let mut date = date.splitn(3, '-').map(str::parse::<u32>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let mut time = time.splitn(3, ':').map(str::parse::<u32>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let seconds = u64::from(hour * 3600 + minute * 60 + second);
+    Some(date_to_time(year, month, day)? + Duration::from_secs(seconds))
+}
+
+/// Parse the date of a `changed` value such as `hostmaster@example.net 20200101`
+fn parse_changed(value: &[u8]) -> Option<SystemTime> {
+    let date = std::str::from_utf8(value).ok()?.split_whitespace().last()?;
+    if date.len() != 8 || !date.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let year = date[0..4].parse().ok()?;
+    let month = date[4..6].parse().ok()?;
+    let day = date[6..8].parse().ok()?;
+    date_to_time(year, month, day)
+}
+
+/// Midnight UTC of a date, in the years 1970 to 9999
+fn date_to_time(year: u32, month: u32, day: u32) -> Option<SystemTime> {
+    if !(1970..=9999).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // Days since the epoch in the proleptic Gregorian calendar, counting years
+    // from March so that the leap day is at the end
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = u64::from(era * 146_097 + day_of_era) - 719_468;
+
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(days * 86_400))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FirstError, RpslParser, RpslWriter};
+
+    #[test]
+    fn test_parse_timestamps() {
+        let epoch = SystemTime::UNIX_EPOCH;
+        assert_eq!(
+            parse_last_modified(b"2020-03-01T01:02:03Z"),
+            Some(epoch + Duration::from_secs(1_583_024_523))
+        );
+        assert_eq!(
+            parse_changed(b"hostmaster@example.net 20000229"),
+            Some(epoch + Duration::from_secs(951_782_400))
+        );
+        assert_eq!(parse_last_modified(b"2020-03-01"), None);
+        assert_eq!(parse_changed(b"hostmaster@example.net"), None);
+        assert_eq!(parse_last_modified(b"99999999-01-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn test_modified_since() {
+        let input = b"route: 192.0.2.0/24\nlast-modified: 2019-12-31T23:59:59Z\n\n\
+            route: 198.51.100.0/24\nlast-modified: 2020-01-01T00:00:00Z\n\n\
+            route: 203.0.113.0/24\nchanged: a@example.net 20191231\nchanged: a@example.net 20200102\n\n\
+            route: 10.0.0.0/8\n";
+        let cutoff = parse_last_modified(b"2020-01-01T00:00:00Z").unwrap();
+
+        let filter = |keep_undated| {
+            let writer = FirstError::new(RpslWriter::new(Vec::new()));
+            let callbacks = ModifiedSince::new(writer, cutoff).with_keep_undated(keep_undated);
+            let mut parser = RpslParser::new(callbacks);
+            parser.parse(&input[..]).unwrap();
+            let writer = parser.into_callbacks().into_inner().into_result().unwrap();
+            String::from_utf8(writer.into_inner()).unwrap()
+        };
+
+        assert_eq!(
+            filter(true),
+            "route: 198.51.100.0/24\nlast-modified: 2020-01-01T00:00:00Z\n\n\
+             route: 203.0.113.0/24\nchanged: a@example.net 20191231\nchanged: a@example.net 20200102\n\n\
+             route: 10.0.0.0/8\n"
+        );
+        assert!(!filter(false).contains("10.0.0.0/8"));
+    }
+
+    #[test]
+    fn test_modified_since_comments() {
+        #[derive(Default)]
+        struct Recorder(Vec<String>);
+
+        impl Callbacks for Recorder {
+            fn start_object(&mut self) {
+                self.0.push("start".into());
+            }
+
+            fn attribute(&mut self, name: &[u8], _value: &[u8]) {
+                self.0.push(String::from_utf8_lossy(name).into_owned());
+            }
+
+            fn end_object(&mut self) {
+                self.0.push("end".into());
+            }
+
+            fn comment(&mut self, comment: &[u8]) {
+                self.0.push(String::from_utf8_lossy(comment).into_owned());
+            }
+        }
+
+        let input =
+            b"% about A\nroute: 192.0.2.0/24 # first\nlast-modified: 2020-01-01T00:00:00Z\n\n\
+            % about B\nroute: 198.51.100.0/24\nlast-modified: 2019-01-01T00:00:00Z # trailing\n\
+            % inside B\n";
+        let cutoff = parse_last_modified(b"2020-01-01T00:00:00Z").unwrap();
+        let mut parser = RpslParser::new(ModifiedSince::new(Recorder::default(), cutoff));
+        parser.parse(&input[..]).unwrap();
+
+        assert_eq!(
+            parser.into_callbacks().into_inner().0,
+            [
+                "% about A",
+                "start",
+                "# first",
+                "route",
+                "last-modified",
+                "end"
+            ]
+        );
+    }
+}
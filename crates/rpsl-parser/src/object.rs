@@ -0,0 +1,242 @@
+use std::io::BufRead;
+
+use crate::{Callbacks, Noop, ParseError, RpslParser};
+
+/// An owned RPSL object: its attributes as `(name, value)` pairs, in input order
+/// and including duplicates
+///
+/// With the `serde` feature this serializes as
+/// `{"class": "route", "attributes": [["route", "192.0.2.0/24"], ["origin", "AS1"]]}`,
+/// keeping attributes as an array of pairs so that duplicates survive a round trip.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "SerdeObject", try_from = "SerdeObject")
+)]
+pub struct RpslObject {
+    pub attributes: Vec<(String, String)>,
+}
+
+/// Serialized form of an [`RpslObject`], with the class spelled out
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerdeObject {
+    #[serde(default)]
+    class: Option<String>,
+    attributes: Vec<(String, String)>,
+}
+
+#[cfg(feature = "serde")]
+impl From<RpslObject> for SerdeObject {
+    fn from(object: RpslObject) -> Self {
+        Self {
+            class: object.class().map(str::to_string),
+            attributes: object.attributes,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SerdeObject> for RpslObject {
+    type Error = String;
+
+    fn try_from(object: SerdeObject) -> Result<Self, String> {
+        let object_class = object.attributes.first().map(|(name, _)| name.as_str());
+        match object.class {
+            Some(class) if Some(class.as_str()) != object_class => Err(format!(
+                "class `{class}` does not match the first attribute `{}`",
+                object_class.unwrap_or_default()
+            )),
+            _ => Ok(Self {
+                attributes: object.attributes,
+            }),
+        }
+    }
+}
+
+impl RpslObject {
+    /// The object class, i.e. the name of the first attribute
+    pub fn class(&self) -> Option<&str> {
+        self.attributes.first().map(|(name, _)| name.as_str())
+    }
+
+    /// The primary key, i.e. the value of the first attribute
+    pub fn key(&self) -> Option<&str> {
+        self.attributes.first().map(|(_, value)| value.as_str())
+    }
+
+    /// The first value of an attribute (matched case-insensitively)
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// All values of an attribute (matched case-insensitively), in order
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.attributes
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Callbacks keeping the attributes of the object parsed last
+#[derive(Default)]
+pub(crate) struct ObjectCollector(RpslObject);
+
+impl Callbacks for ObjectCollector {
+    fn start_object(&mut self) {
+        self.0.attributes.clear();
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.0.attributes.push((
+            String::from_utf8_lossy(name).into_owned(),
+            String::from_utf8_lossy(value).into_owned(),
+        ));
+    }
+
+    fn end_object(&mut self) {}
+}
+
+/// Iterator over the objects of a reader, see [`RpslParser::objects`]
+///
+/// Objects are parsed one at a time as the iterator advances, so stopping early
+/// (with `take`, `break` or `?`) leaves the rest of the input unread. A parse error
+/// is yielded as an `Err` item, after which the iterator ends.
+pub struct RpslObjects<R> {
+    reader: R,
+    parser: RpslParser<ObjectCollector>,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for RpslObjects<R> {
+    type Item = Result<RpslObject, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.parser.parse_object(&mut self.reader) {
+            Ok(true) => Some(Ok(std::mem::take(&mut self.parser.callbacks_mut().0))),
+            Ok(false) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<C: Callbacks> RpslParser<C> {
+    /// Iterate over the objects of `reader` as [`RpslObject`]s, parsing with this
+    /// parser's options. The parser's callbacks are not called.
+    pub fn objects<R: BufRead>(self, reader: R) -> RpslObjects<R> {
+        RpslObjects {
+            reader,
+            parser: self.replace_callbacks(ObjectCollector::default()),
+            done: false,
+        }
+    }
+}
+
+/// Iterate over the objects of `reader` with default parser options, see
+/// [`RpslParser::objects`]
+pub fn rpsl_objects<R: BufRead>(reader: R) -> RpslObjects<R> {
+    RpslParser::new(Noop).objects(reader)
+}
+
+/// Parse every object of `reader` into a vector, with default parser options
+///
+/// This holds the whole input in memory as owned strings, which takes several
+/// times the size of the input; use [`rpsl_objects`] to process large files one
+/// object at a time.
+pub fn parse_into_vec<R: BufRead>(reader: R) -> Result<Vec<RpslObject>, ParseError> {
+    rpsl_objects(reader).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_objects() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n\nroute: 198.51.100.0/24\norigin: AS2\n";
+        let objects: Vec<_> = rpsl_objects(&input[..]).collect::<Result<_, _>>().unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[1].key(), Some("198.51.100.0/24"));
+        assert_eq!(objects[1].get("origin"), Some("AS2"));
+
+        let first = rpsl_objects(&input[..]).next().unwrap().unwrap();
+        assert_eq!(first.get("origin"), Some("AS1"));
+
+        // Parser options apply, and an error ends the iteration
+        let input = b"remarks: a # b\n\n+stray\n\norigin: AS3\n";
+        let mut objects = RpslParser::new(Noop)
+            .with_comment_chars(b";")
+            .objects(&input[..]);
+        assert_eq!(
+            objects.next().unwrap().unwrap().get("remarks"),
+            Some("a # b")
+        );
+        assert!(objects.next().unwrap().is_err());
+        assert!(objects.next().is_none());
+    }
+    #[test]
+    fn test_parse_into_vec() {
+        // The last object ends at the end of input, without a blank line
+        let input = b"route: 192.0.2.0/24\nmnt-by: A\nmnt-by: B\n\naut-num: AS1";
+        let pair = |name: &str, value: &str| (name.to_string(), value.to_string());
+        assert_eq!(
+            parse_into_vec(&input[..]).unwrap(),
+            [
+                RpslObject {
+                    attributes: vec![
+                        pair("route", "192.0.2.0/24"),
+                        pair("mnt-by", "A"),
+                        pair("mnt-by", "B"),
+                    ],
+                },
+                RpslObject {
+                    attributes: vec![pair("aut-num", "AS1")],
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let input = b"route: 192.0.2.0/24\nmnt-by: A\nmnt-by: B\norigin: AS1\n";
+        let object = rpsl_objects(&input[..]).next().unwrap().unwrap();
+
+        let json = serde_json::to_string(&object).unwrap();
+        assert_eq!(
+            json,
+            r#"{"class":"route","attributes":[["route","192.0.2.0/24"],["mnt-by","A"],["mnt-by","B"],["origin","AS1"]]}"#
+        );
+        assert_eq!(serde_json::from_str::<RpslObject>(&json).unwrap(), object);
+
+        // The class may be left out, but must match the first attribute if given
+        let json = r#"{"attributes":[["aut-num","AS1"]]}"#;
+        let object = serde_json::from_str::<RpslObject>(json).unwrap();
+        assert_eq!(object.class(), Some("aut-num"));
+        let json = r#"{"class":"route","attributes":[["aut-num","AS1"]]}"#;
+        assert!(serde_json::from_str::<RpslObject>(json).is_err());
+
+        // An empty object has no class
+        let json = serde_json::to_string(&RpslObject::default()).unwrap();
+        assert_eq!(json, r#"{"class":null,"attributes":[]}"#);
+        assert_eq!(
+            serde_json::from_str::<RpslObject>(&json).unwrap(),
+            RpslObject::default()
+        );
+    }
+}
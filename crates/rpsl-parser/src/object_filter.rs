@@ -0,0 +1,166 @@
+use crate::buffer::ObjectBuffer;
+use crate::{AttributeSpan, Callbacks, ErrorAction, ParseError};
+
+/// Callbacks decorator that only forwards objects accepted by a predicate over
+/// their attributes
+///
+/// Each object is buffered until it ends, then the predicate is called with its
+/// `(name, value)` pairs in input order and the object is forwarded as a whole or
+/// dropped, along with the comments preceding it and within it. Buffering copies
+/// every attribute once, so this is slower than [`ClassFilter`](crate::ClassFilter)
+/// or [`ModifiedSince`](crate::ModifiedSince) for what they cover. The slices
+/// borrow the decorator's buffer and are only valid during the call; copy out
+/// whatever needs to outlive it.
+pub struct ObjectFilter<C, F> {
+    inner: C,
+    predicate: F,
+    object: ObjectBuffer,
+}
+
+impl<C: Callbacks, F: Fn(&[(&[u8], &[u8])]) -> bool> ObjectFilter<C, F> {
+    pub fn new(inner: C, predicate: F) -> Self {
+        Self {
+            inner,
+            predicate,
+            object: ObjectBuffer::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Callbacks, F: Fn(&[(&[u8], &[u8])]) -> bool> Callbacks for ObjectFilter<C, F> {
+    fn start_object(&mut self) {
+        self.object.start();
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.attribute_at(AttributeSpan::default(), name, value);
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        self.object.attribute(span, name, value);
+    }
+
+    fn end_object(&mut self) {
+        let attributes: Vec<(&[u8], &[u8])> = self
+            .object
+            .attributes()
+            .iter()
+            .map(|(_, name, value)| (name.as_slice(), value.as_slice()))
+            .collect();
+        if (self.predicate)(&attributes) {
+            self.object.forward(&mut self.inner);
+        }
+        self.object.clear();
+    }
+
+    fn discard_object(&mut self) {
+        // Nothing of the object has been forwarded yet
+        self.object.clear();
+    }
+
+    fn side_line(&mut self, line: &[u8]) {
+        self.inner.side_line(line);
+    }
+
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        self.object.comment(line_number, name, comment);
+    }
+
+    fn raw_line(&mut self, _name: &[u8], line: &[u8]) {
+        self.object.raw_line(line);
+    }
+
+    fn sub_attribute(&mut self, _parent: &[u8], key: &[u8], value: &[u8]) {
+        self.object.sub_attribute(key, value);
+    }
+
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        self.inner.on_error(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FirstError, RpslParser, RpslWriter};
+
+    #[test]
+    fn test_object_filter() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\nmnt-by: A-MNT\n\n\
+            route: 198.51.100.0/24\norigin: AS2\nmnt-by: B-MNT\n\n\
+            aut-num: AS1\nmnt-by: B-MNT\n";
+
+        let writer = FirstError::new(RpslWriter::new(Vec::new()));
+        let callbacks = ObjectFilter::new(writer, |attributes: &[(&[u8], &[u8])]| {
+            attributes
+                .iter()
+                .any(|(name, value)| *name == b"mnt-by" && *value == b"B-MNT")
+                && attributes.iter().any(|(name, _)| *name == b"origin")
+        });
+        let mut parser = RpslParser::new(callbacks);
+        parser.parse(&input[..]).unwrap();
+        let writer = parser.into_callbacks().into_inner().into_result().unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "route: 198.51.100.0/24\norigin: AS2\nmnt-by: B-MNT\n"
+        );
+    }
+
+    #[test]
+    fn test_object_filter_comments() {
+        #[derive(Default)]
+        struct Recorder(Vec<String>);
+
+        impl Callbacks for Recorder {
+            fn start_object(&mut self) {
+                self.0.push("start".into());
+            }
+
+            fn attribute(&mut self, name: &[u8], _value: &[u8]) {
+                self.0.push(String::from_utf8_lossy(name).into_owned());
+            }
+
+            fn end_object(&mut self) {
+                self.0.push("end".into());
+            }
+
+            fn comment_at(&mut self, line_number: u32, _name: Option<&[u8]>, comment: &[u8]) {
+                self.0.push(format!(
+                    "{line_number}:{}",
+                    String::from_utf8_lossy(comment)
+                ));
+            }
+        }
+
+        let input = b"% about A\nroute: 192.0.2.0/24\norigin: AS1 # first\n% inside A\n\n\
+            % about B\nroute: 198.51.100.0/24\norigin: AS2 # trailing\n";
+        let callbacks =
+            ObjectFilter::new(Recorder::default(), |attributes: &[(&[u8], &[u8])]| {
+                attributes.contains(&(b"route", b"192.0.2.0/24"))
+            });
+        let mut parser = RpslParser::new(callbacks);
+        parser.parse(&input[..]).unwrap();
+
+        assert_eq!(
+            parser.into_callbacks().into_inner().0,
+            [
+                "1:% about A",
+                "start",
+                "route",
+                "3:# first",
+                "origin",
+                "4:% inside A",
+                "end"
+            ]
+        );
+    }
+}
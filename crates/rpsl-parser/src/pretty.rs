@@ -0,0 +1,177 @@
+use std::io::{self, Write};
+
+use crate::TryCallbacks;
+
+/// Callbacks that write each object as human-readable, aligned `name : value` lines
+///
+/// Attributes are buffered until the end of each object so that the colons can be
+/// aligned to the longest attribute name of that object. Values containing newlines,
+/// or longer than the configured width, continue on following lines indented to the
+/// value column. Objects are separated by a blank line.
+///
+/// Writing can fail, so this implements [`TryCallbacks`]; wrap it in
+/// [`FirstError`](crate::FirstError) to use it with [`RpslParser`](crate::RpslParser).
+pub struct PrettyPrinter<W> {
+    writer: W,
+    width: Option<usize>,
+    attributes: Vec<(Vec<u8>, Vec<u8>)>,
+    objects: usize,
+}
+
+impl<W: Write> PrettyPrinter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            width: None,
+            attributes: Vec::new(),
+            objects: 0,
+        }
+    }
+
+    /// Wrap values at whitespace so that lines are at most `width` bytes wide, where
+    /// possible
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_value(&mut self, value: &[u8], indent: usize) -> io::Result<()> {
+        let room = self.width.map(|width| width.saturating_sub(indent).max(1));
+        let mut first = true;
+
+        for line in value.split(|&b| b == b'\n') {
+            for chunk in wrap(line, room) {
+                if !first {
+                    write!(self.writer, "\n{:indent$}", "")?;
+                }
+                self.writer.write_all(chunk)?;
+                first = false;
+            }
+        }
+
+        writeln!(self.writer)
+    }
+}
+
+impl<W: Write> TryCallbacks for PrettyPrinter<W> {
+    type Error = io::Error;
+
+    fn start_object(&mut self) -> io::Result<()> {
+        self.attributes.clear();
+        Ok(())
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> io::Result<()> {
+        self.attributes.push((name.to_vec(), value.to_vec()));
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> io::Result<()> {
+        if self.objects > 0 {
+            writeln!(self.writer)?;
+        }
+        self.objects += 1;
+
+        let attributes = std::mem::take(&mut self.attributes);
+        let name_width = attributes
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+
+        for (name, value) in &attributes {
+            self.writer.write_all(name)?;
+            write!(self.writer, "{:pad$} : ", "", pad = name_width - name.len())?;
+            self.write_value(value, name_width + 3)?;
+        }
+
+        self.attributes = attributes;
+        Ok(())
+    }
+}
+
+/// Split a line into chunks of at most `room` bytes, breaking at spaces. Words longer
+/// than `room` are kept whole.
+fn wrap(line: &[u8], room: Option<usize>) -> Vec<&[u8]> {
+    let Some(room) = room else {
+        return vec![line];
+    };
+
+    let mut chunks = Vec::new();
+    let mut rest = line;
+
+    while rest.len() > room {
+        let split = rest[..=room]
+            .iter()
+            .rposition(|&b| b == b' ')
+            .or_else(|| rest.iter().position(|&b| b == b' '));
+
+        match split {
+            Some(0) => rest = &rest[1..],
+            Some(n) => {
+                chunks.push(&rest[..n]);
+                rest = &rest[n + 1..];
+            }
+            None => break,
+        }
+    }
+
+    chunks.push(rest);
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FirstError, RpslParser};
+
+    fn pretty_print(printer: PrettyPrinter<Vec<u8>>, input: &[u8]) -> String {
+        let mut parser = RpslParser::new(FirstError::new(printer));
+        parser.parse(input).unwrap();
+        let output = parser.into_callbacks().into_result().unwrap().into_inner();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_pretty_print_aligns_per_object() {
+        let input = b"route: 192.0.2.0/24\norigin: AS65000\nmnt-by: MAINT-AS65000\n\n\
+            as-set: AS-EXAMPLE\nmembers: AS65000\n";
+
+        assert_eq!(
+            pretty_print(PrettyPrinter::new(Vec::new()), input),
+            "route  : 192.0.2.0/24\n\
+             origin : AS65000\n\
+             mnt-by : MAINT-AS65000\n\
+             \n\
+             as-set  : AS-EXAMPLE\n\
+             members : AS65000\n"
+        );
+    }
+
+    #[test]
+    fn test_pretty_print_wraps_values() {
+        let input = b"descr: one two three four\nremarks: short\n";
+
+        assert_eq!(
+            pretty_print(PrettyPrinter::new(Vec::new()).with_width(20), input),
+            "descr   : one two\n          three four\nremarks : short\n"
+        );
+    }
+
+    #[test]
+    fn test_pretty_print_multi_line_value() {
+        let mut printer = PrettyPrinter::new(Vec::new());
+        printer.start_object().unwrap();
+        printer.attribute(b"descr", b"first\nsecond").unwrap();
+        printer.end_object().unwrap();
+
+        assert_eq!(
+            String::from_utf8(printer.into_inner()).unwrap(),
+            "descr : first\n        second\n"
+        );
+    }
+}
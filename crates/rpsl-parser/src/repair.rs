@@ -0,0 +1,132 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{FirstError, ParseError, RpslParser, RpslWriter, TryCallbacks};
+
+/// Normalizations applied by [`repair_rpsl`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepairOptions {
+    /// Remove trailing spaces and tabs from values
+    pub trim_trailing_whitespace: bool,
+
+    /// Write attribute names in lowercase
+    pub lowercase_names: bool,
+
+    /// Column values are aligned to, see [`RpslWriter::with_value_column`]
+    pub value_column: Option<usize>,
+}
+
+impl Default for RepairOptions {
+    fn default() -> Self {
+        Self {
+            trim_trailing_whitespace: true,
+            lowercase_names: true,
+            value_column: None,
+        }
+    }
+}
+
+/// Callbacks that normalize attributes before writing them
+struct Repair<W> {
+    writer: RpslWriter<W>,
+    options: RepairOptions,
+    name: Vec<u8>,
+}
+
+impl<W: Write> TryCallbacks for Repair<W> {
+    type Error = io::Error;
+
+    fn start_object(&mut self) -> io::Result<()> {
+        self.writer.start_object()
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> io::Result<()> {
+        let name = if self.options.lowercase_names {
+            self.name.clear();
+            self.name.extend(name.iter().map(u8::to_ascii_lowercase));
+            &self.name
+        } else {
+            name
+        };
+
+        let value = if self.options.trim_trailing_whitespace {
+            let end = value
+                .iter()
+                .rposition(|&b| b != b' ' && b != b'\t')
+                .map_or(0, |n| n + 1);
+            &value[..end]
+        } else {
+            value
+        };
+
+        self.writer.attribute(name, value)
+    }
+
+    fn end_object(&mut self) -> io::Result<()> {
+        self.writer.end_object()
+    }
+}
+
+/// Read RPSL, normalize it and write it back
+///
+/// Objects are always separated by exactly one blank line, with no trailing blank
+/// lines. As the data is re-emitted from parsed attributes, comments are dropped and
+/// continuation lines are folded into a single line, except in verbatim attributes
+/// such as `certif` (see [`RpslParser::with_verbatim_attributes`]).
+pub fn repair_rpsl<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    options: &RepairOptions,
+) -> Result<(), ParseError> {
+    let mut rpsl_writer = RpslWriter::new(writer);
+    if let Some(column) = options.value_column {
+        rpsl_writer = rpsl_writer.with_value_column(column);
+    }
+
+    let repair = Repair {
+        writer: rpsl_writer,
+        options: *options,
+        name: Vec::new(),
+    };
+    let mut parser = RpslParser::new(FirstError::new(repair));
+    parser.parse(reader)?;
+
+    let mut repair = parser.into_callbacks().into_result()?;
+    repair.writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repair(input: &[u8], options: &RepairOptions) -> String {
+        let mut output = Vec::new();
+        repair_rpsl(input, &mut output, options).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_repair() {
+        let input = b"\n\nRoute: 192.0.2.0/24  \nOrigin:\tAS65000\t\n\n\n\n\
+            % comment\nmnt-by: MAINT-AS65000\n\n\n";
+
+        assert_eq!(
+            repair(input, &RepairOptions::default()),
+            "route: 192.0.2.0/24\norigin: AS65000\n\nmnt-by: MAINT-AS65000\n"
+        );
+    }
+
+    #[test]
+    fn test_repair_is_stable() {
+        let input = b"ROUTE:   192.0.2.0/24 \nremarks: first\n  second  \n\n\n\
+            aut-num: AS65000\n\n";
+        let options = RepairOptions {
+            value_column: Some(16),
+            ..RepairOptions::default()
+        };
+
+        let once = repair(input, &options);
+        let twice = repair(once.as_bytes(), &options);
+        assert_eq!(once, twice);
+    }
+}
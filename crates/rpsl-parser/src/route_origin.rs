@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::{Callbacks, ParseError, RpslParser};
+
+/// Result of auditing the `route`/`route6` objects of a database
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RouteOriginReport {
+    /// Number of `route`/`route6` objects seen
+    pub objects: usize,
+
+    /// Distinct origin ASNs for each (normalized) prefix, in first-seen order
+    pub origins: BTreeMap<String, Vec<u32>>,
+
+    /// Objects with a missing or malformed prefix or origin
+    pub malformed: Vec<MalformedRoute>,
+}
+
+impl RouteOriginReport {
+    /// Prefixes with more than one distinct origin
+    pub fn conflicts(&self) -> impl Iterator<Item = (&str, &[u32])> {
+        self.origins
+            .iter()
+            .filter(|(_, origins)| origins.len() > 1)
+            .map(|(prefix, origins)| (prefix.as_str(), origins.as_slice()))
+    }
+}
+
+/// A `route`/`route6` object that could not be checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedRoute {
+    /// Index of the object among all objects in the input
+    pub object: usize,
+    pub route: Option<String>,
+    pub origin: Option<String>,
+    pub reason: &'static str,
+}
+
+/// Callbacks that collect a [`RouteOriginReport`]
+#[derive(Default)]
+pub struct RouteOriginCollector {
+    report: RouteOriginReport,
+    object: usize,
+    class: Option<Vec<u8>>,
+    route: Option<String>,
+    origins: Vec<String>,
+}
+
+impl RouteOriginCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_report(self) -> RouteOriginReport {
+        self.report
+    }
+
+    fn check(&self) -> Result<(String, u32), &'static str> {
+        let route = self.route.as_deref().ok_or("missing route")?;
+        let prefix = match self.class.as_deref() {
+            Some(b"route") => parse_prefix::<Ipv4Addr>(route, 32),
+            _ => parse_prefix::<Ipv6Addr>(route, 128),
+        }
+        .ok_or("malformed prefix")?;
+
+        let origin = match self.origins.as_slice() {
+            [] => return Err("missing origin"),
+            [origin] => parse_asn(origin).ok_or("malformed origin")?,
+            _ => return Err("multiple origins"),
+        };
+
+        Ok((prefix, origin))
+    }
+}
+
+impl Callbacks for RouteOriginCollector {
+    fn start_object(&mut self) {
+        self.class = None;
+        self.route = None;
+        self.origins.clear();
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        let class = self.class.get_or_insert_with(|| name.to_ascii_lowercase());
+
+        if !matches!(class.as_slice(), b"route" | b"route6") {
+            return;
+        }
+
+        if name.eq_ignore_ascii_case(class) {
+            self.route = Some(String::from_utf8_lossy(value).trim().to_string());
+        } else if name.eq_ignore_ascii_case(b"origin") {
+            self.origins
+                .push(String::from_utf8_lossy(value).trim().to_string());
+        }
+    }
+
+    fn end_object(&mut self) {
+        if matches!(self.class.as_deref(), Some(b"route" | b"route6")) {
+            self.report.objects += 1;
+
+            match self.check() {
+                Ok((prefix, origin)) => {
+                    let origins = self.report.origins.entry(prefix).or_default();
+                    if !origins.contains(&origin) {
+                        origins.push(origin);
+                    }
+                }
+                Err(reason) => self.report.malformed.push(MalformedRoute {
+                    object: self.object,
+                    route: self.route.take(),
+                    origin: self.origins.first().cloned(),
+                    reason,
+                }),
+            }
+        }
+
+        self.object += 1;
+    }
+}
+
+/// Audit the `route`/`route6` objects of a database, tallying the origins of each
+/// prefix and recording malformed objects
+pub fn route_origin_report<R: BufRead>(reader: R) -> Result<RouteOriginReport, ParseError> {
+    let mut parser = RpslParser::new(RouteOriginCollector::new());
+    parser.parse(reader)?;
+    Ok(parser.into_callbacks().into_report())
+}
+
+/// Parse and normalize an `address/length` prefix
+fn parse_prefix<A>(value: &str, max_len: u8) -> Option<String>
+where
+    A: std::str::FromStr + Into<IpAddr>,
+{
+    let (addr, len) = value.split_once('/')?;
+    let addr: IpAddr = addr.trim().parse::<A>().ok()?.into();
+    let len: u8 = len.trim().parse().ok()?;
+    (len <= max_len).then(|| format!("{addr}/{len}"))
+}
+
+/// Parse an `AS<number>` origin
+fn parse_asn(value: &str) -> Option<u32> {
+    let digits = value.get(2..)?;
+    if !value[..2].eq_ignore_ascii_case("as") || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_origin_report() {
+        let input = b"route: 192.0.2.0/24\norigin: AS65000\n\n\
+            route: 192.0.2.0/24\norigin: as65001\n\n\
+            route6: 2001:0db8::/32\norigin: AS65002\n\n\
+            route: 198.51.100.0/33\norigin: AS65003\n\n\
+            route: 203.0.113.0/24\norigin: 65004\n\n\
+            aut-num: AS65000\n";
+        let report = route_origin_report(&input[..]).unwrap();
+
+        assert_eq!(report.objects, 5);
+        assert_eq!(report.origins["192.0.2.0/24"], [65000, 65001]);
+        assert_eq!(report.origins["2001:db8::/32"], [65002]);
+        assert_eq!(
+            report.conflicts().collect::<Vec<_>>(),
+            [("192.0.2.0/24", &[65000, 65001][..])]
+        );
+
+        let reasons: Vec<_> = report
+            .malformed
+            .iter()
+            .map(|m| (m.object, m.reason))
+            .collect();
+        assert_eq!(reasons, [(3, "malformed prefix"), (4, "malformed origin")]);
+    }
+}
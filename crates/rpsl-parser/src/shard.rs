@@ -0,0 +1,273 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::num::NonZero;
+use std::path::Path;
+use std::thread;
+
+use crate::{Callbacks, ParseError, RpslParser, read_line};
+
+/// Split an (uncompressed) RPSL file into up to `n` roughly equal byte ranges that
+/// start and end on object boundaries, for parsing in parallel
+///
+/// Ranges are `(start, end)` offsets with `end` exclusive. They are contiguous
+/// and together cover the whole file, so no bytes are dropped. Each range after
+/// the first starts right after an empty line, at or after its target split
+/// point. Fewer than `n` ranges are returned when the file has too few empty
+/// lines, and none for an empty file. A worker can read its range with
+/// [`Seek::seek`] and [`Read::take`](std::io::Read::take).
+pub fn split_rpsl_offsets<P: AsRef<Path>>(path: P, n: usize) -> io::Result<Vec<(u64, u64)>> {
+    let file = File::open(path)?;
+    let len = file.metadata()?.len();
+    split_offsets(BufReader::new(file), len, n)
+}
+
+fn split_offsets<R: BufRead + Seek>(
+    mut reader: R,
+    len: u64,
+    n: usize,
+) -> io::Result<Vec<(u64, u64)>> {
+    let n = n.max(1) as u64;
+    let mut boundaries = vec![0];
+    let mut line = Vec::new();
+
+    for i in 1..n {
+        let target = len * i / n;
+        let last = *boundaries.last().unwrap();
+        if target <= last {
+            continue;
+        }
+
+        // Skip the rest of the line containing the byte before the target, so
+        // that reading continues at the start of a line
+        reader.seek(SeekFrom::Start(target - 1))?;
+        read_line(&mut reader, &mut line, None)?;
+        let mut pos = target - 1 + line.len() as u64;
+
+        // A whitespace-only line may continue an attribute, so only an empty line
+        // ends an object
+        let boundary = loop {
+            if !read_line(&mut reader, &mut line, None)? {
+                break len;
+            }
+            pos += line.len() as u64;
+            if matches!(line.as_slice(), b"\n" | b"\r\n" | b"\r") {
+                break pos;
+            }
+        };
+
+        if boundary > last && boundary < len {
+            boundaries.push(boundary);
+        }
+    }
+
+    if len > 0 {
+        boundaries.push(len);
+    }
+    Ok(boundaries.windows(2).map(|w| (w[0], w[1])).collect())
+}
+
+/// Parse input that is entirely in memory on all cores, with callbacks from
+/// `make_callbacks` for each chunk, and return the callbacks in input order for the
+/// caller to merge
+///
+/// The input is split into one chunk per available core, each ending right after
+/// an empty line so that no object is split, and the chunks are parsed with
+/// [`RpslParser::parse_slice`] and default options. A whitespace-only line does
+/// not split the input, as it may continue an attribute. Line numbers in spans and
+/// errors count from the start of each chunk, and a stop sentinel only ends its
+/// own chunk. The first error, in input order, is returned.
+pub fn parse_parallel<C, F>(data: &[u8], make_callbacks: F) -> Result<Vec<C>, ParseError>
+where
+    C: Callbacks + Send,
+    F: Fn() -> C + Sync,
+{
+    let workers = thread::available_parallelism().map_or(1, NonZero::get);
+    parse_chunks(&split_slice(data, workers), &make_callbacks)
+}
+
+fn parse_chunks<C, F>(chunks: &[&[u8]], make_callbacks: &F) -> Result<Vec<C>, ParseError>
+where
+    C: Callbacks + Send,
+    F: Fn() -> C + Sync,
+{
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .map(|&chunk| {
+                scope.spawn(move || {
+                    let mut parser = RpslParser::new(make_callbacks());
+                    parser.parse_slice(chunk)?;
+                    Ok(parser.into_callbacks())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    })
+}
+
+/// Split `data` into up to `n` roughly equal chunks that each end right after an
+/// empty line (or at the end of `data`)
+fn split_slice(data: &[u8], n: usize) -> Vec<&[u8]> {
+    let n = n.max(1);
+    let mut chunks = Vec::with_capacity(n);
+    let mut start = 0;
+
+    for i in 1..n {
+        let target = data.len() / n * i;
+        if target <= start {
+            continue;
+        }
+        match next_object_start(data, target) {
+            Some(end) if end < data.len() => {
+                chunks.push(&data[start..end]);
+                start = end;
+            }
+            _ => break,
+        }
+    }
+
+    if start < data.len() {
+        chunks.push(&data[start..]);
+    }
+    chunks
+}
+
+/// Offset right after the first empty line that starts after byte `target - 1`
+fn next_object_start(data: &[u8], target: usize) -> Option<usize> {
+    // Skip the rest of the line holding the byte before the target, so that the
+    // search continues at the start of a line
+    let mut pos = line_end(data, target - 1)?;
+    loop {
+        let end = line_end(data, pos)?;
+        if matches!(&data[pos..end], b"\n" | b"\r\n" | b"\r") {
+            return Some(end);
+        }
+        pos = end;
+    }
+}
+
+/// Offset right after the terminator of the line holding `pos`, if it has one
+fn line_end(data: &[u8], pos: usize) -> Option<usize> {
+    let n = pos + memchr::memchr2(b'\n', b'\r', data.get(pos..)?)?;
+    if data[n] == b'\r' && data.get(n + 1) == Some(&b'\n') {
+        Some(n + 2)
+    } else {
+        Some(n + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObjectStatsCollector;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_split_offsets() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n\nroute: 198.51.100.0/24\n\
+            origin: AS2\n\naut-num: AS1\nremarks: a\n  \n b\n\nroute: 203.0.113.0/24\n";
+        let len = input.len() as u64;
+
+        let ranges = split_offsets(Cursor::new(&input[..]), len, 3).unwrap();
+        assert_eq!(ranges.len(), 3);
+        assert_eq!(ranges[0].0, 0);
+        assert_eq!(ranges.last().unwrap().1, len);
+
+        for n in 2..=5 {
+            let ranges = split_offsets(Cursor::new(&input[..]), len, n).unwrap();
+            for pair in ranges.windows(2) {
+                assert_eq!(pair[0].1, pair[1].0);
+                assert!(input[..pair[1].0 as usize].ends_with(b"\n\n"), "{n}");
+            }
+            for (start, end) in ranges {
+                let mut parser = RpslParser::new(ObjectStatsCollector::new());
+                parser
+                    .parse_slice(&input[start as usize..end as usize])
+                    .unwrap();
+            }
+        }
+
+        let ranges = split_offsets(Cursor::new(&input[..]), len, 100).unwrap();
+        assert_eq!(ranges.len(), 4);
+
+        assert_eq!(split_offsets(Cursor::new(&b""[..]), 0, 4).unwrap(), []);
+    }
+
+    #[test]
+    fn test_split_slice() {
+        let input = b"route: 192.0.2.0/24\nremarks: a\n  \n b\n\n\
+            aut-num: AS1\r\nremarks: \r\n\t\r\n\r\n\
+            route: 198.51.100.0/24\n\n\nroute: 203.0.113.0/24\n";
+        for n in 1..=input.len() {
+            let chunks = split_slice(input, n);
+            assert_eq!(chunks.concat(), input);
+            for chunk in &chunks[..chunks.len() - 1] {
+                assert!(
+                    chunk.ends_with(b"\n\n") || chunk.ends_with(b"\r\n\r\n"),
+                    "{n}: {:?}",
+                    String::from_utf8_lossy(chunk)
+                );
+            }
+        }
+        assert_eq!(split_slice(input, 3).len(), 3);
+        assert_eq!(split_slice(b"", 4), Vec::<&[u8]>::new());
+    }
+
+    #[test]
+    fn test_parse_parallel() {
+        let mut input = Vec::new();
+        for i in 0..1000 {
+            input.extend_from_slice(
+                format!(
+                    "route: 10.{}.{}.0/24\norigin: AS{i}\nremarks: first\n  \n second\n\n",
+                    i / 256,
+                    i % 256
+                )
+                .as_bytes(),
+            );
+            if i % 7 == 0 {
+                input.extend_from_slice(b"% comment\n\n");
+            }
+        }
+
+        let mut parser = RpslParser::new(ObjectStatsCollector::new());
+        parser.parse_slice(&input).unwrap();
+        let serial = parser.into_callbacks().into_stats();
+        assert_eq!(serial.objects, 1000);
+
+        for n in [1, 2, 3, 8, 64] {
+            let chunks = split_slice(&input, n);
+            assert_eq!(chunks.len(), n);
+            let collectors = parse_chunks(&chunks, &ObjectStatsCollector::new).unwrap();
+            let stats: Vec<_> = collectors
+                .into_iter()
+                .map(ObjectStatsCollector::into_stats)
+                .collect();
+            assert_eq!(stats.iter().map(|s| s.objects).sum::<u64>(), serial.objects);
+            assert_eq!(
+                stats.iter().map(|s| s.attributes).sum::<u64>(),
+                serial.attributes
+            );
+        }
+
+        let collectors = parse_parallel(&input, ObjectStatsCollector::new).unwrap();
+        let objects: u64 = collectors.into_iter().map(|c| c.into_stats().objects).sum();
+        assert_eq!(objects, serial.objects);
+
+        // Errors are returned
+        assert!(
+            parse_parallel(
+                b"route: 192.0.2.0/24\n\n stray\n",
+                ObjectStatsCollector::new
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+use std::io::BufRead;
+
+use crate::{Callbacks, ParseError, RpslParser};
+
+/// Number of index bits of [`HyperLogLog`], giving 4096 one-byte registers
+const HLL_BITS: u32 = 12;
+
+/// Number of candidate values tracked per wanted top value, see
+/// [`AttributeStatsCollector`]
+const TOP_CANDIDATES_PER_VALUE: usize = 10;
+
+/// Value statistics of one attribute name, see [`attribute_value_stats`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeStats {
+    /// Attribute name, lowercased
+    pub name: String,
+
+    /// Number of times the attribute occurs
+    pub count: u64,
+
+    /// Estimated number of distinct values
+    pub distinct: u64,
+
+    /// The most common values with their (estimated) counts, most common first
+    pub top_values: Vec<(String, u64)>,
+}
+
+/// Object counts of RPSL data, see [`object_stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjectStats {
+    /// Number of objects
+    pub objects: u64,
+
+    /// Number of attributes in all objects
+    pub attributes: u64,
+
+    /// Number of objects of each class (lowercased), in the order classes were
+    /// first seen
+    pub classes: Vec<(String, u64)>,
+
+    /// Most attributes in any one object
+    pub max_attributes: u64,
+}
+
+/// Approximate distinct counter using a fixed 4 KiB of memory, with a typical
+/// relative error of about 1.6%. Small counts are exact in practice.
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    fn new() -> Self {
+        Self {
+            registers: vec![0; 1 << HLL_BITS],
+        }
+    }
+
+    #[inline]
+    fn insert(&mut self, hash: u64) {
+        let index = (hash >> (64 - HLL_BITS)) as usize;
+        // Position of the first set bit after the index bits, capped by a sentinel
+        let rank = ((hash << HLL_BITS) | (1 << (HLL_BITS - 1))).leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&rank| (-f64::from(rank)).exp2())
+            .sum();
+        let estimate = 0.7213 / (1.0 + 1.079 / m) * m * m / sum;
+
+        // Linear counting is more accurate while many registers are still empty
+        let empty = self.registers.iter().filter(|&&rank| rank == 0).count();
+        if estimate <= 2.5 * m && empty > 0 {
+            (m * (m / empty as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+/// Most common values found with the Space-Saving algorithm
+///
+/// Keeps at most `capacity` candidates. A value not yet tracked replaces the
+/// least common candidate and inherits its count, so counts are upper bounds that
+/// are exact for values that were never evicted.
+struct TopValues {
+    capacity: usize,
+    counts: HashMap<Vec<u8>, u64>,
+}
+
+impl TopValues {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            counts: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    fn insert(&mut self, value: &[u8]) {
+        if let Some(count) = self.counts.get_mut(value) {
+            *count += 1;
+        } else if self.counts.len() < self.capacity {
+            self.counts.insert(value.to_vec(), 1);
+        } else if let Some((evicted, min)) = self
+            .counts
+            .iter()
+            .min_by_key(|(_, count)| **count)
+            .map(|(value, count)| (value.clone(), *count))
+        {
+            self.counts.remove(&evicted);
+            self.counts.insert(value.to_vec(), min + 1);
+        }
+    }
+
+    /// The `k` most common values, ties broken by value
+    fn top(self, k: usize) -> Vec<(String, u64)> {
+        let mut values: Vec<_> = self
+            .counts
+            .into_iter()
+            .map(|(value, count)| (String::from_utf8_lossy(&value).into_owned(), count))
+            .collect();
+        values.sort_unstable_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        values.truncate(k);
+        values
+    }
+}
+
+struct AttributeCounters {
+    name: String,
+    count: u64,
+    distinct: HyperLogLog,
+    top_values: TopValues,
+}
+
+/// Callbacks that collect [`AttributeStats`] for every attribute name
+///
+/// Memory use is bounded per attribute name rather than growing with the number
+/// of distinct values: distinct counts are estimated with HyperLogLog in 4 KiB
+/// (about 1.6% typical error), and the top `top_k` values are picked from
+/// `10 * top_k` candidates with the Space-Saving algorithm. Counts of top values
+/// are exact unless more distinct values than candidates were seen, in which case
+/// they may be overestimated, and rare values may be missing from the top.
+pub struct AttributeStatsCollector {
+    top_k: usize,
+    attributes: Vec<AttributeCounters>,
+    /// Index into `attributes` of each (lowercased) name
+    index: HashMap<Vec<u8>, usize>,
+    hasher: BuildHasherDefault<DefaultHasher>,
+}
+
+impl AttributeStatsCollector {
+    pub fn new(top_k: usize) -> Self {
+        Self {
+            top_k,
+            attributes: Vec::new(),
+            index: HashMap::new(),
+            hasher: BuildHasherDefault::default(),
+        }
+    }
+
+    /// Statistics of each attribute name, in the order names were first seen
+    pub fn into_stats(self) -> Vec<AttributeStats> {
+        let top_k = self.top_k;
+        self.attributes
+            .into_iter()
+            .map(|counters| AttributeStats {
+                name: counters.name,
+                count: counters.count,
+                distinct: counters.distinct.estimate(),
+                top_values: counters.top_values.top(top_k),
+            })
+            .collect()
+    }
+}
+
+impl Callbacks for AttributeStatsCollector {
+    fn start_object(&mut self) {}
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        let index = match self.index.get(name) {
+            Some(&index) => index,
+            None => {
+                let lowercase = name.to_ascii_lowercase();
+                let index = *self.index.entry(lowercase).or_insert_with_key(|name| {
+                    self.attributes.push(AttributeCounters {
+                        name: String::from_utf8_lossy(name).into_owned(),
+                        count: 0,
+                        distinct: HyperLogLog::new(),
+                        top_values: TopValues::new(self.top_k * TOP_CANDIDATES_PER_VALUE),
+                    });
+                    self.attributes.len() - 1
+                });
+                // Also map the name as written, to skip lowercasing next time
+                self.index.insert(name.to_vec(), index);
+                index
+            }
+        };
+
+        let value = value.trim_ascii();
+        let counters = &mut self.attributes[index];
+        counters.count += 1;
+        counters.distinct.insert(self.hasher.hash_one(value));
+        counters.top_values.insert(value);
+    }
+
+    fn end_object(&mut self) {}
+}
+
+/// Callbacks that collect [`ObjectStats`], counting objects by the class passed to
+/// [`Callbacks::start_object_class`]
+#[derive(Default)]
+pub struct ObjectStatsCollector {
+    stats: ObjectStats,
+    /// Index into `stats.classes` of each class, as written and lowercased
+    index: HashMap<Vec<u8>, usize>,
+    /// Number of attributes of the current object
+    attributes: u64,
+}
+
+impl ObjectStatsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_stats(self) -> ObjectStats {
+        self.stats
+    }
+}
+
+impl Callbacks for ObjectStatsCollector {
+    fn start_object(&mut self) {
+        self.stats.objects += 1;
+        self.attributes = 0;
+    }
+
+    fn start_object_class(&mut self, class: &[u8]) {
+        self.start_object();
+
+        let index = match self.index.get(class) {
+            Some(&index) => index,
+            None => {
+                let lowercase = class.to_ascii_lowercase();
+                let classes = &mut self.stats.classes;
+                let index = *self.index.entry(lowercase).or_insert_with_key(|class| {
+                    classes.push((String::from_utf8_lossy(class).into_owned(), 0));
+                    classes.len() - 1
+                });
+                self.index.insert(class.to_vec(), index);
+                index
+            }
+        };
+        self.stats.classes[index].1 += 1;
+    }
+
+    fn attribute(&mut self, _name: &[u8], _value: &[u8]) {
+        self.stats.attributes += 1;
+        self.attributes += 1;
+    }
+
+    fn end_object(&mut self) {
+        self.stats.max_attributes = self.stats.max_attributes.max(self.attributes);
+    }
+}
+
+/// Count objects, attributes and objects per class, see [`ObjectStats`]
+pub fn object_stats<R: BufRead>(reader: R) -> Result<ObjectStats, ParseError> {
+    let mut parser = RpslParser::new(ObjectStatsCollector::new());
+    parser.parse(reader)?;
+    Ok(parser.into_callbacks().into_stats())
+}
+
+/// Count how often each attribute occurs, estimate its number of distinct values
+/// and find its `top_k` most common values, see [`AttributeStatsCollector`] for
+/// the accuracy of the estimates
+pub fn attribute_value_stats<R: BufRead>(
+    reader: R,
+    top_k: usize,
+) -> Result<Vec<AttributeStats>, ParseError> {
+    let mut parser = RpslParser::new(AttributeStatsCollector::new(top_k));
+    parser.parse(reader)?;
+    Ok(parser.into_callbacks().into_stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attribute_value_stats() {
+        let input = b"route: 192.0.2.0/24\norigin: AS1\nmnt-by: A-MNT\n\n\
+            route: 198.51.100.0/24\norigin: AS2\nMNT-BY: A-MNT\nmnt-by: B-MNT\n\n\
+            route: 203.0.113.0/24\norigin: AS1\nmnt-by: A-MNT \n";
+        let stats = attribute_value_stats(&input[..], 1).unwrap();
+
+        let names: Vec<_> = stats.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["route", "origin", "mnt-by"]);
+        assert_eq!((stats[0].count, stats[0].distinct), (3, 3));
+        assert_eq!((stats[1].count, stats[1].distinct), (3, 2));
+        assert_eq!(stats[1].top_values, [("AS1".to_string(), 2)]);
+        assert_eq!((stats[2].count, stats[2].distinct), (4, 2));
+        assert_eq!(stats[2].top_values, [("A-MNT".to_string(), 3)]);
+    }
+
+    #[test]
+    fn test_object_stats() {
+        // The last object ends at the end of input, without a blank line
+        let input = b"route: 192.0.2.0/24\norigin: AS1\n\n\
+            aut-num: AS1\nas-name: X\nimport: from AS2\nexport: to AS2\n\n\
+            Route: 198.51.100.0/24\norigin: AS2\n\n\
+            route: 203.0.113.0/24\norigin: AS3\nremarks: a\n  b";
+        let stats = object_stats(&input[..]).unwrap();
+
+        assert_eq!(
+            stats,
+            ObjectStats {
+                objects: 4,
+                attributes: 11,
+                classes: vec![("route".to_string(), 3), ("aut-num".to_string(), 1)],
+                max_attributes: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_approximate_counts() {
+        let mut distinct = HyperLogLog::new();
+        let hasher = BuildHasherDefault::<DefaultHasher>::default();
+        for i in 0..100_000u32 {
+            distinct.insert(hasher.hash_one(i));
+        }
+        let estimate = distinct.estimate() as f64;
+        assert!((estimate - 100_000.0).abs() < 5_000.0, "{estimate}");
+
+        // A frequent value stays on top however many rare values pass through
+        let mut top = TopValues::new(4);
+        for i in 0..1000u32 {
+            top.insert(b"common");
+            top.insert(i.to_string().as_bytes());
+        }
+        let top = top.top(1);
+        assert_eq!(top[0].0, "common");
+        assert!(top[0].1 >= 1000);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{AttributeSpan, Callbacks, ErrorAction, ParseError};
+
+/// Callbacks combinator that forwards every event to two callbacks, in order
+pub struct Tee<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Callbacks, B: Callbacks> Tee<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: Callbacks, B: Callbacks> Callbacks for Tee<A, B> {
+    fn start_object(&mut self) {
+        self.first.start_object();
+        self.second.start_object();
+    }
+
+    fn start_object_class(&mut self, class: &[u8]) {
+        self.first.start_object_class(class);
+        self.second.start_object_class(class);
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) {
+        self.first.attribute(name, value);
+        self.second.attribute(name, value);
+    }
+
+    fn attribute_at(&mut self, span: AttributeSpan, name: &[u8], value: &[u8]) {
+        self.first.attribute_at(span, name, value);
+        self.second.attribute_at(span, name, value);
+    }
+
+    fn end_object(&mut self) {
+        self.first.end_object();
+        self.second.end_object();
+    }
+
+    fn discard_object(&mut self) {
+        self.first.discard_object();
+        self.second.discard_object();
+    }
+
+    fn side_line(&mut self, line: &[u8]) {
+        self.first.side_line(line);
+        self.second.side_line(line);
+    }
+
+    fn comment_at(&mut self, line_number: u32, name: Option<&[u8]>, comment: &[u8]) {
+        self.first.comment_at(line_number, name, comment);
+        self.second.comment_at(line_number, name, comment);
+    }
+
+    fn raw_line(&mut self, name: &[u8], line: &[u8]) {
+        self.first.raw_line(name, line);
+        self.second.raw_line(name, line);
+    }
+
+    fn sub_attribute(&mut self, parent: &[u8], key: &[u8], value: &[u8]) {
+        self.first.sub_attribute(parent, key, value);
+        self.second.sub_attribute(parent, key, value);
+    }
+
+    /// Skips only if both callbacks skip
+    fn on_error(&mut self, err: &ParseError) -> ErrorAction {
+        match (self.first.on_error(err), self.second.on_error(err)) {
+            (ErrorAction::Skip, ErrorAction::Skip) => ErrorAction::Skip,
+            _ => ErrorAction::Abort,
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use crate::Callbacks;
+
+/// Maximum number of attribute names in a [`TemplateValidator`] vocabulary
+pub const MAX_VOCABULARY: usize = 64;
+
+/// Result of checking one object against the template for its class
+///
+/// Attribute sets are presence masks: bit `i` stands for the `i`th name of the
+/// validator's vocabulary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectCheck<'a> {
+    /// Index of the object among all objects in the input
+    pub object: usize,
+
+    /// Class of the object (its first attribute name, lowercased)
+    pub class: &'a [u8],
+
+    /// Vocabulary attributes present in the object
+    pub present: u64,
+
+    /// Whether the object has attributes outside the vocabulary
+    pub overflow: bool,
+
+    /// Whether there is a template for the class. If not, `missing` and
+    /// `not_allowed` are empty.
+    pub has_template: bool,
+
+    /// Mandatory attributes of the template that are absent
+    pub missing: u64,
+
+    /// Present attributes that the template does not allow
+    pub not_allowed: u64,
+}
+
+impl ObjectCheck<'_> {
+    /// Whether the object has a template and matches it, with no attributes outside
+    /// the vocabulary
+    pub fn is_valid(&self) -> bool {
+        self.has_template && !self.overflow && self.missing == 0 && self.not_allowed == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Template {
+    mandatory: u64,
+    allowed: u64,
+}
+
+/// Callbacks that check which attributes each object has against per-class templates
+///
+/// Attribute names are mapped to bits of a fixed, ordered vocabulary, so each object
+/// is checked with a few bit operations. The result for every object is passed to
+/// `on_object`. Names are matched case-insensitively.
+pub struct TemplateValidator<F> {
+    vocabulary: Vec<String>,
+    bits: HashMap<Vec<u8>, u32>,
+    templates: HashMap<Vec<u8>, Template>,
+    on_object: F,
+    object: usize,
+    class: Option<Vec<u8>>,
+    present: u64,
+    overflow: bool,
+    name_buf: Vec<u8>,
+}
+
+impl<F: FnMut(&ObjectCheck)> TemplateValidator<F> {
+    /// Create a validator for the given attribute vocabulary
+    ///
+    /// # Panics
+    ///
+    /// If the vocabulary has more than [`MAX_VOCABULARY`] names.
+    pub fn new(vocabulary: &[&str], on_object: F) -> Self {
+        assert!(
+            vocabulary.len() <= MAX_VOCABULARY,
+            "vocabulary has more than {MAX_VOCABULARY} names"
+        );
+
+        Self {
+            vocabulary: vocabulary.iter().map(|name| name.to_string()).collect(),
+            bits: vocabulary
+                .iter()
+                .enumerate()
+                .map(|(bit, name)| (name.as_bytes().to_ascii_lowercase(), bit as u32))
+                .collect(),
+            templates: HashMap::new(),
+            on_object,
+            object: 0,
+            class: None,
+            present: 0,
+            overflow: false,
+            name_buf: Vec::new(),
+        }
+    }
+
+    /// Add the template for a class
+    ///
+    /// # Panics
+    ///
+    /// If an attribute name is not in the vocabulary.
+    pub fn with_template(mut self, class: &str, mandatory: &[&str], optional: &[&str]) -> Self {
+        let mandatory = self.mask(mandatory);
+        let optional = self.mask(optional);
+        self.templates.insert(
+            class.as_bytes().to_ascii_lowercase(),
+            Template {
+                mandatory,
+                allowed: mandatory | optional,
+            },
+        );
+        self
+    }
+
+    /// Presence mask for the given attribute names
+    ///
+    /// # Panics
+    ///
+    /// If an attribute name is not in the vocabulary.
+    pub fn mask(&self, names: &[&str]) -> u64 {
+        names.iter().fold(0, |mask, name| {
+            let bit = self
+                .bits
+                .get(&name.as_bytes().to_ascii_lowercase())
+                .unwrap_or_else(|| panic!("attribute '{name}' is not in the vocabulary"));
+            mask | (1 << bit)
+        })
+    }
+
+    /// Attribute names of the bits set in a presence mask, in vocabulary order
+    pub fn names(&self, mask: u64) -> Vec<&str> {
+        self.vocabulary
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| mask & (1 << bit) != 0)
+            .map(|(_, name)| name.as_str())
+            .collect()
+    }
+}
+
+impl<F: FnMut(&ObjectCheck)> Callbacks for TemplateValidator<F> {
+    fn start_object(&mut self) {
+        self.class = None;
+        self.present = 0;
+        self.overflow = false;
+    }
+
+    fn attribute(&mut self, name: &[u8], _value: &[u8]) {
+        self.name_buf.clear();
+        self.name_buf
+            .extend(name.iter().map(u8::to_ascii_lowercase));
+
+        if self.class.is_none() {
+            self.class = Some(self.name_buf.clone());
+        }
+
+        match self.bits.get(&self.name_buf) {
+            Some(bit) => self.present |= 1 << bit,
+            None => self.overflow = true,
+        }
+    }
+
+    fn end_object(&mut self) {
+        let class = self.class.as_deref().unwrap_or_default();
+        let template = self.templates.get(class);
+
+        let check = ObjectCheck {
+            object: self.object,
+            class,
+            present: self.present,
+            overflow: self.overflow,
+            has_template: template.is_some(),
+            missing: template.map_or(0, |t| t.mandatory & !self.present),
+            not_allowed: template.map_or(0, |t| self.present & !t.allowed),
+        };
+        (self.on_object)(&check);
+
+        self.object += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RpslParser;
+
+    #[test]
+    fn test_template_validator() {
+        let input = b"route: 192.0.2.0/24\norigin: AS65000\nmnt-by: A\nmnt-by: B\n\n\
+            Route: 198.51.100.0/24\nmnt-by: A\nremarks: x\n\n\
+            route: 203.0.113.0/24\norigin: AS65000\nmnt-by: A\nfoo: bar\n\n\
+            aut-num: AS65000\n";
+
+        let mut checks = Vec::new();
+        let validator = TemplateValidator::new(
+            &["route", "origin", "mnt-by", "descr", "remarks", "aut-num"],
+            |check: &ObjectCheck| {
+                checks.push((
+                    String::from_utf8_lossy(check.class).to_string(),
+                    check.is_valid(),
+                    check.overflow,
+                    check.missing,
+                    check.not_allowed,
+                ))
+            },
+        )
+        .with_template("route", &["route", "origin", "mnt-by"], &["descr"]);
+
+        let mut parser = RpslParser::new(validator);
+        parser.parse(&input[..]).unwrap();
+        let validator = parser.into_callbacks();
+
+        assert_eq!(validator.names(0b10010), ["origin", "remarks"]);
+        drop(validator);
+
+        assert_eq!(
+            checks,
+            [
+                ("route".to_string(), true, false, 0, 0),
+                ("route".to_string(), false, false, 0b10, 0b10000),
+                ("route".to_string(), false, true, 0, 0),
+                ("aut-num".to_string(), false, false, 0, 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use std::io::BufRead;
+
+use crate::{Noop, ParseError, RpslParser};
+
+/// Check the syntax of RPSL data, passing each syntax error to `on_error` as soon as
+/// it is found and carrying on with the next line
+///
+/// Only [`ParseError::InvalidSyntax`] is recoverable; I/O errors (and timeouts)
+/// stop validation and are returned. Returns the number of syntax errors found.
+pub fn validate_streaming<R, F>(mut reader: R, mut on_error: F) -> Result<usize, ParseError>
+where
+    R: BufRead,
+    F: FnMut(&ParseError),
+{
+    let mut parser = RpslParser::new(Noop);
+    let mut errors = 0;
+    loop {
+        match parser.parse_object(&mut reader) {
+            Ok(true) => {}
+            Ok(false) => return Ok(errors),
+            Err(err @ ParseError::InvalidSyntax { .. }) => {
+                // The offending line has been consumed, so parsing resumes after it
+                on_error(&err);
+                errors += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_streaming() {
+        let input = b" stray continuation\nroute: 192.0.2.0/24\nno colon here\norigin: AS1\n\n\
+            route: 198.51.100.0/24\n:empty name\n";
+
+        let mut lines = Vec::new();
+        let errors = validate_streaming(&input[..], |err| {
+            if let ParseError::InvalidSyntax { line_number, .. } = err {
+                lines.push(*line_number);
+            }
+        })
+        .unwrap();
+
+        assert_eq!(errors, 3);
+        assert_eq!(lines, [1, 3, 7]);
+    }
+}
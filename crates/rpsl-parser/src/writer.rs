@@ -0,0 +1,378 @@
+use std::io::{self, Write};
+
+use crate::TryCallbacks;
+
+/// Line terminator written by [`RpslWriter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// `\n`, as used on Unix
+    #[default]
+    Lf,
+
+    /// `\r\n`, as used on Windows
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+/// Writes objects as RPSL text
+///
+/// Each attribute is written as a `name: value` line, and objects are separated by a
+/// single blank line, with no blank line after the last object. Lines end with
+/// `\n` unless set otherwise with [`RpslWriter::with_line_ending`]. Values containing
+/// newlines are written as continuation lines starting with a space, or the
+/// character set with [`RpslWriter::with_continuation_char`].
+///
+/// Attributes are written in the order given, duplicates included, so writing the
+/// objects parsed from RPSL text reproduces it apart from comments and whitespace.
+///
+/// As callbacks, attributes are buffered and each object is written when it ends.
+/// Writing can fail, so this implements [`TryCallbacks`]; wrap it in
+/// [`FirstError`](crate::FirstError) to use it with [`RpslParser`](crate::RpslParser).
+pub struct RpslWriter<W> {
+    writer: W,
+    value_column: Option<usize>,
+    sort_by_key: bool,
+    line_ending: LineEnding,
+    continuation_char: u8,
+    attributes: Vec<(Vec<u8>, Vec<u8>)>,
+    pending: Vec<Vec<(Vec<u8>, Vec<u8>)>>,
+    objects: usize,
+}
+
+impl<W: Write> RpslWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            value_column: None,
+            sort_by_key: false,
+            line_ending: LineEnding::Lf,
+            continuation_char: b' ',
+            attributes: Vec::new(),
+            pending: Vec::new(),
+            objects: 0,
+        }
+    }
+
+    /// Pad attribute names so that values start at this (0-based) column, like the
+    /// 16 column layout used by the RIPE database. Longer names are followed by a
+    /// single space.
+    pub fn with_value_column(mut self, column: usize) -> Self {
+        self.value_column = Some(column);
+        self
+    }
+
+    /// Write objects sorted by primary key (the value of their first attribute)
+    /// instead of in input order, so that diffs between generated dumps only show
+    /// actual changes. Objects with the same key keep their input order.
+    ///
+    /// Every object is held in memory until [`RpslWriter::finish`] is called, so
+    /// memory use grows with the size of the whole output.
+    pub fn with_sort_by_key(mut self, sort_by_key: bool) -> Self {
+        self.sort_by_key = sort_by_key;
+        self
+    }
+
+    /// Terminator of every written line, including the blank lines between objects
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// First character of continuation lines: `b' '` (the default), `b'\t'` or
+    /// `b'+'`. With a value column, the character is followed by spaces up to it.
+    ///
+    /// `+` keeps empty lines within a value readable; a line holding only a space
+    /// or tab is easily lost, e.g. by editors stripping trailing whitespace.
+    pub fn with_continuation_char(mut self, continuation_char: u8) -> Self {
+        self.continuation_char = continuation_char;
+        self
+    }
+
+    /// Write the objects held back by [`RpslWriter::with_sort_by_key`], in key
+    /// order, and flush. Does nothing else when not sorting.
+    pub fn finish(&mut self) -> io::Result<()> {
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.sort_by(|a, b| object_key(a).cmp(object_key(b)));
+        for object in &pending {
+            self.write_owned(object)?;
+        }
+        self.flush()
+    }
+
+    /// Number of objects written so far
+    pub fn objects(&self) -> usize {
+        self.objects
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Write one object, or hold it back until [`RpslWriter::finish`] when sorting
+    pub fn write_object(&mut self, attributes: &[(&[u8], &[u8])]) -> io::Result<()> {
+        if self.sort_by_key {
+            self.pending.push(
+                attributes
+                    .iter()
+                    .map(|(name, value)| (name.to_vec(), value.to_vec()))
+                    .collect(),
+            );
+            return Ok(());
+        }
+        self.write_now(attributes)
+    }
+
+    fn write_now(&mut self, attributes: &[(&[u8], &[u8])]) -> io::Result<()> {
+        if self.objects > 0 {
+            self.writer.write_all(self.line_ending.as_bytes())?;
+        }
+        self.objects += 1;
+
+        for (name, value) in attributes {
+            self.write_attribute(name, value)?;
+        }
+        Ok(())
+    }
+
+    fn write_owned(&mut self, attributes: &[(Vec<u8>, Vec<u8>)]) -> io::Result<()> {
+        let borrowed: Vec<(&[u8], &[u8])> = attributes
+            .iter()
+            .map(|(name, value)| (name.as_slice(), value.as_slice()))
+            .collect();
+        self.write_now(&borrowed)
+    }
+
+    fn write_attribute(&mut self, name: &[u8], value: &[u8]) -> io::Result<()> {
+        self.writer.write_all(name)?;
+        self.writer.write_all(b":")?;
+
+        let indent = match self.value_column {
+            Some(column) => column.saturating_sub(name.len() + 1).max(1),
+            None => 1,
+        };
+
+        let mut lines = value.split(|&b| b == b'\n');
+        if let Some(first) = lines.next()
+            && !first.is_empty()
+        {
+            write!(self.writer, "{:indent$}", "")?;
+            self.writer.write_all(first)?;
+        }
+        self.writer.write_all(self.line_ending.as_bytes())?;
+
+        let indent = self.value_column.unwrap_or(1).max(1) - 1;
+        for line in lines {
+            self.writer.write_all(&[self.continuation_char])?;
+            write!(self.writer, "{:indent$}", "")?;
+            self.writer.write_all(line)?;
+            self.writer.write_all(self.line_ending.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> TryCallbacks for RpslWriter<W> {
+    type Error = io::Error;
+
+    fn start_object(&mut self) -> io::Result<()> {
+        self.attributes.clear();
+        Ok(())
+    }
+
+    fn attribute(&mut self, name: &[u8], value: &[u8]) -> io::Result<()> {
+        self.attributes.push((name.to_vec(), value.to_vec()));
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> io::Result<()> {
+        if self.sort_by_key {
+            self.pending.push(std::mem::take(&mut self.attributes));
+            return Ok(());
+        }
+
+        let attributes = std::mem::take(&mut self.attributes);
+        self.write_owned(&attributes)?;
+        self.attributes = attributes;
+        Ok(())
+    }
+}
+
+/// The primary key of an object, i.e. the value of its first attribute
+fn object_key(attributes: &[(Vec<u8>, Vec<u8>)]) -> &[u8] {
+    attributes.first().map_or(&[], |(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContinuationJoin, FirstError, Noop, RpslParser};
+
+    #[test]
+    fn test_write_object() {
+        let mut writer = RpslWriter::new(Vec::new());
+        writer
+            .write_object(&[(b"route", b"192.0.2.0/24"), (b"descr", b"")])
+            .unwrap();
+        writer
+            .write_object(&[(b"remarks", b"first\nsecond")])
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "route: 192.0.2.0/24\ndescr:\n\nremarks: first\n second\n"
+        );
+    }
+
+    #[test]
+    fn test_write_value_column() {
+        let mut writer = RpslWriter::new(Vec::new()).with_value_column(8);
+        writer
+            .write_object(&[(b"route", b"192.0.2.0/24"), (b"remarks", b"a\nb")])
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "route:  192.0.2.0/24\nremarks: a\n        b\n"
+        );
+    }
+
+    #[test]
+    fn test_write_sort_by_key() {
+        let input = "route: 198.51.100.0/24\n\naut-num: AS1\n\nroute: 192.0.2.0/24\n\n\
+            route6: 198.51.100.0/24\n";
+        let writer = RpslWriter::new(Vec::new()).with_sort_by_key(true);
+        let mut parser = RpslParser::new(FirstError::new(writer));
+        parser.parse(input.as_bytes()).unwrap();
+        let mut writer = parser.into_callbacks().into_result().unwrap();
+        assert_eq!(writer.objects(), 0);
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "route: 192.0.2.0/24\n\nroute: 198.51.100.0/24\n\nroute6: 198.51.100.0/24\n\n\
+             aut-num: AS1\n"
+        );
+    }
+
+    #[test]
+    fn test_write_parse_roundtrip() {
+        let input = "route: 192.0.2.0/24\norigin: AS65000\nmnt-by: A\nmnt-by: B\n\n\
+            aut-num: AS65000\nas-name: EXAMPLE\n";
+        let mut parser = RpslParser::new(FirstError::new(RpslWriter::new(Vec::new())));
+        parser.parse(input.as_bytes()).unwrap();
+        let output = parser.into_callbacks().into_result().unwrap().into_inner();
+
+        assert_eq!(String::from_utf8(output).unwrap(), input);
+    }
+
+    #[test]
+    fn test_write_continuation_char() {
+        let input = "route: 192.0.2.0/24\nremarks: first\n  indented\n+\n+ last\n\n\
+            aut-num: AS1\nremarks: a\n\tb\n";
+        let parser = || RpslParser::new(Noop).with_continuation_join(ContinuationJoin::Newline);
+        let objects: Vec<_> = parser()
+            .objects(input.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        for (continuation_char, expected) in [
+            (b' ', "remarks: first\n indented\n \n last\n"),
+            (b'+', "remarks: first\n+indented\n+\n+last\n"),
+            (b'\t', "remarks: first\n\tindented\n\t\n\tlast\n"),
+        ] {
+            let mut writer = RpslWriter::new(Vec::new()).with_continuation_char(continuation_char);
+            for object in &objects {
+                let attributes: Vec<(&[u8], &[u8])> = object
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| (name.as_bytes(), value.as_bytes()))
+                    .collect();
+                writer.write_object(&attributes).unwrap();
+            }
+            let output = String::from_utf8(writer.into_inner()).unwrap();
+            assert!(output.contains(expected), "{output}");
+
+            // Reading the output back gives the same objects
+            let reparsed: Vec<_> = parser()
+                .objects(output.as_bytes())
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(reparsed, objects);
+        }
+    }
+
+    #[test]
+    fn test_write_objects_roundtrip() {
+        let input = "% comment\n\nroute:   192.0.2.0/24 # note\nmnt-by: B-MNT\norigin: AS1\n\
+            mnt-by: A-MNT\nmnt-by: B-MNT\nremarks: a\n  b\n\n\n\
+            aut-num: AS1\nimport: from AS2\nexport: to AS2\nimport: from AS3\n\n\
+            route: 192.0.2.0/24\nmnt-by: C-MNT\n";
+        let normalized = "route: 192.0.2.0/24\nmnt-by: B-MNT\norigin: AS1\n\
+            mnt-by: A-MNT\nmnt-by: B-MNT\nremarks: a b\n\n\
+            aut-num: AS1\nimport: from AS2\nexport: to AS2\nimport: from AS3\n\n\
+            route: 192.0.2.0/24\nmnt-by: C-MNT\n";
+
+        let write = |input: &str| {
+            let mut writer = RpslWriter::new(Vec::new());
+            let parser = RpslParser::new(Noop).with_trim_values(true);
+            for object in parser.objects(input.as_bytes()) {
+                let object = object.unwrap();
+                let attributes: Vec<(&[u8], &[u8])> = object
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| (name.as_bytes(), value.as_bytes()))
+                    .collect();
+                writer.write_object(&attributes).unwrap();
+            }
+            String::from_utf8(writer.into_inner()).unwrap()
+        };
+
+        assert_eq!(write(input), normalized);
+        assert_eq!(write(normalized), normalized);
+    }
+
+    #[test]
+    fn test_write_line_ending() {
+        fn rewrite(input: &[u8], line_ending: LineEnding) -> String {
+            let writer = RpslWriter::new(Vec::new()).with_line_ending(line_ending);
+            let mut parser = RpslParser::new(FirstError::new(writer));
+            parser.parse(input).unwrap();
+            let output = parser.into_callbacks().into_result().unwrap().into_inner();
+            String::from_utf8(output).unwrap()
+        }
+
+        let input = "key-cert: X509-1\ncertif: first\n second\n\naut-num: AS1\n";
+        assert_eq!(rewrite(input.as_bytes(), LineEnding::Lf), input);
+
+        // Reading the output back gives the same objects
+        let crlf = rewrite(input.as_bytes(), LineEnding::CrLf);
+        assert_eq!(crlf, input.replace('\n', "\r\n"));
+        assert_eq!(rewrite(crlf.as_bytes(), LineEnding::Lf), input);
+    }
+
+    #[test]
+    fn test_write_canonical_separators() {
+        let input = "\n\n% header\n\nroute: 192.0.2.0/24\n\n\n\n\naut-num: AS1\n\n\n\
+            as-set: AS-X\n\n\n";
+        let mut parser = RpslParser::new(FirstError::new(RpslWriter::new(Vec::new())));
+        parser.parse(input.as_bytes()).unwrap();
+        let output = parser.into_callbacks().into_result().unwrap().into_inner();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "route: 192.0.2.0/24\n\naut-num: AS1\n\nas-set: AS-X\n"
+        );
+    }
+}
@@ -1,16 +1,39 @@
 from pathlib import Path
-from typing import IO, Union
+from typing import IO, Any, Callable, Dict, List, Optional, Tuple, Union
 
 import polars as pl
 
+SchemaLike = Union[
+    pl.Schema,
+    pl.DataFrame,
+    Dict[str, pl.DataType],
+    List[Tuple[str, Union[str, pl.DataType]]],
+]
+
 # Import the Rust extension
 from polars_rpsl._rpsl_reader import read_rpsl as _read_rpsl_rs
+from polars_rpsl._rpsl_reader import read_rpsl_async as _read_rpsl_async_rs
 from polars_rpsl._rpsl_reader import read_rpsl_bytes as _read_rpsl_bytes_rs
+from polars_rpsl._rpsl_reader import read_rpsl_full as _read_rpsl_full_rs
+from polars_rpsl._rpsl_reader import read_rpsl_full_bytes as _read_rpsl_full_bytes_rs
+from polars_rpsl._rpsl_reader import attribute_value_stats as _attribute_value_stats_rs
+from polars_rpsl._rpsl_reader import map_rpsl as _map_rpsl_rs
+from polars_rpsl._rpsl_reader import read_rpsl_partitioned as _read_rpsl_partitioned_rs
+from polars_rpsl._rpsl_reader import write_rpsl as _write_rpsl_rs
 
 
 def read_rpsl(
     source: Union[str, Path, bytes, "IO[bytes]"],
-    schema: Union[pl.Schema, pl.DataFrame, None] = None,
+    schema: Union[SchemaLike, None] = None,
+    *,
+    tolerate_truncated_gzip: bool = False,
+    empty_as_null: bool = False,
+    line_numbers: bool = False,
+    force_list: Union[List[str], None] = None,
+    limit: Union[int, None] = None,
+    comments: bool = False,
+    classes: Union[List[str], None] = None,
+    encoding: Union[str, None] = None,
 ) -> pl.DataFrame:
     """
     Read RPSL data from a file, bytes, or binary file-like object into a Polars DataFrame.
@@ -22,11 +45,55 @@ def read_rpsl(
         - A file path (str or Path). Gzip-compressed files (.gz) are automatically detected.
         - Raw bytes containing RPSL data.
         - A binary file-like object with a read() method (e.g., open(path, 'rb'), io.BytesIO).
-    schema : pl.Schema, pl.DataFrame, or None, optional
+    schema : pl.Schema, pl.DataFrame, dict, list of tuples, or None, optional
         Schema to use for reading the data. If provided, the data will be read into
-        columns matching the schema. Only pl.String and pl.List(pl.String) types are
-        supported. If None (default), returns a single column with all attributes as
-        a list of structs.
+        columns matching the schema, in schema order. Only pl.String and
+        pl.List(pl.String) types are supported. Can be given as a pl.Schema, an
+        (empty) pl.DataFrame, a dict of column names to dtypes, or a list of
+        ``(name, dtype)`` tuples. In the list form the dtype may also be one of the
+        strings ``"str"``, ``"string"``, ``"utf8"`` (for pl.String) or
+        ``"list[str]"``, ``"list[string]"``, ``"list[utf8]"`` (for
+        pl.List(pl.String)), case-insensitively. If None (default), returns a single
+        column with all attributes as a list of structs.
+    tolerate_truncated_gzip : bool, default False
+        If True, a gzip file that ends unexpectedly (e.g. a truncated download) is
+        read up to the point of truncation instead of raising an error. The last
+        object may be incomplete. Only applies when reading a ``.gz`` file path.
+    empty_as_null : bool, default False
+        Only used with a schema. If True, attributes whose value is empty (or only
+        whitespace) are treated as missing, producing null (or no list element).
+        By default they are kept as present with the value ``""``.
+    line_numbers : bool, default False
+        Only used without a schema. If True, the attribute structs get a third
+        ``line`` field (UInt32) with the line number each attribute starts on.
+    force_list : list of str, optional
+        Only used with a schema. Attributes to read as pl.List(pl.String) columns,
+        so that a single occurrence becomes a one-element list. Overrides a
+        pl.String dtype in the schema; attributes missing from the schema are
+        added as list columns after the schema columns.
+    limit : int, optional
+        Only read the first ``limit`` objects. Parsing stops there, so the rest of
+        the file is not read, which makes exploring large dumps fast. Objects
+        dropped by ``classes`` don't count towards the limit.
+    comments : bool, default False
+        Only used without a schema. If True, add a ``comments`` column of
+        pl.List(pl.String) with, for each object, the comments seen since the
+        previous object: comment-only lines within and before the object, and
+        comments trailing its lines. Comments after the last object are dropped.
+    classes : list of str, optional
+        Only read objects of these classes (the name of their first attribute,
+        matched case-insensitively). Other objects are dropped while parsing,
+        before any columns are built. With a schema, the columns are the schema
+        columns as usual, so attributes of the selected classes that are not in
+        the schema are still left out. Without a schema, the attribute structs
+        only hold attributes of the selected classes. Dropped objects don't count
+        towards ``limit``.
+    encoding : str, optional
+        Encoding of the file, e.g. ``"latin1"`` for legacy dumps, as a WHATWG
+        Encoding Standard label (which reads ``"latin1"`` as its superset
+        windows-1252). The file is decoded to UTF-8 before parsing. By default it
+        is read as UTF-8, with invalid sequences replaced by U+FFFD. Only applies
+        when reading a file path.
 
     Returns
     -------
@@ -53,44 +120,374 @@ def read_rpsl(
     >>> df.schema
     Schema({'aut-num': String, 'mnt-by': List(String)})
 
+    Read with schema given as a list of (name, dtype) tuples:
+
+    >>> df = read_rpsl("data.txt", schema=[("aut-num", "str"), ("mnt-by", "list[str]")])
+
     Read from bytes:
 
     >>> data = b"aut-num: AS123\\nmnt-by: EXAMPLE-MNT\\n\\n"
     >>> df = read_rpsl(data)
 
+    Read only the first 100 objects of a large dump:
+
+    >>> df = read_rpsl("ripe.db.gz", limit=100)
+
+    Read only route objects:
+
+    >>> df = read_rpsl("ripe.db.gz", classes=["route", "route6"])
+
     Read from a binary file-like object:
 
     >>> with open("data.txt", "rb") as f:
     ...     df = read_rpsl(f)
     """
-    # Prepare schema argument
-    schema_arg = None
-    if schema is not None:
-        if isinstance(schema, pl.Schema):
-            # Convert Schema to empty DataFrame
-            schema_arg = pl.DataFrame(schema=schema)
-        elif isinstance(schema, pl.DataFrame):
-            # Use DataFrame's schema directly
-            schema_arg = schema
-        else:
-            raise TypeError(
-                f"schema must be pl.Schema, pl.DataFrame, or None, got {type(schema).__name__}"
-            )
+    schema_arg = _schema_arg(schema)
 
     # Handle different source types
     if isinstance(source, bytes):
-        return _read_rpsl_bytes_rs(source, schema_arg)
+        return _read_rpsl_bytes_rs(
+            source,
+            schema_arg,
+            empty_as_null,
+            line_numbers,
+            force_list,
+            limit,
+            comments,
+            classes,
+        )
     elif hasattr(source, "read"):
-        # It's a file-like object - read all bytes
-        data = source.read()
-        if not isinstance(data, bytes):
-            raise TypeError(
-                f"file-like object must return bytes from read(), got {type(data).__name__}"
-            )
-        return _read_rpsl_bytes_rs(data, schema_arg)
+        return _read_rpsl_bytes_rs(
+            _read_source(source),
+            schema_arg,
+            empty_as_null,
+            line_numbers,
+            force_list,
+            limit,
+            comments,
+            classes,
+        )
     else:
         # Assume it's a path
-        return _read_rpsl_rs(str(source), schema_arg)
+        return _read_rpsl_rs(
+            str(source),
+            schema_arg,
+            tolerate_truncated_gzip,
+            empty_as_null,
+            line_numbers,
+            force_list,
+            limit,
+            comments,
+            classes,
+            encoding,
+        )
+
+
+async def read_rpsl_async(
+    source: Union[str, Path],
+    schema: Union[SchemaLike, None] = None,
+    *,
+    tolerate_truncated_gzip: bool = False,
+    empty_as_null: bool = False,
+    line_numbers: bool = False,
+    force_list: Union[List[str], None] = None,
+    limit: Union[int, None] = None,
+    comments: bool = False,
+    classes: Union[List[str], None] = None,
+    encoding: Union[str, None] = None,
+) -> pl.DataFrame:
+    """
+    Read an RPSL file into a Polars DataFrame without blocking the event loop.
+
+    The file is parsed on a worker thread, without holding the GIL, so other
+    tasks keep running meanwhile. Takes the same parameters as :func:`read_rpsl`,
+    except that the source must be a file path.
+
+    Examples
+    --------
+    >>> df = await read_rpsl_async("ripe.db.route.gz", schema={"route": pl.String})
+    """
+    return await _read_rpsl_async_rs(
+        str(source),
+        _schema_arg(schema),
+        tolerate_truncated_gzip,
+        empty_as_null,
+        line_numbers,
+        force_list,
+        limit,
+        comments,
+        classes,
+        encoding,
+    )
+
+
+def read_rpsl_full(
+    source: Union[str, Path, bytes, "IO[bytes]"],
+    schema: SchemaLike,
+    *,
+    tolerate_truncated_gzip: bool = False,
+    empty_as_null: bool = False,
+    line_numbers: bool = False,
+    force_list: Union[List[str], None] = None,
+) -> pl.DataFrame:
+    """
+    Read RPSL data into schema columns plus the schema-less 'attributes' column, in one pass.
+
+    This is equivalent to reading the source both with and without ``schema`` and
+    concatenating the results horizontally, but only parses the data once.
+
+    Parameters
+    ----------
+    source : str, Path, bytes, or binary file-like object
+        Source of RPSL data, as for :func:`read_rpsl`.
+    schema : pl.Schema, pl.DataFrame, dict, or list of tuples
+        Schema for the typed columns, as for :func:`read_rpsl`. Must not contain
+        an 'attributes' column.
+    tolerate_truncated_gzip : bool, default False
+        See :func:`read_rpsl`.
+    empty_as_null : bool, default False
+        See :func:`read_rpsl`.
+    line_numbers : bool, default False
+        If True, add a ``line`` field to the 'attributes' structs, as for
+        :func:`read_rpsl`.
+    force_list : list of str, optional
+        See :func:`read_rpsl`.
+
+    Returns
+    -------
+    pl.DataFrame
+        DataFrame with one column per schema field, followed by an 'attributes'
+        column with List[Struct{name: String, value: String}].
+
+    Examples
+    --------
+    >>> schema = pl.Schema({'route': pl.String, 'origin': pl.String})
+    >>> df = read_rpsl_full("data.txt", schema=schema)
+    >>> df.columns
+    ['route', 'origin', 'attributes']
+    """
+    schema_arg = _schema_arg(schema)
+    if schema_arg is None:
+        raise TypeError("schema is required")
+
+    if isinstance(source, bytes):
+        return _read_rpsl_full_bytes_rs(
+            source, schema_arg, empty_as_null, line_numbers, force_list
+        )
+    elif hasattr(source, "read"):
+        return _read_rpsl_full_bytes_rs(
+            _read_source(source), schema_arg, empty_as_null, line_numbers, force_list
+        )
+    else:
+        return _read_rpsl_full_rs(
+            str(source),
+            schema_arg,
+            tolerate_truncated_gzip,
+            empty_as_null,
+            line_numbers,
+            force_list,
+        )
+
+
+def map_rpsl(
+    source: Union[str, Path],
+    func: Callable[[Dict[str, List[str]]], Any],
+    *,
+    workers: Union[int, None] = None,
+) -> List[Any]:
+    """
+    Call a function on every object of an RPSL file, parsing in parallel.
+
+    The file is split into ranges of whole objects that are parsed on separate
+    threads without holding the GIL. Each worker takes the GIL only to call
+    ``func``, so calls never run at the same time (unless Python is built
+    without the GIL), and the speedup comes from parsing in parallel. Work that
+    releases the GIL itself, such as many NumPy operations, also runs in
+    parallel. Threads are used rather than processes, so ``func`` does not need
+    to be picklable.
+
+    Parameters
+    ----------
+    source : str or Path
+        RPSL file to read. Gzip-compressed files (``.gz``) are decompressed
+        transparently, but are parsed by a single worker.
+    func : callable
+        Called with each object as a dict mapping attribute names (as written in
+        the file) to the list of their values, in order.
+    workers : int, optional
+        Number of threads to parse with. Defaults to the number of CPUs.
+
+    Returns
+    -------
+    list
+        The return values of ``func``, in the order of the objects in the file.
+
+    If ``func`` raises, the remaining objects are skipped and the exception is
+    raised again.
+
+    Examples
+    --------
+    >>> origins = map_rpsl("ripe.db.route", lambda obj: obj.get("origin", [None])[0])
+    """
+    return _map_rpsl_rs(str(source), func, workers)
+
+
+def read_rpsl_partitioned(
+    source: Union[str, Path],
+    attribute: str,
+    *,
+    schema: Union[SchemaLike, None] = None,
+) -> Dict[Optional[str], pl.DataFrame]:
+    """
+    Read an RPSL file into one DataFrame per value of an attribute.
+
+    Useful to split a file mixing several registries by ``source``, for example.
+    Each frame has the same columns as :func:`read_rpsl` would give with
+    ``schema``.
+
+    Parameters
+    ----------
+    source : str or Path
+        RPSL file to read. Gzip-compressed files (``.gz``) are decompressed
+        transparently.
+    attribute : str
+        Attribute whose value picks the frame of each object, matched
+        case-insensitively. When an object has it several times, the first value
+        is used; the frame still holds every value.
+    schema : pl.Schema, pl.DataFrame, dict, or list of tuples, optional
+        Schema of each frame, as for :func:`read_rpsl`. Reads schema-less if None.
+
+    Returns
+    -------
+    dict
+        Frames keyed on the (trimmed) attribute value, in the order the values are
+        first seen. Objects without the attribute are in the frame keyed ``None``.
+
+    Examples
+    --------
+    >>> frames = read_rpsl_partitioned("irr.db", "source", schema={"route": pl.String})
+    >>> for source, df in frames.items():
+    ...     df.write_parquet(f"{source or 'unknown'}.parquet")
+    """
+    return _read_rpsl_partitioned_rs(str(source), attribute, _schema_arg(schema))
+
+
+def attribute_value_stats(source: Union[str, Path], *, top_k: int = 10) -> pl.DataFrame:
+    """
+    Profile the values of every attribute in an RPSL file.
+
+    Helps to decide which attributes to include in a schema, to index, or to cast
+    to ``pl.Categorical``. Memory use does not grow with the number of distinct
+    values, at the cost of approximate results: distinct counts are estimated
+    with HyperLogLog (typically within about 1.6%, exact for small counts), and
+    top values are picked from ``10 * top_k`` candidates, so their counts may be
+    overestimated when an attribute has more distinct values than that.
+
+    Parameters
+    ----------
+    source : str or Path
+        RPSL file to read. Gzip-compressed files (``.gz``) are decompressed
+        transparently.
+    top_k : int, default 10
+        Number of most common values to report per attribute.
+
+    Returns
+    -------
+    pl.DataFrame
+        One row per attribute name (lowercased), in the order first seen, with
+        columns 'name', 'count' (occurrences), 'distinct' (estimated distinct
+        values) and 'top_values', a list of {value, count} structs with the most
+        common values first.
+
+    Examples
+    --------
+    >>> stats = attribute_value_stats("ripe.db.inetnum.gz", top_k=5)
+    >>> stats.filter(pl.col("distinct") < 100)
+    """
+    rows = [
+        (name, count, distinct, [{"value": v, "count": n} for v, n in top_values])
+        for name, count, distinct, top_values in _attribute_value_stats_rs(str(source), top_k)
+    ]
+    return pl.DataFrame(
+        rows,
+        schema={
+            "name": pl.String,
+            "count": pl.UInt64,
+            "distinct": pl.UInt64,
+            "top_values": pl.List(pl.Struct({"value": pl.String, "count": pl.UInt64})),
+        },
+        orient="row",
+    )
+
+
+def write_rpsl(df: pl.DataFrame, path: Union[str, Path]) -> None:
+    """
+    Write a Polars DataFrame as RPSL text, one object per row.
+
+    Parameters
+    ----------
+    df : pl.DataFrame
+        Data to write. A frame with an 'attributes' column of
+        List[Struct{name: String, value: String}], as returned by :func:`read_rpsl`
+        without a schema (or by :func:`read_rpsl_full`), is written from that
+        column alone; other struct fields such as ``line`` are ignored. Otherwise
+        every column must be pl.String or pl.List(pl.String), and each becomes an
+        attribute named after the column, in column order, with one line per list
+        element.
+    path : str or Path
+        File to write. Output is gzip-compressed if the path ends in ``.gz``.
+
+    Null values and null list elements are omitted, and rows without any
+    attributes are skipped. Values containing newlines are written as
+    continuation lines.
+
+    Examples
+    --------
+    >>> df = read_rpsl("data.txt", schema={"route": pl.String, "origin": pl.String})
+    >>> write_rpsl(df.filter(pl.col("origin") == "AS65000"), "filtered.txt.gz")
+    """
+    _write_rpsl_rs(df, str(path))
+
+
+def _schema_arg(schema: Union[SchemaLike, None]):
+    if schema is None:
+        return None
+    elif isinstance(schema, pl.Schema):
+        # Convert Schema to empty DataFrame
+        return pl.DataFrame(schema=schema)
+    elif isinstance(schema, pl.DataFrame):
+        # Use DataFrame's schema directly
+        return schema
+    elif isinstance(schema, dict):
+        return pl.DataFrame(schema=pl.Schema(schema))
+    elif isinstance(schema, list):
+        if all(isinstance(dtype, str) for _, dtype in schema):
+            # Dtype strings are mapped to Polars types on the Rust side, preserving order
+            return [(str(name), dtype) for name, dtype in schema]
+        return pl.DataFrame(schema=pl.Schema(schema))
+    else:
+        raise TypeError(
+            "schema must be pl.Schema, pl.DataFrame, dict, list of (name, dtype) tuples, "
+            f"or None, got {type(schema).__name__}"
+        )
+
+
+def _read_source(source: "IO[bytes]") -> bytes:
+    # It's a file-like object - read all bytes
+    data = source.read()
+    if not isinstance(data, bytes):
+        raise TypeError(
+            f"file-like object must return bytes from read(), got {type(data).__name__}"
+        )
+    return data
 
 
-__all__ = ["read_rpsl"]
+__all__ = [
+    "attribute_value_stats",
+    "map_rpsl",
+    "read_rpsl",
+    "read_rpsl_async",
+    "read_rpsl_full",
+    "read_rpsl_partitioned",
+    "write_rpsl",
+]
@@ -1,10 +1,20 @@
+import asyncio
+import gzip
 import tempfile
 from pathlib import Path
 
 import polars as pl
 import pytest
 
-from polars_rpsl import read_rpsl
+from polars_rpsl import (
+    attribute_value_stats,
+    map_rpsl,
+    read_rpsl,
+    read_rpsl_async,
+    read_rpsl_full,
+    read_rpsl_partitioned,
+    write_rpsl,
+)
 
 
 def test_read_rpsl():
@@ -243,3 +253,421 @@ descr:          Test
         Path(f.name).unlink()
 
 
+def test_read_truncated_gzip():
+    """Test that a truncated gzip file can be read when tolerated."""
+    import gzip
+
+    content = b"""route:          192.0.2.0/24
+origin:         AS65000
+
+route:          198.51.100.0/24
+origin:         AS65001
+"""
+    with tempfile.NamedTemporaryFile(delete=False, suffix=".gz") as f:
+        # Drop the gzip trailer (CRC32 and size) to simulate a truncated download
+        f.write(gzip.compress(content)[:-8])
+        f.flush()
+
+        with pytest.raises(Exception):
+            read_rpsl(f.name)
+
+        df = read_rpsl(f.name, tolerate_truncated_gzip=True)
+
+        assert df.shape[0] == 2
+
+        Path(f.name).unlink()
+
+
+def test_read_with_encoding():
+    """Test decoding a Latin-1 file."""
+    content = "person:         Jos\u00e9 Garc\u00eda\nnic-hdl:        JG1-TEST\n".encode("latin-1")
+    with tempfile.NamedTemporaryFile(delete=False, suffix=".txt") as f:
+        f.write(content)
+        f.flush()
+
+        df = read_rpsl(f.name, schema={"person": pl.String}, encoding="latin1")
+        assert df["person"].to_list() == ["Jos\u00e9 Garc\u00eda"]
+
+        # Read as UTF-8 by default, so the Latin-1 bytes are invalid
+        df = read_rpsl(f.name, schema={"person": pl.String})
+        assert df["person"].to_list() == ["Jos\ufffd Garc\ufffda"]
+
+        with pytest.raises(Exception):
+            read_rpsl(f.name, encoding="no-such-encoding")
+
+        df = asyncio.run(read_rpsl_async(f.name, schema={"person": pl.String}, encoding="latin1"))
+        assert df["person"].to_list() == ["Jos\u00e9 Garc\u00eda"]
+
+        Path(f.name).unlink()
+
+
+def test_read_full():
+    """Test reading schema columns and the attributes column in one pass."""
+    content = b"""route:          192.0.2.0/24
+origin:         AS65000
+descr:          Example route
+
+route:          198.51.100.0/24
+origin:         AS65001
+"""
+    schema = pl.Schema({"route": pl.String, "origin": pl.String})
+    df = read_rpsl_full(content, schema=schema)
+
+    assert df.columns == ["route", "origin", "attributes"]
+    assert df["route"].to_list() == ["192.0.2.0/24", "198.51.100.0/24"]
+    assert df["origin"].to_list() == ["AS65000", "AS65001"]
+    assert [len(attrs) for attrs in df["attributes"].to_list()] == [3, 2]
+
+
+def test_read_with_schema_whitespace_only_value():
+    """Test that a whitespace-only value is present but empty, unless treated as null."""
+    # Written line by line to keep the trailing whitespace visible
+    content = (
+        b"route:          192.0.2.0/24\n"
+        b"descr:          \n"
+        b"remarks:   \t  \n"
+        b"\n"
+        b"route:          198.51.100.0/24\n"
+    )
+    schema = pl.Schema({
+        "route": pl.String,
+        "descr": pl.String,
+        "remarks": pl.List(pl.String),
+    })
+
+    df = read_rpsl(content, schema=schema)
+    assert df["descr"].to_list() == ["", None]
+    assert df["remarks"].to_list() == [[""], []]
+
+    df = read_rpsl(content, schema=schema, empty_as_null=True)
+    assert df["descr"].to_list() == [None, None]
+    assert df["remarks"].to_list() == [[], []]
+
+
+def test_read_with_schema_from_tuple_list():
+    """Test passing the schema as a list of (name, dtype string) tuples."""
+    content = b"""aut-num:        AS65000
+mnt-by:         MAINT-AS65000
+mnt-by:         RIPE-NCC-END-MNT
+"""
+    df = read_rpsl(content, schema=[("mnt-by", "list[str]"), ("aut-num", "str")])
+
+    assert df.columns == ["mnt-by", "aut-num"]
+    assert df.schema == pl.Schema({"mnt-by": pl.List(pl.String), "aut-num": pl.String})
+    assert df["mnt-by"].to_list() == [["MAINT-AS65000", "RIPE-NCC-END-MNT"]]
+
+
+def test_read_with_schema_from_dict():
+    """Test passing the schema as a dict of Polars dtypes."""
+    content = b"""route:          192.0.2.0/24
+origin:         AS65000
+"""
+    df = read_rpsl(content, schema={"origin": pl.String, "route": pl.String})
+
+    assert df.columns == ["origin", "route"]
+    assert df["route"].to_list() == ["192.0.2.0/24"]
+
+
+def test_read_with_schema_unknown_dtype_string():
+    """Test that an unsupported dtype string raises an error."""
+    with pytest.raises(Exception, match="Unsupported schema type"):
+        read_rpsl(b"route: 192.0.2.0/24\n", schema=[("route", "int")])
+
+
+def test_read_with_line_numbers():
+    """Test the opt-in per-attribute line field in schema-less mode."""
+    content = b"""% comment
+route:          192.0.2.0/24
+descr:          first
+                second
+origin:         AS65000
+
+route:          198.51.100.0/24
+"""
+    df = read_rpsl(content, line_numbers=True)
+
+    assert df.schema == pl.Schema(
+        {
+            "attributes": pl.List(
+                pl.Struct({"name": pl.String, "value": pl.String, "line": pl.UInt32})
+            )
+        }
+    )
+    lines = [[attr["line"] for attr in obj] for obj in df["attributes"].to_list()]
+    assert lines == [[2, 3, 5], [7]]
+
+
+def test_read_with_schema_force_list():
+    """Test that force_list turns single occurrences into one-element lists."""
+    content = b"""aut-num:        AS65000
+mnt-by:         FOO
+admin-c:        EXAMPLE-NIC
+"""
+    schema = pl.Schema({"aut-num": pl.String, "mnt-by": pl.String})
+    df = read_rpsl(content, schema=schema, force_list=["mnt-by", "admin-c"])
+
+    assert df.schema == pl.Schema(
+        {
+            "aut-num": pl.String,
+            "mnt-by": pl.List(pl.String),
+            "admin-c": pl.List(pl.String),
+        }
+    )
+    assert df["mnt-by"].to_list() == [["FOO"]]
+    assert df["admin-c"].to_list() == [["EXAMPLE-NIC"]]
+
+
+def test_schema_object_without_schema_attributes():
+    """Test that objects with none of the schema attributes still produce a row."""
+    content = b"""route:          192.0.2.0/24
+mnt-by:         EXAMPLE-MNT
+
+aut-num:        AS65000
+
+route:          198.51.100.0/24
+"""
+    schema = {"route": pl.String, "mnt-by": pl.List(pl.String)}
+    df = read_rpsl(content, schema=schema)
+    assert df.shape == (3, 2)
+    assert df["route"].to_list() == ["192.0.2.0/24", None, "198.51.100.0/24"]
+    assert df["mnt-by"].to_list() == [["EXAMPLE-MNT"], [], []]
+
+
+def test_read_rpsl_async():
+    """Test that the async read matches the blocking one and reports errors."""
+    content = b"""route:          192.0.2.0/24
+origin:         AS65000
+
+route:          198.51.100.0/24
+origin:         AS65001
+"""
+    with tempfile.NamedTemporaryFile(delete=False, suffix=".txt") as f:
+        f.write(content)
+        path = f.name
+
+    try:
+        schema = {"route": pl.String, "origin": pl.String}
+        df = asyncio.run(read_rpsl_async(path, schema=schema))
+        assert df.equals(read_rpsl(path, schema=schema))
+
+        with pytest.raises(Exception):
+            asyncio.run(read_rpsl_async(path + ".missing"))
+    finally:
+        Path(path).unlink()
+
+
+def test_map_rpsl():
+    """Test that map_rpsl returns results in file order for any number of workers."""
+    content = "".join(
+        f"route:          192.0.2.{i}/32\norigin:         AS{i}\nmnt-by: A\nmnt-by: B\n\n"
+        for i in range(100)
+    ).encode()
+    with tempfile.NamedTemporaryFile(delete=False, suffix=".txt") as f:
+        f.write(content)
+        path = f.name
+
+    try:
+        expected = [f"AS{i}" for i in range(100)]
+        for workers in (1, 4):
+            assert map_rpsl(path, lambda obj: obj["origin"][0], workers=workers) == expected
+
+        objects = map_rpsl(path, lambda obj: obj, workers=2)
+        assert objects[0] == {
+            "route": ["192.0.2.0/32"],
+            "origin": ["AS0"],
+            "mnt-by": ["A", "B"],
+        }
+
+        def fail(obj):
+            raise ValueError(obj["route"][0])
+
+        with pytest.raises(ValueError):
+            map_rpsl(path, fail, workers=4)
+    finally:
+        Path(path).unlink()
+
+
+def test_read_with_limit():
+    """Test that limit returns only the first objects and stops parsing there."""
+    content = b"""route:          192.0.2.0/24
+
+route:          198.51.100.0/24
+
+this line would fail to parse
+"""
+    df = read_rpsl(content, limit=2)
+    assert df.shape[0] == 2
+
+    df = read_rpsl(content, schema={"route": pl.String}, limit=1)
+    assert df["route"].to_list() == ["192.0.2.0/24"]
+
+    with pytest.raises(Exception, match="Expected an attribute"):
+        read_rpsl(content)
+
+
+def test_read_with_classes():
+    """Test that classes drops objects of other classes before building."""
+    content = b"""route:          192.0.2.0/24
+origin:         AS65000
+
+aut-num:        AS65000
+as-name:        EXAMPLE
+
+ROUTE6:         2001:db8::/32
+origin:         AS65000
+"""
+    df = read_rpsl(content, classes=["route", "route6"])
+    assert df.shape[0] == 2
+    names = {a["name"] for attrs in df["attributes"].to_list() for a in attrs}
+    assert "as-name" not in names
+
+    df = read_rpsl(content, schema={"route": pl.String, "origin": pl.String}, classes=["route"])
+    assert df["route"].to_list() == ["192.0.2.0/24"]
+
+    df = read_rpsl(content, classes=["aut-num"], limit=1)
+    assert df.shape[0] == 1
+
+    # Only objects kept by classes count towards limit
+    df = read_rpsl(content, schema={"route": pl.String}, classes=["route6"], limit=1)
+    assert df.shape[0] == 1
+    assert df["route"].to_list() == [None]
+
+
+def test_read_with_comments():
+    """Test the opt-in comments column in schema-less mode."""
+    content = b"""% header
+route:          192.0.2.0/24 # trailing
+origin:         AS65000
+
+route:          198.51.100.0/24
+"""
+    df = read_rpsl(content, comments=True)
+
+    assert df.columns == ["attributes", "comments"]
+    assert df["comments"].to_list() == [["% header", "# trailing"], []]
+
+
+# =============================================================================
+# Writing tests
+# =============================================================================
+
+
+def test_write_rpsl_roundtrip_schemaless():
+    """Test that a schema-less frame is written back attribute by attribute."""
+    content = b"""route:          192.0.2.0/24
+descr:          first
+                second
+origin:         AS65000
+
+route:          198.51.100.0/24
+origin:         AS65001
+"""
+    df = read_rpsl(content, line_numbers=True)
+
+    with tempfile.TemporaryDirectory() as tmp:
+        path = Path(tmp) / "out.txt"
+        write_rpsl(df, path)
+
+        assert path.read_text() == (
+            "route: 192.0.2.0/24\n"
+            "descr: first second\n"
+            "origin: AS65000\n"
+            "\n"
+            "route: 198.51.100.0/24\n"
+            "origin: AS65001\n"
+        )
+
+
+def test_write_rpsl_columns_gzip():
+    """Test writing String and List(String) columns, omitting nulls, to a .gz file."""
+    df = pl.DataFrame(
+        {
+            "aut-num": ["AS65000", "AS65001", None],
+            "mnt-by": [["FOO", "BAR"], [], None],
+            "remarks": [None, "hello", None],
+        }
+    )
+
+    with tempfile.TemporaryDirectory() as tmp:
+        path = Path(tmp) / "out.txt.gz"
+        write_rpsl(df, path)
+
+        with gzip.open(path, "rt") as f:
+            assert f.read() == (
+                "aut-num: AS65000\n"
+                "mnt-by: FOO\n"
+                "mnt-by: BAR\n"
+                "\n"
+                "aut-num: AS65001\n"
+                "remarks: hello\n"
+            )
+
+        assert read_rpsl(path, schema=df.schema)["mnt-by"].to_list() == [
+            ["FOO", "BAR"],
+            [],
+        ]
+
+
+def test_write_rpsl_unsupported_column():
+    """Test that columns that aren't String or List(String) are rejected."""
+    df = pl.DataFrame({"aut-num": ["AS65000"], "count": [1]})
+
+    with tempfile.TemporaryDirectory() as tmp:
+        with pytest.raises(Exception, match="Unsupported schema type"):
+            write_rpsl(df, Path(tmp) / "out.txt")
+
+
+def test_read_rpsl_partitioned():
+    """Test that objects are split by the first value of an attribute."""
+    content = b"""route: 192.0.2.0/24
+source: RIPE
+
+route: 198.51.100.0/24
+source: ARIN
+source: RIPE
+
+route: 203.0.113.0/24
+
+route: 10.0.0.0/8
+SOURCE:  RIPE
+"""
+    with tempfile.NamedTemporaryFile(delete=False, suffix=".txt") as f:
+        f.write(content)
+        path = f.name
+
+    try:
+        frames = read_rpsl_partitioned(path, "source", schema={"route": pl.String})
+        assert list(frames) == ["RIPE", "ARIN", None]
+        assert frames["RIPE"]["route"].to_list() == ["192.0.2.0/24", "10.0.0.0/8"]
+        assert frames["ARIN"]["route"].to_list() == ["198.51.100.0/24"]
+        assert frames[None]["route"].to_list() == ["203.0.113.0/24"]
+
+        schemaless = read_rpsl_partitioned(path, "source")
+        assert schemaless["ARIN"]["attributes"].list.len().to_list() == [3]
+    finally:
+        Path(path).unlink()
+
+
+def test_attribute_value_stats():
+    """Test per-attribute occurrence, distinct and top value counts."""
+    content = b"""route: 192.0.2.0/24
+origin: AS1
+mnt-by: A-MNT
+
+route: 198.51.100.0/24
+origin: AS2
+mnt-by: A-MNT
+mnt-by: B-MNT
+"""
+    with tempfile.NamedTemporaryFile(delete=False, suffix=".txt") as f:
+        f.write(content)
+        path = f.name
+
+    try:
+        stats = attribute_value_stats(path, top_k=1)
+        assert stats["name"].to_list() == ["route", "origin", "mnt-by"]
+        assert stats["count"].to_list() == [2, 2, 3]
+        assert stats["distinct"].to_list() == [2, 2, 2]
+        assert stats["top_values"][2].to_list() == [{"value": "A-MNT", "count": 2}]
+    finally:
+        Path(path).unlink()